@@ -1,19 +1,29 @@
-use crate::components::address_list::{AddressList, AddressListMessage};
+use crate::components::address_list::{AddressList, AddressListMessage, Import};
+use crate::components::results_list::preview_dialog::pretty_raw;
 use crate::components::results_list::{
-    OnlineModeFilter, ResultsList, ResultsListMessage, WhitelistFilter,
+    OnlineModeFilter, RconFilter, ResultsList, ResultsListMessage, WhitelistFilter,
 };
 use crate::components::{address_list, settings};
 use crate::i18n::{self, Language, Tr};
 use crate::styles::{COLOR_THEME, COLOR_THEME_LIGHT};
+use crate::text::MotdLook;
 use futures::StreamExt;
 use futures::channel::{mpsc, oneshot};
 use futures::stream::BoxStream;
 use iced::{Element, Subscription, Task, Theme, window};
+use ipnet::IpNet;
 use once_cell::sync::Lazy;
+use scanner::audit::AdvisoryDb;
+use scanner::geoip::GeoDb;
+use scanner::honeypot::HoneypotConfig;
 use scanner::limits::{Concurrency, Ports, TimeoutMs};
-use scanner::parse::{parse_ip_ranges, parse_ip_ranges_reporting};
-use scanner::types::{ScanConfig, ServerInfo};
+use scanner::parse::{Targets, parse_targets, parse_targets_reporting};
+use scanner::prefixes::{self, Query};
+use scanner::transcript::Recorder;
+use scanner::transport::TokioNet;
+use scanner::types::{LanConfig, ProbeOptions, ScanConfig, ServerInfo};
 use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -26,6 +36,10 @@ static RUNTIME: Lazy<tokio::runtime::Runtime> = Lazy::new(|| {
 
 const REFRESH_TIMER_ID: u8 = 0;
 const THEME_SUB_ID: u8 = 1;
+const OBFUSCATE_TIMER_ID: u8 = 2;
+
+// Java worlds announce every 1.5 s, so this hears each one several times.
+const LAN_LISTEN: Duration = Duration::from_secs(10);
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum ModalKind {
@@ -51,6 +65,8 @@ pub enum Message {
     TimeoutChanged(String),
     ToggleQuery(bool),
     ToggleOnlineModeCheck(bool),
+    ToggleRaknetCheck(bool),
+    ToggleRconCheck(bool),
     OpenModal(ModalKind),
     CloseModal,
     RangesEditorAction(iced::widget::text_editor::Action),
@@ -59,10 +75,39 @@ pub enum Message {
     SystemColorScheme(bool),
     SetLanguage(Language),
     CopyAddress,
+    CopyRaw,
+    ShowRaw(bool),
     CopiedReset,
     ToggleVersionExpand,
     ExportResults,
+    ExportAudit,
+    ImportServersDat,
+    ServersDatImported(Option<String>),
+    ImportTargets,
+    TargetsImported(Option<Import>),
+    AdvisoryDbChanged(String),
+    PrefixDirChanged(String),
+    GeoIpDbChanged(String),
+    // None when the path didn't load
+    GeoIpLoaded {
+        path: String,
+        db: Option<Arc<GeoDb>>,
+    },
+    PrefixesResolved {
+        ranges: Vec<IpNet>,
+        rejected: Vec<String>,
+    },
+    HoneypotMinPortsChanged(String),
+    HoneypotPlayerFactorChanged(String),
+    HoneypotTemplatesChanged(String),
+    ToggleHideHoneypots(bool),
+    ToggleRecordTranscripts(bool),
+    ToggleKeepRaw(bool),
+    TogglePlainMotd(bool),
+    ToggleExportFavicons(bool),
+    ToggleMergeServersDat(bool),
     RefreshTick,
+    ObfuscateTick,
     ServerRefreshed(Option<ServerInfo>),
     AvatarDecoded {
         addr: SocketAddr,
@@ -81,6 +126,33 @@ pub struct ScanSettings {
     pub(crate) bedrock_ports_error: bool,
     pub(crate) query_enabled: bool,
     pub(crate) online_mode_check: bool,
+    pub(crate) raknet_check: bool,
+    pub(crate) rcon_check: bool,
+    // Path to a local advisory JSON file; empty = built-in rules only
+    pub(crate) advisory_db: String,
+    pub(crate) advisory_db_error: bool,
+    // Folder of prefix databases and provider range files that `asn:`,
+    // `country:` and `provider:` lines resolve against
+    pub(crate) prefix_dir: String,
+    // MMDB/CSV file, or a folder of them, for ASN and location; empty = none
+    pub(crate) geoip_db: String,
+    pub(crate) geoip_db_error: bool,
+    pub(crate) honeypot_min_ports: String,
+    pub(crate) honeypot_player_factor: String,
+    // Path to a file of known honeypot status bodies, one per line
+    pub(crate) honeypot_templates: String,
+    pub(crate) honeypot_templates_error: bool,
+    pub(crate) hide_honeypots: bool,
+    // Scans go through a recorder and offer the transcript when done
+    pub(crate) record_transcripts: bool,
+    // Probes keep the raw status for the preview's Raw tab and JSON export
+    pub(crate) keep_raw: bool,
+    // MOTDs without colors, formatting or animation
+    pub(crate) plain_motd: bool,
+    // JSON and NDJSON exports carry each server's favicon data URL
+    pub(crate) export_favicons: bool,
+    // servers.dat exports add to the chosen file instead of replacing it
+    pub(crate) merge_servers_dat: bool,
 }
 
 impl Default for ScanSettings {
@@ -94,6 +166,23 @@ impl Default for ScanSettings {
             bedrock_ports_error: false,
             query_enabled: true,
             online_mode_check: false,
+            raknet_check: false,
+            rcon_check: false,
+            advisory_db: String::new(),
+            advisory_db_error: false,
+            prefix_dir: String::new(),
+            geoip_db: String::new(),
+            geoip_db_error: false,
+            honeypot_min_ports: "5".into(),
+            honeypot_player_factor: "10".into(),
+            honeypot_templates: String::new(),
+            honeypot_templates_error: false,
+            hide_honeypots: false,
+            record_transcripts: false,
+            keep_raw: false,
+            plain_motd: false,
+            export_favicons: false,
+            merge_servers_dat: true,
         }
     }
 }
@@ -105,6 +194,37 @@ impl ScanSettings {
     fn bedrock_ports_parsed(&self) -> Ports {
         Ports::from_input(&self.bedrock_ports)
     }
+    fn timeout(&self) -> Duration {
+        Duration::from_millis(TimeoutMs::from_input(&self.timeout_ms).get())
+    }
+    fn probe_options(&self) -> ProbeOptions {
+        ProbeOptions::builder()
+            .timeout(self.timeout())
+            .query(self.query_enabled)
+            .online_mode_check(self.online_mode_check)
+            .raknet_check(self.raknet_check)
+            .rcon_check(self.rcon_check)
+            .keep_raw(self.keep_raw)
+            .build()
+    }
+    // One matching port says nothing, so the threshold never drops below two.
+    fn honeypot_config(&self) -> HoneypotConfig {
+        HoneypotConfig {
+            min_ports: self
+                .honeypot_min_ports
+                .trim()
+                .parse()
+                .unwrap_or(HoneypotConfig::DEFAULT_MIN_PORTS)
+                .max(2),
+            player_factor: self
+                .honeypot_player_factor
+                .trim()
+                .parse()
+                .unwrap_or(HoneypotConfig::DEFAULT_PLAYER_FACTOR)
+                .max(1),
+            ..Default::default()
+        }
+    }
 }
 
 pub struct McScan {
@@ -123,8 +243,16 @@ pub struct McScan {
     pub(crate) language: Language,
     pub(crate) copied: bool,
     pub(crate) version_expanded: bool,
+    // Preview shows the Raw tab instead of the overview
+    pub(crate) show_raw: bool,
+    // Advances while obfuscated MOTD text is on screen
+    pub(crate) motd_frame: u64,
     pub(crate) refresh_index: usize,
     pub(crate) rejected_ranges: usize,
+    pub(crate) recorder: Option<Arc<Recorder<TokioNet>>>,
+    // GeoIP path last loaded or being loaded, so closing Settings only
+    // rereads the database when it changed
+    geoip_path: Option<String>,
 }
 
 impl McScan {
@@ -133,13 +261,19 @@ impl McScan {
 
         let language = cfg.language.resolve();
         let mut address_list = AddressList::default();
-        address_list.push_ranges(parse_ip_ranges(&cfg.ranges.join("\n")));
+        address_list.push_targets(parse_targets(&cfg.ranges.join("\n")));
+        for saved in cfg.imports {
+            if let Some(import) = crate::export::reread_scan_output(&saved.path) {
+                address_list.push_import(import);
+            }
+        }
+        address_list.set_lan(cfg.lan_discovery);
 
         let theme_pref = cfg.theme;
 
         let is_dark = theme_pref != crate::config::ThemePref::Light;
 
-        let app = Self {
+        let mut app = Self {
             wid: None,
             results: ResultsList::default(),
             address_list,
@@ -152,6 +286,23 @@ impl McScan {
                 bedrock_ports_error: false,
                 query_enabled: cfg.query_enabled,
                 online_mode_check: cfg.online_mode_check,
+                raknet_check: cfg.raknet_check,
+                rcon_check: cfg.rcon_check,
+                advisory_db: cfg.advisory_db,
+                advisory_db_error: false,
+                prefix_dir: cfg.prefix_dir,
+                geoip_db: cfg.geoip_db,
+                geoip_db_error: false,
+                honeypot_min_ports: cfg.honeypot_min_ports,
+                honeypot_player_factor: cfg.honeypot_player_factor,
+                honeypot_templates: cfg.honeypot_templates,
+                honeypot_templates_error: false,
+                hide_honeypots: cfg.hide_honeypots,
+                record_transcripts: cfg.record_transcripts,
+                keep_raw: cfg.keep_raw,
+                plain_motd: cfg.plain_motd,
+                export_favicons: cfg.export_favicons,
+                merge_servers_dat: cfg.merge_servers_dat,
             },
             is_scanning: false,
             scan_id: 0,
@@ -164,12 +315,23 @@ impl McScan {
             language,
             copied: false,
             version_expanded: false,
+            show_raw: false,
+            motd_frame: 0,
             refresh_index: 0,
             rejected_ranges: 0,
+            recorder: None,
+            geoip_path: None,
         };
+        app.reload_advisories();
+        let geoip = app.reload_geoip();
+        app.reload_honeypot();
+        app.results.set_hide_honeypots(app.settings.hide_honeypots);
         (
             app,
-            Task::discard(window::latest()).map(Message::WindowInitialized),
+            Task::batch([
+                geoip,
+                Task::discard(window::latest()).map(Message::WindowInitialized),
+            ]),
         )
     }
 
@@ -179,14 +341,39 @@ impl McScan {
                 .address_list
                 .values()
                 .iter()
-                .map(|n| n.to_string())
+                .map(|n| self.address_list.range_label(n))
+                .chain(self.address_list.patterns().iter().map(|p| p.to_string()))
+                .chain(self.address_list.endpoints().iter().map(|e| e.to_string()))
+                .collect(),
+            imports: self
+                .address_list
+                .imports()
+                .iter()
+                .map(|i| crate::config::SavedImport {
+                    path: i.path.clone(),
+                })
                 .collect(),
+            lan_discovery: self.address_list.lan(),
             java_ports: self.settings.java_ports.clone(),
             bedrock_ports: self.settings.bedrock_ports.clone(),
             concurrency: self.settings.concurrency.clone(),
             timeout_ms: self.settings.timeout_ms.clone(),
             query_enabled: self.settings.query_enabled,
             online_mode_check: self.settings.online_mode_check,
+            raknet_check: self.settings.raknet_check,
+            rcon_check: self.settings.rcon_check,
+            advisory_db: self.settings.advisory_db.clone(),
+            prefix_dir: self.settings.prefix_dir.clone(),
+            geoip_db: self.settings.geoip_db.clone(),
+            honeypot_min_ports: self.settings.honeypot_min_ports.clone(),
+            honeypot_player_factor: self.settings.honeypot_player_factor.clone(),
+            honeypot_templates: self.settings.honeypot_templates.clone(),
+            hide_honeypots: self.settings.hide_honeypots,
+            record_transcripts: self.settings.record_transcripts,
+            keep_raw: self.settings.keep_raw,
+            plain_motd: self.settings.plain_motd,
+            export_favicons: self.settings.export_favicons,
+            merge_servers_dat: self.settings.merge_servers_dat,
             theme: self.theme_pref,
             language: self.language.into(),
         }
@@ -205,7 +392,7 @@ impl McScan {
                 self.settings.java_ports_error = jp.is_empty();
                 self.settings.bedrock_ports_error = bp.is_empty();
 
-                if self.address_list.values().is_empty() {
+                if self.address_list.is_empty() && !self.address_list.lan() {
                     self.ranges_editor = iced::widget::text_editor::Content::new();
                     self.rejected_ranges = 0;
                     self.modal = ModalKind::AddRanges;
@@ -217,15 +404,22 @@ impl McScan {
                 }
 
                 self.results.clear();
+                self.reload_advisories();
+                self.reload_honeypot();
                 let config = self.scan_config();
                 self.total_targets = config.target_count();
+                self.recorder = self
+                    .settings
+                    .record_transcripts
+                    .then(|| Arc::new(Recorder::new(TokioNet, &config.options)));
                 self.scanned_count = 0;
                 self.scan_id += 1;
                 self.is_scanning = true;
                 tracing::info!(
                     targets = self.total_targets,
+                    lan = self.address_list.lan(),
                     concurrency = config.concurrency.get(),
-                    timeout_ms = config.timeout_ms.get(),
+                    timeout_ms = config.options.timeouts.read.as_millis() as u64,
                     "scan started"
                 );
                 self.persist();
@@ -238,6 +432,7 @@ impl McScan {
                     found = self.results.count(),
                     "scan stopped"
                 );
+                self.offer_transcript();
             }
 
             Message::ServerFound(info) => {
@@ -261,10 +456,14 @@ impl McScan {
                     found = self.results.count(),
                     "scan complete"
                 );
+                self.offer_transcript();
             }
 
             Message::AddressList(msg) => {
-                let ranges_changed = matches!(msg, AddressListMessage::RemoveClicked(_));
+                let ranges_changed = matches!(
+                    msg,
+                    AddressListMessage::RemoveClicked(_) | AddressListMessage::ToggleLan(_)
+                );
                 self.address_list.update(msg);
                 if ranges_changed {
                     self.persist();
@@ -276,9 +475,10 @@ impl McScan {
                     self.modal = ModalKind::ServerPreview(addr);
                     self.copied = false;
                     self.version_expanded = false;
+                    self.show_raw = false;
                     if let Some(server) = self.results.get_by_addr(addr) {
-                        let edition = server.edition.clone();
-                        return self.spawn_probe(addr, edition);
+                        let edition = server.edition;
+                        return self.spawn_probe(addr, edition, "preview probe");
                     }
                 }
                 ResultsListMessage::SearchInput(text) => {
@@ -309,8 +509,10 @@ impl McScan {
                 ResultsListMessage::EditionPicked(edition) => self.results.set_edition(edition),
                 ResultsListMessage::OnlineModePicked(mode) => self.results.set_online_mode(mode),
                 ResultsListMessage::WhitelistPicked(wl) => self.results.set_whitelist(wl),
+                ResultsListMessage::RconPicked(rcon) => self.results.set_rcon(rcon),
                 ResultsListMessage::VersionFilter(text) => self.results.set_version_filter(text),
                 ResultsListMessage::PluginFilter(text) => self.results.set_plugin_filter(text),
+                ResultsListMessage::NetworkFilter(text) => self.results.set_network_filter(text),
                 ResultsListMessage::ResetFilters => self.results.reset_filters(),
             },
 
@@ -322,6 +524,30 @@ impl McScan {
                 self.settings.bedrock_ports_error = false;
                 self.settings.bedrock_ports = v;
             }
+            Message::AdvisoryDbChanged(v) => {
+                self.settings.advisory_db_error = false;
+                self.settings.advisory_db = v;
+            }
+            Message::PrefixDirChanged(v) => self.settings.prefix_dir = v,
+            Message::GeoIpDbChanged(v) => {
+                self.settings.geoip_db_error = false;
+                self.settings.geoip_db = v;
+            }
+            Message::HoneypotMinPortsChanged(v) => self.settings.honeypot_min_ports = v,
+            Message::HoneypotPlayerFactorChanged(v) => self.settings.honeypot_player_factor = v,
+            Message::HoneypotTemplatesChanged(v) => {
+                self.settings.honeypot_templates_error = false;
+                self.settings.honeypot_templates = v;
+            }
+            Message::ToggleHideHoneypots(v) => {
+                self.settings.hide_honeypots = v;
+                self.results.set_hide_honeypots(v);
+            }
+            Message::ToggleRecordTranscripts(v) => self.settings.record_transcripts = v,
+            Message::ToggleKeepRaw(v) => self.settings.keep_raw = v,
+            Message::TogglePlainMotd(v) => self.settings.plain_motd = v,
+            Message::ToggleExportFavicons(v) => self.settings.export_favicons = v,
+            Message::ToggleMergeServersDat(v) => self.settings.merge_servers_dat = v,
             Message::ConcurrencyChanged(v) => self.settings.concurrency = v,
             Message::TimeoutChanged(v) => self.settings.timeout_ms = v,
             Message::ToggleQuery(v) => self.settings.query_enabled = v,
@@ -332,6 +558,13 @@ impl McScan {
                     self.results.set_whitelist(WhitelistFilter::Any);
                 }
             }
+            Message::ToggleRaknetCheck(v) => self.settings.raknet_check = v,
+            Message::ToggleRconCheck(v) => {
+                self.settings.rcon_check = v;
+                if !v {
+                    self.results.set_rcon(RconFilter::Any);
+                }
+            }
 
             Message::OpenModal(kind) => {
                 if kind == ModalKind::AddRanges {
@@ -340,19 +573,36 @@ impl McScan {
                 self.modal = kind;
             }
             Message::CloseModal => {
+                let mut task = Task::none();
+                if self.modal == ModalKind::Settings {
+                    self.reload_advisories();
+                    task = self.reload_geoip();
+                    self.reload_honeypot();
+                }
                 self.modal = ModalKind::None;
                 self.copied = false;
+                self.show_raw = false;
                 self.persist();
+                return task;
+            }
+            Message::GeoIpLoaded { path, db } => {
+                // A load for a path since replaced in Settings is stale.
+                if self.geoip_path.as_deref() == Some(path.as_str()) {
+                    self.settings.geoip_db_error = db.is_none();
+                    self.results.set_geoip(db.unwrap_or_default());
+                }
             }
 
             Message::RangesEditorAction(action) => self.ranges_editor.perform(action),
 
             Message::ConfirmAddRanges => {
                 let raw = self.ranges_editor.text();
-                let (ranges, rejected) = parse_ip_ranges_reporting(&raw);
-                self.address_list.push_ranges(ranges);
+                let (mut targets, rejected) = parse_targets_reporting(&raw);
+                let queries = std::mem::take(&mut targets.queries);
+                self.address_list.push_targets(targets);
                 self.rejected_ranges = rejected.len();
-                if rejected.is_empty() {
+                // With queries pending, the dialog stays open until they resolve.
+                if rejected.is_empty() && queries.is_empty() {
                     self.ranges_editor = iced::widget::text_editor::Content::new();
                     self.modal = ModalKind::None;
                 } else {
@@ -360,6 +610,27 @@ impl McScan {
                         iced::widget::text_editor::Content::with_text(&rejected.join("\n"));
                 }
                 self.persist();
+                if !queries.is_empty() {
+                    return self.spawn_resolve_prefixes(queries);
+                }
+            }
+
+            Message::PrefixesResolved { ranges, rejected } => {
+                self.address_list.push_targets(Targets {
+                    ranges,
+                    ..Targets::default()
+                });
+                self.rejected_ranges += rejected.len();
+                let mut lines: Vec<String> =
+                    self.ranges_editor.text().lines().map(Into::into).collect();
+                lines.retain(|l| !l.trim().is_empty());
+                lines.extend(rejected);
+                if lines.is_empty() && self.modal == ModalKind::AddRanges {
+                    self.modal = ModalKind::None;
+                }
+                self.ranges_editor =
+                    iced::widget::text_editor::Content::with_text(&lines.join("\n"));
+                self.persist();
             }
 
             Message::SetThemePref(pref) => {
@@ -383,9 +654,16 @@ impl McScan {
                 self.persist();
             }
 
-            Message::CopyAddress => {
+            Message::CopyAddress | Message::CopyRaw => {
                 if let ModalKind::ServerPreview(addr) = &self.modal {
-                    let s = format!("{}:{}", addr.ip(), addr.port());
+                    let s = if matches!(message, Message::CopyRaw) {
+                        match self.results.get_by_addr(*addr).and_then(pretty_raw) {
+                            Some(raw) => raw,
+                            None => return Task::none(),
+                        }
+                    } else {
+                        format!("{}:{}", addr.ip(), addr.port())
+                    };
                     self.copied = true;
                     let (tx, rx) = oneshot::channel::<()>();
                     std::thread::spawn(move || {
@@ -408,17 +686,70 @@ impl McScan {
                 self.copied = false;
             }
 
+            Message::ShowRaw(v) => {
+                self.show_raw = v;
+                self.copied = false;
+            }
+
             Message::ToggleVersionExpand => {
                 self.version_expanded = !self.version_expanded;
             }
 
             Message::ExportResults => {
                 if self.results.count() > 0 {
-                    let csv = crate::export::to_csv(self.results.items());
-                    RUNTIME.spawn(crate::export::save_dialog(csv));
+                    let items = self.results.items();
+                    let (raw, favicons) = (self.settings.keep_raw, self.settings.export_favicons);
+                    RUNTIME.spawn(crate::export::save_dialog(crate::export::ResultExports {
+                        csv: crate::export::to_csv(items),
+                        json: crate::export::to_json(items, raw, favicons),
+                        ndjson: crate::export::to_ndjson(items, raw, favicons),
+                        servers_dat: scanner::servers_dat::entries(items),
+                        merge_servers_dat: self.settings.merge_servers_dat,
+                    }));
+                }
+            }
+
+            Message::ImportServersDat => {
+                return Task::perform(
+                    crate::export::open_servers_dat(),
+                    Message::ServersDatImported,
+                );
+            }
+
+            Message::ServersDatImported(Some(addrs)) => {
+                let mut text = self.ranges_editor.text();
+                if !text.trim().is_empty() && !text.ends_with('\n') {
+                    text.push('\n');
+                }
+                text.push_str(&addrs);
+                self.ranges_editor = iced::widget::text_editor::Content::with_text(&text);
+            }
+            Message::ServersDatImported(None) => {}
+
+            Message::ImportTargets => {
+                return Task::perform(crate::export::open_scan_output(), Message::TargetsImported);
+            }
+
+            Message::TargetsImported(Some(import)) => {
+                self.address_list.push_import(import);
+                self.modal = ModalKind::None;
+                self.persist();
+            }
+            Message::TargetsImported(None) => {}
+
+            Message::ExportAudit => {
+                if self.results.count() > 0 {
+                    let db = self.results.advisories();
+                    let html = scanner::audit::to_html(self.results.items(), db);
+                    let json = scanner::audit::to_json(self.results.items(), db);
+                    RUNTIME.spawn(crate::export::save_audit_dialog(html, json));
                 }
             }
 
+            Message::ObfuscateTick => {
+                self.motd_frame = self.motd_frame.wrapping_add(1);
+            }
+
             Message::RefreshTick => {
                 let count = self.results.count();
                 if count == 0 {
@@ -427,8 +758,8 @@ impl McScan {
                 let idx = self.refresh_index % count;
                 self.refresh_index = self.refresh_index.wrapping_add(1);
                 let addr = self.results.items()[idx].addr;
-                let edition = self.results.items()[idx].edition.clone();
-                return self.spawn_probe(addr, edition);
+                let edition = self.results.items()[idx].edition;
+                return self.spawn_probe(addr, edition, "refresh");
             }
 
             Message::ServerRefreshed(Some(info)) => {
@@ -461,6 +792,8 @@ impl McScan {
                 ScanKey {
                     id: self.scan_id,
                     config,
+                    lan: self.lan_config().map(Arc::new),
+                    recorder: self.recorder.clone(),
                 },
                 build_scan_stream,
             )
@@ -480,7 +813,21 @@ impl McScan {
             Subscription::none()
         };
 
-        Subscription::batch([scan_sub, refresh_sub, theme_sub])
+        let obfuscate_sub = if !self.settings.plain_motd && self.results.has_obfuscated() {
+            Subscription::run_with(OBFUSCATE_TIMER_ID, obfuscate_timer_stream)
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch([scan_sub, refresh_sub, theme_sub, obfuscate_sub])
+    }
+
+    pub(crate) fn motd_look(&self) -> MotdLook {
+        MotdLook {
+            dark: self.is_dark,
+            styled: !self.settings.plain_motd,
+            frame: self.motd_frame,
+        }
     }
 
     pub fn theme(&self) -> Theme {
@@ -534,21 +881,104 @@ impl McScan {
         stack.into()
     }
 
-    fn spawn_probe(&self, addr: SocketAddr, edition: scanner::types::Edition) -> Task<Message> {
-        let timeout = TimeoutMs::from_input(&self.settings.timeout_ms).get();
-        let query_enabled = self.settings.query_enabled;
-        let online_mode_check = self.settings.online_mode_check;
+    fn reload_advisories(&mut self) {
+        let path = self.settings.advisory_db.trim();
+        if path.is_empty() {
+            self.settings.advisory_db_error = false;
+            self.results.set_advisories(AdvisoryDb::default());
+            return;
+        }
+        match std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|raw| AdvisoryDb::from_json(&raw).map_err(|e| e.to_string()))
+        {
+            Ok(db) => {
+                tracing::info!(path, advisories = db.advisories.len(), "advisory db loaded");
+                self.settings.advisory_db_error = false;
+                self.results.set_advisories(db);
+            }
+            Err(error) => {
+                tracing::warn!(path, %error, "advisory db not loaded");
+                self.settings.advisory_db_error = true;
+                self.results.set_advisories(AdvisoryDb::default());
+            }
+        }
+    }
+
+    // Databases run to hundreds of megabytes, so they load off the UI thread,
+    // and only when the path changed.
+    fn reload_geoip(&mut self) -> Task<Message> {
+        let path = self.settings.geoip_db.trim().to_string();
+        if self.geoip_path.as_ref() == Some(&path) {
+            return Task::none();
+        }
+        self.geoip_path = Some(path.clone());
+        if path.is_empty() {
+            self.settings.geoip_db_error = false;
+            self.results.set_geoip(Arc::default());
+            return Task::none();
+        }
+        let (tx, rx) = oneshot::channel();
+        RUNTIME.spawn_blocking(move || {
+            let db = match GeoDb::load(Path::new(&path)) {
+                Ok(db) => {
+                    tracing::info!(path, "geoip db loaded");
+                    Some(Arc::new(db))
+                }
+                Err(error) => {
+                    tracing::warn!(path, %error, "geoip db not loaded");
+                    None
+                }
+            };
+            let _ = tx.send(Message::GeoIpLoaded { path, db });
+        });
+        Task::perform(async move { rx.await.unwrap_or(Message::NoOp) }, |m| m)
+    }
+
+    fn reload_honeypot(&mut self) {
+        let mut config = self.settings.honeypot_config();
+        let path = self.settings.honeypot_templates.trim();
+        self.settings.honeypot_templates_error = false;
+        if !path.is_empty() {
+            match std::fs::read_to_string(path) {
+                Ok(raw) => {
+                    config.add_templates(&raw);
+                    tracing::info!(
+                        path,
+                        templates = config.templates.len(),
+                        "honeypot templates loaded"
+                    );
+                }
+                Err(error) => {
+                    tracing::warn!(path, %error, "honeypot templates not loaded");
+                    self.settings.honeypot_templates_error = true;
+                }
+            }
+        }
+        self.results.set_honeypot(config, self.scan_config());
+    }
+
+    // Saving compresses the whole transcript, so it happens off the UI thread.
+    fn offer_transcript(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            RUNTIME.spawn(crate::export::save_transcript_dialog(recorder));
+        }
+    }
+
+    // `what` names the trigger in the log when the probe fails.
+    fn spawn_probe(
+        &self,
+        addr: SocketAddr,
+        edition: scanner::types::Edition,
+        what: &'static str,
+    ) -> Task<Message> {
+        let options = self.settings.probe_options();
         let (tx, rx) = oneshot::channel::<Option<ServerInfo>>();
         RUNTIME.spawn(async move {
-            let result = scanner::probe_server(
-                addr,
-                edition,
-                timeout,
-                query_enabled,
-                online_mode_check,
-                None,
-            )
-            .await;
+            let result = scanner::probe_server(addr, edition, &options)
+                .await
+                .map_err(|error| tracing::debug!(%addr, %error, "{what} failed"))
+                .ok();
             let _ = tx.send(result);
         });
         Task::perform(
@@ -557,6 +987,30 @@ impl McScan {
         )
     }
 
+    // Reads the prefix files off the UI thread; queries matching nothing come
+    // back as rejected lines.
+    fn spawn_resolve_prefixes(&self, queries: Vec<Query>) -> Task<Message> {
+        let dir = PathBuf::from(self.settings.prefix_dir.trim());
+        let (tx, rx) = oneshot::channel();
+        RUNTIME.spawn_blocking(move || {
+            let (mut ranges, mut rejected) = (Vec::new(), Vec::new());
+            let found = prefixes::resolve(&queries, &dir).unwrap_or_else(|error| {
+                tracing::warn!(dir = %dir.display(), %error, "prefix dir not readable");
+                vec![Vec::new(); queries.len()]
+            });
+            for (query, nets) in queries.iter().zip(found) {
+                if nets.is_empty() {
+                    rejected.push(query.to_string());
+                } else {
+                    tracing::info!(%query, prefixes = nets.len(), "prefixes resolved");
+                    ranges.extend(nets);
+                }
+            }
+            let _ = tx.send(Message::PrefixesResolved { ranges, rejected });
+        });
+        Task::perform(async move { rx.await.unwrap_or(Message::NoOp) }, |m| m)
+    }
+
     fn spawn_favicon_decode(&self, addr: SocketAddr, favicon: String) -> Task<Message> {
         let (tx, rx) = oneshot::channel();
         RUNTIME.spawn_blocking(move || {
@@ -573,12 +1027,27 @@ impl McScan {
     fn scan_config(&self) -> ScanConfig {
         ScanConfig {
             ranges: self.address_list.values().to_vec(),
+            targets: self.address_list.targets().collect(),
             java_ports: self.settings.java_ports_parsed(),
             bedrock_ports: self.settings.bedrock_ports_parsed(),
+            overrides: self.address_list.overrides().to_vec(),
+            patterns: self.address_list.patterns().to_vec(),
             concurrency: Concurrency::from_input(&self.settings.concurrency),
-            timeout_ms: TimeoutMs::from_input(&self.settings.timeout_ms),
+            // Ranges get the status ping only; previews enrich on open.
+            options: ProbeOptions::builder()
+                .timeout(self.settings.timeout())
+                .keep_raw(self.settings.keep_raw)
+                .build(),
         }
     }
+
+    fn lan_config(&self) -> Option<LanConfig> {
+        self.address_list.lan().then(|| LanConfig {
+            bedrock_ports: self.settings.bedrock_ports_parsed(),
+            listen: LAN_LISTEN,
+            options: self.settings.probe_options(),
+        })
+    }
 }
 
 fn app_bg_style(t: &iced::Theme) -> iced::widget::container::Style {
@@ -593,6 +1062,20 @@ fn app_bg_style(t: &iced::Theme) -> iced::widget::container::Style {
     }
 }
 
+// Roughly the client's rate for re-rolling `§k` glyphs.
+fn obfuscate_timer_stream(_: &u8) -> BoxStream<'static, Message> {
+    let (tx, rx) = mpsc::unbounded();
+    RUNTIME.spawn(async move {
+        loop {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            if tx.unbounded_send(Message::ObfuscateTick).is_err() {
+                break;
+            }
+        }
+    });
+    Box::pin(rx)
+}
+
 fn refresh_timer_stream(_: &u8) -> BoxStream<'static, Message> {
     let (tx, rx) = mpsc::unbounded();
     RUNTIME.spawn(async move {
@@ -617,6 +1100,8 @@ fn system_color_scheme_stream(_: &u8) -> BoxStream<'static, Message> {
 struct ScanKey {
     id: u64,
     config: Arc<ScanConfig>,
+    lan: Option<Arc<LanConfig>>,
+    recorder: Option<Arc<Recorder<TokioNet>>>,
 }
 
 impl std::hash::Hash for ScanKey {
@@ -627,25 +1112,51 @@ impl std::hash::Hash for ScanKey {
 
 fn build_scan_stream(key: &ScanKey) -> BoxStream<'static, Message> {
     let config = key.config.clone();
+    let lan = key.lan.clone();
+    let recorder = key.recorder.clone();
     let (tx, rx) = mpsc::unbounded();
 
     RUNTIME.spawn(async move {
-        let mut stream = Box::pin(scanner::scan(config));
-        let mut scanned = 0usize;
-        while let Some(maybe_info) = stream.next().await {
-            scanned += 1;
-            if let Some(info) = maybe_info
-                && tx.unbounded_send(Message::ServerFound(info)).is_err()
-            {
-                return;
-            }
-            if scanned.is_multiple_of(512)
-                && tx.unbounded_send(Message::ScanProgress(scanned)).is_err()
-            {
-                return;
+        let scan_tx = tx.clone();
+        let ranges = async move {
+            let mut stream = match recorder {
+                Some(recorder) => scanner::transcript::scan_recorded(config, recorder).boxed(),
+                None => scanner::scan(config).boxed(),
+            };
+            let mut scanned = 0usize;
+            while let Some(maybe_info) = stream.next().await {
+                scanned += 1;
+                if let Some(info) = maybe_info
+                    && scan_tx.unbounded_send(Message::ServerFound(info)).is_err()
+                {
+                    return false;
+                }
+                if scanned.is_multiple_of(512)
+                    && scan_tx
+                        .unbounded_send(Message::ScanProgress(scanned))
+                        .is_err()
+                {
+                    return false;
+                }
             }
+            true
+        };
+        let lan_tx = tx.clone();
+        let lan = async move {
+            let Some(lan) = lan else {
+                return true;
+            };
+            let mut stream = Box::pin(scanner::discover_lan(lan));
+            while let Some(info) = stream.next().await {
+                if lan_tx.unbounded_send(Message::ServerFound(info)).is_err() {
+                    return false;
+                }
+            }
+            true
+        };
+        if let (true, true) = futures::join!(ranges, lan) {
+            let _ = tx.unbounded_send(Message::ScanComplete);
         }
-        let _ = tx.unbounded_send(Message::ScanComplete);
     });
 
     Box::pin(rx)
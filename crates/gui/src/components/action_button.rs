@@ -28,7 +28,7 @@ pub fn render(app: &McScan) -> Element<'_, Message> {
         .into();
     }
 
-    let can_scan = !app.address_list.values().is_empty();
+    let can_scan = !app.address_list.is_empty();
     let btn = button(
         container(
             text(tr.scan)
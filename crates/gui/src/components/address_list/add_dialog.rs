@@ -28,6 +28,16 @@ pub fn render(app: &McScan) -> Element<'_, Message> {
         row![
             btn(BtnVariant::Danger(tr.cancel), Message::CloseModal),
             Space::new().width(10),
+            btn(
+                BtnVariant::Secondary(tr.import_servers_dat),
+                Message::ImportServersDat
+            ),
+            Space::new().width(10),
+            btn(
+                BtnVariant::Secondary(tr.import_targets),
+                Message::ImportTargets
+            ),
+            Space::new().width(10),
             btn(BtnVariant::Primary(tr.add), Message::ConfirmAddRanges),
         ],
     ];
@@ -8,19 +8,40 @@ use iced::widget::space::Space;
 use iced::widget::{button, column, container, mouse_area, row, svg, text, text_input};
 use iced::{Alignment, Background, Border, Color, Element, Fill, Padding, Theme};
 use ipnet::IpNet;
-use scanner::types::host_count;
+use scanner::octets::OctetPattern;
+use scanner::parse::{RangeSet, Targets};
+use scanner::types::{Endpoint, PortOverride, range_host_count, taken_over};
+use std::path::PathBuf;
 
 #[derive(Debug, Clone)]
 pub enum AddressListMessage {
     RemoveClicked(usize),
+    ToggleLan(bool),
     HoverEnter(usize),
     HoverExit,
     ScrollChanged(bool),
 }
 
+/// Exact targets read from a scanner's output file, listed under its name.
+/// Only the path is saved; the file is read again at startup.
+#[derive(Debug, Clone)]
+pub struct Import {
+    pub name: String,
+    pub path: PathBuf,
+    pub targets: Vec<Endpoint>,
+}
+
 #[derive(Default)]
 pub struct AddressList {
     values: Vec<IpNet>,
+    // Rows list ranges, patterns, endpoints, then imports, indexed straight through
+    patterns: Vec<OctetPattern>,
+    endpoints: Vec<Endpoint>,
+    imports: Vec<Import>,
+    // Own port sets for some of `values`
+    overrides: Vec<PortOverride>,
+    // Passive LAN discovery alongside (or instead of) the ranges
+    lan: bool,
     hover_index: Option<usize>,
     is_scrollable: bool,
 }
@@ -30,20 +51,122 @@ impl AddressList {
         &self.values
     }
 
-    pub fn push_ranges(&mut self, ranges: Vec<IpNet>) {
-        for r in ranges {
-            if !self.values.contains(&r) {
-                self.values.push(r);
+    pub fn overrides(&self) -> &[PortOverride] {
+        &self.overrides
+    }
+
+    /// A range as typed, with its own ports if it has any.
+    pub fn range_label(&self, net: &IpNet) -> String {
+        match self.overrides.iter().find(|o| o.net == *net) {
+            Some(o) => o.to_string(),
+            None => net.to_string(),
+        }
+    }
+
+    pub fn patterns(&self) -> &[OctetPattern] {
+        &self.patterns
+    }
+
+    pub fn endpoints(&self) -> &[Endpoint] {
+        &self.endpoints
+    }
+
+    pub fn imports(&self) -> &[Import] {
+        &self.imports
+    }
+
+    /// Typed endpoints and every import's, for the scan.
+    pub fn targets(&self) -> impl Iterator<Item = Endpoint> + '_ {
+        let imported = self.imports.iter().flat_map(|i| i.targets.iter());
+        self.endpoints.iter().chain(imported).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len() + self.patterns.len() + self.endpoints.len() + self.imports.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn lan(&self) -> bool {
+        self.lan
+    }
+
+    pub fn set_lan(&mut self, lan: bool) {
+        self.lan = lan;
+    }
+
+    /// Adds `targets`, keeping plain ranges as the fewest non-overlapping
+    /// CIDRs. A range with its own ports stays as typed, and the scan skips its
+    /// hosts in the ranges around it, so each host is probed on one set of ports.
+    pub fn push_targets(&mut self, targets: Targets) {
+        // Re-adding a range with other ports replaces the ones it had, and
+        // re-adding it without any puts it back on the scan's ports.
+        let plain_again: Vec<IpNet> = targets
+            .ranges
+            .iter()
+            .filter(|net| !targets.overrides.iter().any(|o| o.net == **net))
+            .copied()
+            .collect();
+        self.overrides.retain(|old| !plain_again.contains(&old.net));
+        for o in targets.overrides {
+            self.overrides.retain(|old| old.net != o.net);
+            self.overrides.push(o);
+        }
+        let overridden: Vec<IpNet> = self.overrides.iter().map(|o| o.net).collect();
+        let plain: RangeSet = self
+            .values
+            .iter()
+            .chain(&targets.ranges)
+            .filter(|net| !overridden.contains(net))
+            .copied()
+            .collect();
+        self.values = plain
+            .cidrs()
+            .into_iter()
+            .filter(|net| !overridden.contains(net))
+            .collect();
+        self.values.extend(overridden);
+        for p in targets.patterns {
+            if !self.patterns.contains(&p) {
+                self.patterns.push(p);
+            }
+        }
+        for e in targets.endpoints {
+            if !self.endpoints.contains(&e) {
+                self.endpoints.push(e);
             }
         }
     }
 
+    // Importing the same file again replaces its earlier targets.
+    pub fn push_import(&mut self, import: Import) {
+        match self.imports.iter_mut().find(|i| i.path == import.path) {
+            Some(existing) => *existing = import,
+            None => self.imports.push(import),
+        }
+    }
+
     pub fn update(&mut self, message: AddressListMessage) {
         match message {
             AddressListMessage::RemoveClicked(i) => {
-                self.values.remove(i);
+                let ranges = self.values.len();
+                let patterns = ranges + self.patterns.len();
+                let endpoints = patterns + self.endpoints.len();
+                if i < ranges {
+                    let net = self.values.remove(i);
+                    self.overrides.retain(|o| o.net != net);
+                } else if i < patterns {
+                    self.patterns.remove(i - ranges);
+                } else if i < endpoints {
+                    self.endpoints.remove(i - patterns);
+                } else if i < self.len() {
+                    self.imports.remove(i - endpoints);
+                }
                 self.hover_index = None;
             }
+            AddressListMessage::ToggleLan(lan) => self.lan = lan,
             AddressListMessage::HoverEnter(i) => self.hover_index = Some(i),
             AddressListMessage::HoverExit => self.hover_index = None,
             AddressListMessage::ScrollChanged(scrollable) => {
@@ -53,22 +176,53 @@ impl AddressList {
     }
 
     pub fn total_hosts(&self) -> u64 {
-        let total: u128 = self.values.iter().map(host_count).sum();
+        let total: u128 = self
+            .values
+            .iter()
+            .map(|net| self.host_count(net))
+            .sum::<u128>()
+            + self
+                .patterns
+                .iter()
+                .map(OctetPattern::host_count)
+                .sum::<u128>()
+            + self.targets().count() as u128;
         total.min(u64::MAX as u128) as u64
     }
 
+    // The hosts `net` is scanned for, less those a range with its own ports takes.
+    fn host_count(&self, net: &IpNet) -> u128 {
+        range_host_count(net, &taken_over(net, &self.overrides))
+    }
+
     pub fn view(&self) -> Element<'_, AddressListMessage> {
         let trash_handle = crate::components::ui::icons::trash();
 
         // Assume a scrollbar past a panelful, before the user has scrolled.
-        let is_scrollable = self.is_scrollable || self.values.len() > 15;
+        let is_scrollable = self.is_scrollable || self.len() > 15;
 
+        let rows = self
+            .values
+            .iter()
+            .map(|net| (self.range_label(net), self.host_count(net)))
+            .chain(
+                self.patterns
+                    .iter()
+                    .map(|p| (p.to_string(), p.host_count())),
+            )
+            .chain(self.endpoints.iter().map(|e| (e.to_string(), 1)))
+            .chain(
+                self.imports
+                    .iter()
+                    .map(|i| (i.name.clone(), i.targets.len() as u128)),
+            );
         let mut list = column![].spacing(2);
-        for (i, net) in self.values.iter().enumerate() {
+        for (i, (label, count)) in rows.enumerate() {
             let hovered = self.hover_index == Some(i);
             list = list.push(range_row(
                 i,
-                net,
+                label,
+                count,
                 trash_handle.clone(),
                 hovered,
                 is_scrollable,
@@ -83,16 +237,16 @@ impl AddressList {
     }
 }
 
-fn range_row(
+fn range_row<'a>(
     index: usize,
-    net: &IpNet,
+    label: String,
+    count: u128,
     trash_handle: svg::Handle,
     is_hovered: bool,
     is_scrollable: bool,
-) -> Element<'_, AddressListMessage> {
-    let net_str = net.to_string();
+) -> Element<'a, AddressListMessage> {
     let right_side = row![
-        text(format_host_count(host_count(net)))
+        text(format_host_count(count))
             .size(11)
             .font(MONO)
             .style(|t: &Theme| text::Style {
@@ -123,7 +277,7 @@ fn range_row(
     .align_y(Alignment::Center);
 
     let row_content = row![
-        text_input("", &net_str)
+        text_input("", &label)
             .size(13)
             .font(MONO)
             .padding(Padding::ZERO)
@@ -249,3 +403,48 @@ fn trash_btn_style(t: &Theme, status: iced::widget::button::Status) -> iced::wid
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scanner::parse::parse_targets;
+
+    fn list(lines: &[&str]) -> AddressList {
+        let mut list = AddressList::default();
+        for line in lines {
+            list.push_targets(parse_targets(line));
+        }
+        list
+    }
+
+    fn labels(list: &AddressList) -> Vec<String> {
+        list.values().iter().map(|n| list.range_label(n)).collect()
+    }
+
+    #[test]
+    fn overlapping_ranges_collapse() {
+        let list = list(&["10.0.5.0/24", "10.0.0.0/16", "10.1.0.0/16", "10.0.0.1"]);
+        assert_eq!(labels(&list), ["10.0.0.0/15"]);
+        assert_eq!(list.total_hosts(), (1 << 17) - 2);
+    }
+
+    #[test]
+    fn ranges_with_own_ports_keep_every_other_host() {
+        let one = list(&["10.0.0.0/24", "10.0.0.7 java=25570"]);
+        assert_eq!(labels(&one), ["10.0.0.0/24", "10.0.0.7/32 java=25570"]);
+        assert_eq!(one.total_hosts(), 254);
+
+        let half = list(&["10.0.0.0/23", "10.0.1.0/24 java=25570"]);
+        assert_eq!(labels(&half), ["10.0.0.0/23", "10.0.1.0/24 java=25570"]);
+        // 10.0.0.1 through 10.0.1.0 stay plain
+        assert_eq!(half.total_hosts(), 256 + 254);
+    }
+
+    #[test]
+    fn nested_ranges_with_own_ports_count_once() {
+        let list = list(&["10.0.0.0/16 java=1", "10.0.1.0/24 java=2"]);
+        // The /24 takes the 254 hosts it scans out of the /16, which keeps
+        // 10.0.1.0 and 10.0.1.255.
+        assert_eq!(list.total_hosts(), (1 << 16) - 2);
+    }
+}
@@ -25,13 +25,20 @@ pub fn render(app: &McScan) -> Element<'_, Message> {
         })
         .width(Fill);
 
-    let results = app.results.view(app.tr()).map(Message::ResultsList);
+    let results = app
+        .results
+        .view(app.tr(), app.motd_look())
+        .map(Message::ResultsList);
 
     let mut col = column![header].width(Fill).height(Fill);
     if app.results.count() > 0 {
         let toolbar = container(
             app.results
-                .toolbar(app.tr(), app.settings.online_mode_check)
+                .toolbar(
+                    app.tr(),
+                    app.settings.online_mode_check,
+                    app.settings.rcon_check,
+                )
                 .map(Message::ResultsList),
         )
         .padding(Padding {
@@ -103,13 +110,22 @@ fn title_row(app: &McScan) -> Element<'_, Message> {
         } else {
             format!("{} / {} {}", visible, found, tr.found)
         };
-        r = r.push(status(label)).push(btn(
-            BtnVariant::Icon {
-                handle: icons::export(),
-                size: 12.0,
-            },
-            Message::ExportResults,
-        ));
+        r = r
+            .push(status(label))
+            .push(btn(
+                BtnVariant::Icon {
+                    handle: icons::shield(),
+                    size: 12.0,
+                },
+                Message::ExportAudit,
+            ))
+            .push(btn(
+                BtnVariant::Icon {
+                    handle: icons::export(),
+                    size: 12.0,
+                },
+                Message::ExportResults,
+            ));
     }
 
     r.into()
@@ -1,12 +1,15 @@
 use iced::Length::Fixed;
+use iced::widget::container::Style as ContainerStyle;
 use iced::widget::space::Space;
-use iced::widget::{column, image, row, text};
-use iced::{Alignment, Color, Element, Fill, Theme};
+use iced::widget::{column, container, image, rich_text, row, text};
+use iced::{Alignment, Background, Border, Color, Element, Fill, Padding, Theme};
 
 use crate::components::ui::chip;
 use crate::i18n::Tr;
 use crate::styles::{MONO, MONO_SEMIBOLD, SANS, SANS_SEMIBOLD, c, is_dark};
-use crate::text::strip_section_codes;
+use crate::text::{MotdLook, motd_spans, strip_section_codes};
+use scanner::audit::{Finding, Severity};
+use scanner::motd::Motd;
 use scanner::types::ServerInfo;
 
 use super::ResultsListMessage;
@@ -18,11 +21,13 @@ const CARD_CONTENT_HEIGHT: f32 = 58.0;
 pub fn server_card_content<'a>(
     info: &'a ServerInfo,
     favicon: Option<image::Handle>,
+    findings: &[Finding],
     tr: &'static Tr,
+    look: MotdLook,
 ) -> Element<'a, ResultsListMessage> {
     let (name, description) = split_motd(&info.motd);
     let avatar = build_avatar_icon(
-        &name,
+        &name.plain(),
         &info.edition,
         favicon,
         AvatarSize::SMALL,
@@ -34,12 +39,14 @@ pub fn server_card_content<'a>(
             }
         },
     );
-    let ip_port = format!("{}:{}", info.addr.ip(), info.addr.port());
+    let mut ip_port = format!("{}:{}", info.addr.ip(), info.addr.port());
+    if !info.endpoints.is_empty() {
+        ip_port.push_str(&format!(" +{}", info.endpoints.len()));
+    }
 
     let mut left_col = column![
-        text(name)
+        rich_text(motd_spans(&name, SANS_SEMIBOLD, look))
             .size(15)
-            .font(SANS_SEMIBOLD)
             .style(|t: &Theme| text::Style {
                 color: Some(if is_dark(t) {
                     c("#E8EBF0")
@@ -52,9 +59,8 @@ pub fn server_card_content<'a>(
 
     if !description.is_empty() {
         left_col = left_col.push(
-            text(description)
+            rich_text(motd_spans(&description, SANS, look))
                 .size(12)
-                .font(SANS)
                 .style(|t: &Theme| text::Style {
                     color: Some(if is_dark(t) {
                         c("#A2ABBA")
@@ -66,7 +72,7 @@ pub fn server_card_content<'a>(
         );
     }
 
-    left_col = left_col.push(
+    let mut addr_row = row![
         text(ip_port)
             .size(12)
             .font(MONO)
@@ -78,7 +84,22 @@ pub fn server_card_content<'a>(
                 }),
             })
             .wrapping(text::Wrapping::None),
-    );
+    ]
+    .spacing(8)
+    .align_y(Alignment::Center);
+    if let Some(worst) = findings.first() {
+        addr_row = addr_row.push(findings_badge(findings.len(), worst.severity));
+    }
+    if !info.honeypot.is_empty() {
+        addr_row = addr_row.push(badge(tr.honeypot_badge.to_string(), |t: &Theme| {
+            if is_dark(t) {
+                c("#B07CE8")
+            } else {
+                c("#7B3FC4")
+            }
+        }));
+    }
+    left_col = left_col.push(addr_row);
 
     let left_block = left_col.spacing(3).width(Fill).clip(true);
 
@@ -99,6 +120,47 @@ pub fn server_card_content<'a>(
         .into()
 }
 
+// Findings come sorted, so the first one sets the color.
+fn findings_badge(count: usize, worst: Severity) -> Element<'static, ResultsListMessage> {
+    let color = move |t: &Theme| match (worst, is_dark(t)) {
+        (Severity::Critical | Severity::High, true) => c("#E5604D"),
+        (Severity::Critical | Severity::High, false) => c("#CC3A28"),
+        (Severity::Medium, true) => c("#E5A24D"),
+        (Severity::Medium, false) => c("#B36A16"),
+        (_, true) => c("#6B7480"),
+        (_, false) => c("#8A929E"),
+    };
+    badge(format!("\u{26A0} {count}"), color)
+}
+
+fn badge(
+    label: String,
+    color: impl Fn(&Theme) -> Color + Copy + 'static,
+) -> Element<'static, ResultsListMessage> {
+    container(
+        text(label)
+            .size(10)
+            .font(MONO_SEMIBOLD)
+            .style(move |t: &Theme| text::Style {
+                color: Some(color(t)),
+            }),
+    )
+    .style(move |t: &Theme| ContainerStyle {
+        background: Some(Background::Color(Color {
+            a: 0.12,
+            ..color(t)
+        })),
+        border: Border {
+            color: Color { a: 0.3, ..color(t) },
+            width: 1.0,
+            radius: 4.0.into(),
+        },
+        ..Default::default()
+    })
+    .padding(Padding::from([1, 5]))
+    .into()
+}
+
 fn players_column(
     online: u64,
     max: u64,
@@ -209,12 +271,15 @@ pub(crate) fn ping_color(ms: u64) -> Color {
     }
 }
 
-fn split_motd(motd: &str) -> (String, String) {
-    let cleaned = strip_section_codes(motd);
-    let stripped = cleaned.trim();
-    if let Some((first, rest)) = stripped.split_once('\n') {
-        (first.trim().to_string(), rest.trim().to_string())
-    } else {
-        (stripped.to_string(), String::new())
+fn split_motd(motd: &Motd) -> (Motd, Motd) {
+    let mut lines = motd.trim().lines().into_iter();
+    let first = lines.next().unwrap_or_default().trim();
+    let mut rest = Motd::default();
+    for (i, line) in lines.enumerate() {
+        if i > 0 {
+            rest.append("\n".into());
+        }
+        rest.append(line);
     }
+    (first, rest.trim())
 }
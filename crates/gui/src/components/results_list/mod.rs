@@ -5,15 +5,20 @@ mod toolbar;
 
 use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
 
 use iced::widget::{button, container, image, text};
 use iced::{Background, Border, Element, Fill, Padding, Shadow, Theme};
 
 use crate::i18n::Tr;
 use crate::styles::{SANS, c, is_dark};
-use crate::text::strip_section_codes;
-use scanner::types::{Edition, ServerInfo};
+use crate::text::{MotdLook, strip_section_codes};
+use scanner::audit::{self, AdvisoryDb, Finding};
+use scanner::correlate;
+use scanner::geoip::GeoDb;
+use scanner::honeypot::{self, HoneypotConfig};
+use scanner::types::{Edition, ScanConfig, ServerInfo};
 
 use crate::components::ui::VirtualList;
 use avatar::{AvatarSize, favicon_handle};
@@ -59,6 +64,14 @@ pub enum WhitelistFilter {
     Off,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RconFilter {
+    #[default]
+    Any,
+    Exposed,
+    Closed,
+}
+
 #[derive(Default)]
 pub(super) struct Filters {
     pub(super) query: String,
@@ -67,8 +80,13 @@ pub(super) struct Filters {
     pub(super) edition: EditionFilter,
     pub(super) online_mode: OnlineModeFilter,
     pub(super) whitelist: WhitelistFilter,
+    pub(super) rcon: RconFilter,
     pub(super) version: String,
     pub(super) plugin: String,
+    // Country code, ASN or AS organisation
+    pub(super) network: String,
+    // Set from Settings rather than the filter panel, so not counted or reset.
+    pub(super) hide_honeypots: bool,
 }
 
 impl Filters {
@@ -76,8 +94,10 @@ impl Filters {
         usize::from(self.edition != EditionFilter::All)
             + usize::from(self.online_mode != OnlineModeFilter::Any)
             + usize::from(self.whitelist != WhitelistFilter::Any)
+            + usize::from(self.rcon != RconFilter::Any)
             + usize::from(!self.version.trim().is_empty())
             + usize::from(!self.plugin.trim().is_empty())
+            + usize::from(!self.network.trim().is_empty())
     }
 }
 
@@ -85,7 +105,19 @@ impl Filters {
 pub struct ResultsList {
     items: Vec<ServerInfo>,
     // Indices stay valid because items are only appended or updated, never removed.
+    // Every endpoint of a merged server maps to the same index.
     index: HashMap<SocketAddr, usize>,
+    guid_index: HashMap<String, usize>,
+    host_index: HashMap<IpAddr, Vec<usize>>,
+    // Rows with obfuscated MOTD text, so the animation tick needn't scan them all
+    obfuscated: usize,
+    advisories: AdvisoryDb,
+    // Audit findings per item, rerun only when the item or the advisories change
+    findings: Vec<Vec<Finding>>,
+    honeypot: HoneypotConfig,
+    geoip: Arc<GeoDb>,
+    // The current scan, for the ports each host was swept on ("answers on every port")
+    scan: ScanConfig,
     favicon_hash: HashMap<SocketAddr, u64>,
     avatars_small: HashMap<SocketAddr, image::Handle>,
     avatars_large: HashMap<SocketAddr, image::Handle>,
@@ -112,8 +144,10 @@ pub enum ResultsListMessage {
     EditionPicked(EditionFilter),
     OnlineModePicked(OnlineModeFilter),
     WhitelistPicked(WhitelistFilter),
+    RconPicked(RconFilter),
     VersionFilter(String),
     PluginFilter(String),
+    NetworkFilter(String),
     ResetFilters,
 }
 
@@ -121,19 +155,122 @@ impl ResultsList {
     pub fn push(&mut self, mut info: ServerInfo) -> Option<String> {
         let addr = info.addr;
         let favicon = info.favicon.take();
-        match self.index.get(&addr) {
-            Some(&idx) => self.items[idx] = info,
+        let existing = self
+            .index
+            .get(&addr)
+            .copied()
+            .or_else(|| self.same_server_index(&info));
+        let idx = match existing {
+            Some(idx) if self.items[idx].addr == addr => {
+                self.obfuscated += is_obfuscated(&info) as usize;
+                // A restarted server answers with a new GUID.
+                if info.edition == Edition::Bedrock {
+                    let old = self.items[idx].server_guid.as_ref();
+                    if old != info.server_guid.as_ref()
+                        && let Some(old) = old
+                        && self.guid_index.get(old) == Some(&idx)
+                    {
+                        self.guid_index.remove(old);
+                    }
+                    if let Some(guid) = &info.server_guid {
+                        self.guid_index.insert(guid.clone(), idx);
+                    }
+                }
+                let old = std::mem::replace(&mut self.items[idx], info);
+                self.obfuscated -= is_obfuscated(&old) as usize;
+                self.items[idx].merge_endpoints(&old);
+                idx
+            }
+            // Another endpoint of a server already listed: fold it into that row.
+            Some(idx) => {
+                self.items[idx].merge_endpoints(&info);
+                for e in std::iter::once(addr).chain(info.endpoints) {
+                    self.index.insert(e, idx);
+                }
+                self.view_dirty.set(true);
+                return None;
+            }
             None => {
-                self.index.insert(addr, self.items.len());
+                let idx = self.items.len();
+                for &e in &info.endpoints {
+                    self.index.insert(e, idx);
+                }
+                if let Some(guid) = &info.server_guid
+                    && info.edition == Edition::Bedrock
+                {
+                    self.guid_index.insert(guid.clone(), idx);
+                }
+                self.index.insert(addr, idx);
+                self.obfuscated += is_obfuscated(&info) as usize;
                 self.items.push(info);
+                idx
             }
-        }
+        };
+        self.geoip.annotate(&mut self.items[idx]);
+        self.audit_item(idx);
+        self.link_hybrid(idx);
+        self.flag_honeypots(self.items[idx].addr.ip());
         self.view_dirty.set(true);
         self.update_favicon(addr, favicon)
     }
+
+    fn audit_item(&mut self, idx: usize) {
+        let findings = audit::audit(&self.items[idx], &self.advisories);
+        match self.findings.get_mut(idx) {
+            Some(slot) => *slot = findings,
+            None => self.findings.push(findings),
+        }
+    }
+
+    fn same_server_index(&self, info: &ServerInfo) -> Option<usize> {
+        let idx = *self.guid_index.get(info.server_guid.as_ref()?)?;
+        self.items[idx].same_server(info).then_some(idx)
+    }
+
+    // Pairs the item with the other edition on its host, if they look like one server.
+    // Rerun on every update, so a pair that no longer correlates comes apart.
+    fn link_hybrid(&mut self, idx: usize) {
+        let addr = self.items[idx].addr;
+        let peers = self.host_index.entry(addr.ip()).or_default();
+        if !peers.contains(&idx) {
+            peers.push(idx);
+        }
+        self.items[idx].hybrid_with = None;
+        for other in peers.clone() {
+            let (java, bedrock) = match self.items[idx].edition {
+                Edition::Java => (idx, other),
+                Edition::Bedrock => (other, idx),
+            };
+            if correlate::is_hybrid_pair(&self.items[java], &self.items[bedrock]) {
+                self.items[idx].hybrid_with = Some(self.items[other].addr);
+                self.items[other].hybrid_with = Some(addr);
+            } else if self.items[other].hybrid_with == Some(addr) {
+                self.items[other].hybrid_with = None;
+            }
+        }
+    }
+
+    // Host-wide signals change as more ports of the host come in, so rerun them all.
+    fn flag_honeypots(&mut self, ip: IpAddr) {
+        let Some(group) = self.host_index.get(&ip) else {
+            return;
+        };
+        let host: Vec<&ServerInfo> = group.iter().map(|&i| &self.items[i]).collect();
+        let shared = honeypot::host_signals(&host, self.scan.java_ports_at(ip), &self.honeypot);
+        for &i in group {
+            let mut signals = honeypot::server_signals(&self.items[i], &self.honeypot);
+            signals.extend(shared.iter().copied());
+            self.items[i].honeypot = signals;
+        }
+    }
+
     pub fn clear(&mut self) {
         self.items.clear();
+        self.findings.clear();
         self.index.clear();
+        self.guid_index.clear();
+        self.host_index.clear();
+        self.obfuscated = 0;
         self.favicon_hash.clear();
         self.avatars_small.clear();
         self.avatars_large.clear();
@@ -183,10 +320,10 @@ impl ResultsList {
     }
 
     pub fn refresh(&mut self, mut info: ServerInfo) -> Option<String> {
-        let addr = info.addr;
         let favicon = info.favicon.take();
-        {
-            let &idx = self.index.get(&addr)?;
+        // A merged endpoint refreshes the row (and favicon) of its primary address.
+        let addr = {
+            let &idx = self.index.get(&info.addr)?;
             let s = &mut self.items[idx];
             s.online = info.online;
             s.max_players = info.max_players;
@@ -200,11 +337,17 @@ impl ResultsList {
             s.plugins = info.plugins;
             s.online_mode = info.online_mode;
             s.whitelist = info.whitelist;
+            s.rcon_exposed = info.rcon_exposed;
+            s.raknet_protocol = info.raknet_protocol;
+            s.raknet_mtu = info.raknet_mtu;
+            s.raknet_security = info.raknet_security;
+            s.raw_status = info.raw_status;
             s.ping_history.push(info.latency_ms);
             if s.ping_history.len() > 30 {
                 s.ping_history.remove(0);
             }
-        }
+            s.addr
+        };
         self.view_dirty.set(true);
         self.update_favicon(addr, favicon)
     }
@@ -266,6 +409,52 @@ impl ResultsList {
         self.view_dirty.set(true);
     }
 
+    pub fn set_advisories(&mut self, db: AdvisoryDb) {
+        self.advisories = db;
+        self.findings = self
+            .items
+            .iter()
+            .map(|item| audit::audit(item, &self.advisories))
+            .collect();
+    }
+
+    pub fn advisories(&self) -> &AdvisoryDb {
+        &self.advisories
+    }
+
+    pub fn set_geoip(&mut self, db: Arc<GeoDb>) {
+        self.geoip = db;
+        for item in &mut self.items {
+            self.geoip.annotate(item);
+        }
+        self.view_dirty.set(true);
+    }
+
+    pub fn set_honeypot(&mut self, config: HoneypotConfig, scan: ScanConfig) {
+        self.honeypot = config;
+        self.scan = scan;
+        let hosts: Vec<IpAddr> = self.host_index.keys().copied().collect();
+        for ip in hosts {
+            self.flag_honeypots(ip);
+        }
+        self.view_dirty.set(true);
+    }
+
+    // Whether any MOTD needs the obfuscation animation.
+    pub fn has_obfuscated(&self) -> bool {
+        self.obfuscated > 0
+    }
+
+    pub fn set_hide_honeypots(&mut self, hide: bool) {
+        self.filters.hide_honeypots = hide;
+        self.view_dirty.set(true);
+    }
+
+    pub fn set_rcon(&mut self, rcon: RconFilter) {
+        self.filters.rcon = rcon;
+        self.view_dirty.set(true);
+    }
+
     pub fn set_version_filter(&mut self, text: String) {
         self.filters.version = text;
         self.view_dirty.set(true);
@@ -276,12 +465,19 @@ impl ResultsList {
         self.view_dirty.set(true);
     }
 
+    pub fn set_network_filter(&mut self, text: String) {
+        self.filters.network = text;
+        self.view_dirty.set(true);
+    }
+
     pub fn reset_filters(&mut self) {
         self.filters.edition = EditionFilter::All;
         self.filters.online_mode = OnlineModeFilter::Any;
         self.filters.whitelist = WhitelistFilter::Any;
+        self.filters.rcon = RconFilter::Any;
         self.filters.version.clear();
         self.filters.plugin.clear();
+        self.filters.network.clear();
         self.view_dirty.set(true);
     }
 
@@ -308,8 +504,9 @@ impl ResultsList {
         let q = f.query.trim().to_lowercase();
         let version_q = f.version.trim().to_lowercase();
         let plugin_q = f.plugin.trim().to_lowercase();
+        let network_q = f.network.trim().to_lowercase();
         let mut order: Vec<usize> = (0..self.items.len())
-            .filter(|&i| passes_filters(&self.items[i], f, &q, &version_q, &plugin_q))
+            .filter(|&i| passes_filters(&self.items[i], f, &q, &version_q, &plugin_q, &network_q))
             .collect();
         // Stable sort keeps ties in discovery order.
         match f.sort {
@@ -328,11 +525,12 @@ impl ResultsList {
         &self,
         tr: &'static Tr,
         show_login_filters: bool,
+        show_rcon_filter: bool,
     ) -> Element<'_, ResultsListMessage> {
-        toolbar::render(self, tr, show_login_filters)
+        toolbar::render(self, tr, show_login_filters, show_rcon_filter)
     }
 
-    pub fn view(&self, tr: &'static Tr) -> Element<'_, ResultsListMessage> {
+    pub fn view(&self, tr: &'static Tr, look: MotdLook) -> Element<'_, ResultsListMessage> {
         if self.items.is_empty() {
             return empty_state(tr.results_empty);
         }
@@ -351,7 +549,13 @@ impl ResultsList {
             move |row| {
                 let info = &self.items[order[row]];
                 let addr = info.addr;
-                let content = server_card_content(info, self.avatars_small.get(&addr).cloned(), tr);
+                let content = server_card_content(
+                    info,
+                    self.avatars_small.get(&addr).cloned(),
+                    &self.findings[order[row]],
+                    tr,
+                    look,
+                );
                 button(content)
                     .on_press(ResultsListMessage::OpenPreview(addr))
                     .style(card_btn_style)
@@ -390,10 +594,13 @@ fn passes_filters(
     query: &str,
     version_q: &str,
     plugin_q: &str,
+    network_q: &str,
 ) -> bool {
-    edition_matches(f.edition, &s.edition)
+    (!f.hide_honeypots || s.honeypot.is_empty())
+        && edition_matches(f.edition, &s.edition)
         && online_mode_matches(f.online_mode, s.online_mode)
         && whitelist_matches(f.whitelist, s.whitelist)
+        && rcon_matches(f.rcon, s.rcon_exposed)
         && (query.is_empty() || search_matches(s, query))
         && (version_q.is_empty()
             || strip_section_codes(&s.version)
@@ -403,6 +610,19 @@ fn passes_filters(
             || s.plugins
                 .iter()
                 .any(|p| strip_section_codes(p).to_lowercase().contains(plugin_q)))
+        && (network_q.is_empty() || network_matches(s, network_q))
+}
+
+// An exact country code or ASN (`24940`, `as24940`), or part of the AS name.
+fn network_matches(s: &ServerInfo, query: &str) -> bool {
+    let asn = query.strip_prefix("as").unwrap_or(query);
+    s.country
+        .as_ref()
+        .is_some_and(|c| c.eq_ignore_ascii_case(query))
+        || s.asn.is_some_and(|n| n.to_string() == asn)
+        || s.as_org
+            .as_ref()
+            .is_some_and(|org| org.to_lowercase().contains(query))
 }
 
 fn edition_matches(filter: EditionFilter, edition: &Edition) -> bool {
@@ -429,15 +649,28 @@ fn whitelist_matches(filter: WhitelistFilter, whitelist: Option<bool>) -> bool {
     }
 }
 
+fn rcon_matches(filter: RconFilter, exposed: Option<bool>) -> bool {
+    match filter {
+        RconFilter::Any => true,
+        RconFilter::Exposed => exposed == Some(true),
+        RconFilter::Closed => exposed == Some(false),
+    }
+}
+
 fn search_matches(s: &ServerInfo, query: &str) -> bool {
     let addr = format!("{}:{}", s.addr.ip(), s.addr.port());
     addr.contains(query)
-        || strip_section_codes(&s.motd).to_lowercase().contains(query)
+        || s.endpoints.iter().any(|e| e.to_string().contains(query))
+        || s.motd.plain().to_lowercase().contains(query)
         || strip_section_codes(&s.version)
             .to_lowercase()
             .contains(query)
 }
 
+fn is_obfuscated(server: &ServerInfo) -> bool {
+    server.motd.spans.iter().any(|span| span.style.obfuscated)
+}
+
 fn card_btn_style(t: &Theme, status: button::Status) -> button::Style {
     let dark = is_dark(t);
     let bg = if dark { c("#181D25") } else { c("#FFFFFF") };
@@ -533,6 +766,18 @@ mod tests {
         assert!(list.get_by_addr(addr(25567)).is_none());
     }
 
+    #[test]
+    fn tracks_obfuscated_motds_across_pushes() {
+        let mut list = ResultsList::default();
+        let mut info = ServerInfo::base(addr(1), Edition::Java);
+        info.motd = "§kxx§r hi".into();
+        list.push(info.clone());
+        assert!(list.has_obfuscated());
+        info.motd = "hi".into();
+        list.push(info);
+        assert!(!list.has_obfuscated());
+    }
+
     #[test]
     fn refresh_updates_existing_entry_via_index() {
         let mut list = ResultsList::default();
@@ -574,6 +819,135 @@ mod tests {
         );
     }
 
+    fn bedrock(port: u16, guid: &str) -> ServerInfo {
+        let mut s = ServerInfo::base(addr(port), Edition::Bedrock);
+        s.server_guid = Some(guid.into());
+        s
+    }
+
+    #[test]
+    fn push_merges_endpoints_sharing_a_guid() {
+        let mut list = ResultsList::default();
+        list.push(bedrock(19132, "42"));
+        list.push(bedrock(19133, "42"));
+        list.push(bedrock(19134, "7"));
+
+        assert_eq!(list.count(), 2);
+        let merged = list.get_by_addr(addr(19133)).unwrap();
+        assert_eq!(merged.addr, addr(19132));
+        assert_eq!(merged.endpoints, vec![addr(19133)]);
+
+        // Re-finding the primary endpoint keeps the merged ones.
+        list.push(bedrock(19132, "42"));
+        assert_eq!(
+            list.get_by_addr(addr(19132)).unwrap().endpoints,
+            vec![addr(19133)]
+        );
+        assert_eq!(list.count(), 2);
+
+        search(&mut list, "127.0.0.1:19133");
+        assert_eq!(visible_ports(&list), vec![19132]);
+    }
+
+    #[test]
+    fn a_restarted_server_merges_by_its_new_guid() {
+        let mut list = ResultsList::default();
+        list.push(bedrock(19132, "42"));
+        list.push(bedrock(19132, "43"));
+        list.push(bedrock(19133, "43"));
+        list.push(bedrock(19134, "42"));
+        assert_eq!(list.count(), 2);
+        assert_eq!(
+            list.get_by_addr(addr(19132)).unwrap().endpoints,
+            vec![addr(19133)]
+        );
+    }
+
+    #[test]
+    fn push_links_java_and_bedrock_on_one_host() {
+        let mut java = ServerInfo::base(addr(25565), Edition::Java);
+        java.motd = "Skyblock Network".into();
+        let mut bedrock = ServerInfo::base(addr(19132), Edition::Bedrock);
+        bedrock.motd = "Skyblock Network".into();
+        let mut other = ServerInfo::base(addr(19133), Edition::Bedrock);
+        other.motd = "Creative".into();
+
+        let mut list = ResultsList::default();
+        list.push(java);
+        list.push(other);
+        list.push(bedrock);
+
+        let hybrid_with = |port| list.get_by_addr(addr(port)).unwrap().hybrid_with;
+        assert_eq!(hybrid_with(25565), Some(addr(19132)));
+        assert_eq!(hybrid_with(19132), Some(addr(25565)));
+        assert_eq!(hybrid_with(19133), None);
+    }
+
+    #[test]
+    fn findings_follow_each_update_of_a_row() {
+        let mut list = ResultsList::default();
+        let mut info = ServerInfo::base(addr(25565), Edition::Java);
+        info.rcon_exposed = Some(true);
+        list.push(info.clone());
+        let rules =
+            |list: &ResultsList| list.findings[0].iter().map(|f| f.rule).collect::<Vec<_>>();
+        assert_eq!(rules(&list), ["rcon_exposed"]);
+
+        info.rcon_exposed = None;
+        list.push(info);
+        assert!(rules(&list).is_empty());
+        list.clear();
+        assert!(list.findings.is_empty());
+    }
+
+    #[test]
+    fn a_pair_that_stops_correlating_is_unlinked() {
+        let mut java = ServerInfo::base(addr(25565), Edition::Java);
+        java.motd = "Skyblock Network".into();
+        let mut bedrock = ServerInfo::base(addr(19132), Edition::Bedrock);
+        bedrock.motd = "Skyblock Network".into();
+
+        let mut list = ResultsList::default();
+        list.push(java.clone());
+        list.push(bedrock);
+        assert_eq!(
+            list.get_by_addr(addr(25565)).unwrap().hybrid_with,
+            Some(addr(19132))
+        );
+
+        // Re-probed, the Java side now shows another MOTD.
+        java.motd = "Creative".into();
+        list.push(java);
+        let hybrid_with = |port| list.get_by_addr(addr(port)).unwrap().hybrid_with;
+        assert_eq!(hybrid_with(25565), None);
+        assert_eq!(hybrid_with(19132), None);
+    }
+
+    #[test]
+    fn identical_ports_are_flagged_and_can_be_hidden() {
+        let mut list = ResultsList::default();
+        list.set_honeypot(
+            HoneypotConfig {
+                min_ports: 2,
+                ..Default::default()
+            },
+            ScanConfig::default(),
+        );
+        let mut canned = ServerInfo::base(addr(1), Edition::Java);
+        canned.status_hash = Some(42);
+        list.push(canned.clone());
+        assert!(list.get_by_addr(addr(1)).unwrap().honeypot.is_empty());
+
+        // The second copy flags both, including the row already listed.
+        canned.addr = addr(2);
+        list.push(canned);
+        assert!(!list.get_by_addr(addr(1)).unwrap().honeypot.is_empty());
+
+        list.set_hide_honeypots(true);
+        assert!(visible_ports(&list).is_empty());
+        assert_eq!(list.filters.active_count(), 0);
+    }
+
     #[test]
     fn clear_drops_the_index() {
         let mut list = ResultsList::default();
@@ -663,6 +1037,21 @@ mod tests {
         assert_eq!(list.filters.active_count(), 1);
     }
 
+    #[test]
+    fn rcon_filter_skips_unchecked_servers() {
+        let mut list = seeded();
+        let mut exposed = server(4, Edition::Java, 1, 10, "D");
+        exposed.rcon_exposed = Some(true);
+        list.push(exposed);
+
+        list.set_rcon(RconFilter::Exposed);
+        assert_eq!(visible_ports(&list), vec![4]);
+        list.set_rcon(RconFilter::Closed);
+        assert!(visible_ports(&list).is_empty());
+        list.reset_filters();
+        assert_eq!(list.filters.active_count(), 0);
+    }
+
     #[test]
     fn edition_filter_narrows_to_one_edition() {
         let mut list = seeded();
@@ -697,4 +1086,26 @@ mod tests {
         list.set_sort(SortKey::Players);
         assert_eq!(visible_ports(&list), vec![4, 1]);
     }
+
+    #[test]
+    fn loading_geoip_annotates_listed_servers_for_the_network_filter() {
+        let mut list = ResultsList::default();
+        list.push(ServerInfo::base(addr(25565), Edition::Java));
+        list.push(ServerInfo::base(
+            SocketAddr::from(([10, 0, 0, 1], 25565)),
+            Edition::Java,
+        ));
+        let mut db = GeoDb::default();
+        db.add(b"network,asn,as_org,country\n127.0.0.0/8,AS64500,Loopback Hosting,DE\n".to_vec())
+            .unwrap();
+        list.set_geoip(Arc::new(db));
+        assert_eq!(list.get_by_addr(addr(25565)).unwrap().asn, Some(64500));
+
+        for query in ["de", "AS64500", "64500", "loopback"] {
+            list.set_network_filter(query.into());
+            assert_eq!(list.visible_count(), 1, "{query}");
+        }
+        list.set_network_filter("FR".into());
+        assert_eq!(list.visible_count(), 0);
+    }
 }
@@ -2,20 +2,24 @@ use crate::components::ui::{
     BtnVariant, body, btn, caption, cell, cell_colored, chip, chip_dot, field, heading,
     scrollbar as styled_scroll, wrap,
 };
-use crate::text::strip_section_codes;
+use crate::text::{motd_spans, strip_section_codes};
 use iced::Length::Fixed;
 use iced::mouse::Interaction;
 use iced::widget::container::Style as ContainerStyle;
 use iced::widget::space::Space;
-use iced::widget::{button, column, container, mouse_area, row, svg, text};
+use iced::widget::{button, column, container, mouse_area, rich_text, row, svg, text};
 use iced::{
     Alignment, Background, Border, Color, ContentFit, Element, Fill, Padding, Shadow, Theme,
 };
 
+use super::ResultsListMessage;
 use super::avatar::{AvatarSize, build_avatar_icon};
 use crate::app::{McScan, Message, ModalKind};
-use crate::styles::{MONO, MONO_SEMIBOLD, SANS_SEMIBOLD, c, is_dark};
-use scanner::types::Edition;
+use crate::i18n::Tr;
+use crate::styles::{MONO, MONO_SEMIBOLD, SANS, SANS_SEMIBOLD, c, is_dark};
+use scanner::honeypot::Signal;
+use scanner::motd::Motd;
+use scanner::types::{Edition, ServerInfo};
 
 pub fn render(app: &McScan) -> Element<'_, Message> {
     let addr = match &app.modal {
@@ -41,7 +45,7 @@ pub fn render(app: &McScan) -> Element<'_, Message> {
     let server_name = motd_first_line(&server.motd);
 
     let avatar = build_avatar_icon(
-        &server_name,
+        &server_name.plain(),
         &server.edition,
         app.results.avatar_large(addr),
         AvatarSize::LARGE,
@@ -90,9 +94,13 @@ pub fn render(app: &McScan) -> Element<'_, Message> {
         row![
             avatar,
             Space::new().width(16),
-            column![heading(server_name), Space::new().height(6), online_row,]
-                .width(Fill)
-                .clip(true),
+            column![
+                heading(motd_spans(&server_name, SANS_SEMIBOLD, app.motd_look())),
+                Space::new().height(6),
+                online_row,
+            ]
+            .width(Fill)
+            .clip(true),
             close_btn,
         ]
         .align_y(Alignment::Start),
@@ -155,14 +163,23 @@ pub fn render(app: &McScan) -> Element<'_, Message> {
 
     let addr_section = field(tr.address, addr_row.into());
 
-    let motd_text = strip_section_codes(server.motd.trim());
+    let motd = server.motd.trim();
     let motd_section = field(
         tr.motd,
-        container(body(if motd_text.is_empty() {
-            "—".to_string()
+        container(if motd.is_empty() {
+            body("—")
         } else {
-            motd_text
-        }))
+            rich_text(motd_spans(&motd, SANS, app.motd_look()))
+                .size(13)
+                .style(|t: &Theme| text::Style {
+                    color: Some(if is_dark(t) {
+                        c("#A2ABBA")
+                    } else {
+                        c("#4A5260")
+                    }),
+                })
+                .into()
+        })
         .style(inset_box_style)
         .padding(Padding::from([11, 13]))
         .width(Fill)
@@ -188,6 +205,35 @@ pub fn render(app: &McScan) -> Element<'_, Message> {
         let v = if wl { tr.enabled } else { tr.disabled };
         extra_cells.push(cell(tr.whitelist, v.to_string(), false));
     }
+    if let Some(exposed) = server.rcon_exposed {
+        let v = if exposed {
+            tr.rcon_exposed
+        } else {
+            tr.rcon_closed
+        };
+        extra_cells.push(cell(tr.rcon, v.to_string(), false));
+    }
+    if let Some(asn) = server.asn {
+        let v = match &server.as_org {
+            Some(org) => format!("AS{asn} · {org}"),
+            None => format!("AS{asn}"),
+        };
+        extra_cells.push(cell(tr.network, v, false));
+    }
+    if let Some(country) = &server.country {
+        let v = match &server.city {
+            Some(city) => format!("{country} · {city}"),
+            None => country.clone(),
+        };
+        extra_cells.push(cell(tr.location, v, false));
+    }
+    if let Some(proto) = server.raknet_protocol {
+        let v = match server.raknet_mtu {
+            Some(mtu) => format!("v{proto} · {mtu}"),
+            None => format!("v{proto}"),
+        };
+        extra_cells.push(cell(tr.raknet, v, true));
+    }
     if !server.mods.is_empty() {
         extra_cells.push(cell(tr.mods, server.mods.len().to_string(), true));
     }
@@ -295,7 +341,81 @@ pub fn render(app: &McScan) -> Element<'_, Message> {
         None
     };
 
-    let mut col = column![header, separator, addr_section, motd_section, stats_block,].width(Fill);
+    let endpoints_block = if !server.endpoints.is_empty() {
+        let chips: Vec<Element<'_, Message>> = std::iter::once(server.addr)
+            .chain(server.endpoints.iter().copied())
+            .map(|e| chip(e.to_string()))
+            .collect();
+        let title = if server.is_dual_stack() {
+            format!("{} · {}", tr.endpoints, tr.dual_stack)
+        } else {
+            tr.endpoints.to_string()
+        };
+        Some(field(title, wrap(chips).spacing(7.0).into()))
+    } else {
+        None
+    };
+
+    let hybrid_block = server.hybrid_with.map(|other| {
+        let other_edition = match server.edition {
+            Edition::Java => tr.bedrock_edition,
+            Edition::Bedrock => tr.java_edition,
+        };
+        let label = format!("{other_edition} · {}:{} →", other.ip(), other.port());
+        let link = mouse_area(chip(label))
+            .on_press(Message::ResultsList(ResultsListMessage::OpenPreview(other)))
+            .interaction(Interaction::Pointer);
+        field(tr.hybrid, link.into())
+    });
+
+    let honeypot_block = (!server.honeypot.is_empty()).then(|| {
+        let chips: Vec<Element<'_, Message>> = server
+            .honeypot
+            .iter()
+            .map(|&s| chip(signal_label(s, tr)))
+            .collect();
+        field(tr.honeypot, wrap(chips).spacing(7.0).into())
+    });
+
+    let mut col = column![header, separator].width(Fill);
+
+    // Tabs only appear when the probe kept the raw status.
+    if let Some(raw) = pretty_raw(server) {
+        let tab = |label, raw_tab: bool| {
+            let variant = if app.show_raw == raw_tab {
+                BtnVariant::Primary(label)
+            } else {
+                BtnVariant::Secondary(label)
+            };
+            btn(variant, Message::ShowRaw(raw_tab))
+        };
+        col = col.push(
+            container(row![
+                tab(tr.overview, false),
+                Space::new().width(8),
+                tab(tr.raw, true),
+            ])
+            .padding(Padding::from([12, 22])),
+        );
+        if app.show_raw {
+            col = col.push(raw_block(raw, copy_icon, copy_label));
+            return overlay(col);
+        }
+    }
+
+    col = col.push(addr_section).push(motd_section).push(stats_block);
+
+    if let Some(s) = honeypot_block {
+        col = col.push(s);
+    }
+
+    if let Some(s) = hybrid_block {
+        col = col.push(s);
+    }
+
+    if let Some(s) = endpoints_block {
+        col = col.push(s);
+    }
 
     col = col.push(chart_block);
 
@@ -311,6 +431,70 @@ pub fn render(app: &McScan) -> Element<'_, Message> {
         col = col.push(s);
     }
 
+    overlay(col)
+}
+
+/// The raw status as shown and copied: Java JSON pretty-printed, the Bedrock
+/// pong one `;` field per line.
+pub(crate) fn pretty_raw(server: &ServerInfo) -> Option<String> {
+    let raw = server.raw_status.as_deref()?;
+    Some(match server.edition {
+        Edition::Java => serde_json::from_str::<serde_json::Value>(raw)
+            .ok()
+            .and_then(|v| serde_json::to_string_pretty(&v).ok())
+            .unwrap_or_else(|| raw.to_string()),
+        Edition::Bedrock => raw.split(';').collect::<Vec<_>>().join("\n"),
+    })
+}
+
+fn raw_block<'a>(raw: String, copy_icon: svg::Handle, label: &'a str) -> Element<'a, Message> {
+    let copy_btn = button(
+        row![
+            svg(copy_icon)
+                .content_fit(ContentFit::Fill)
+                .width(Fixed(13.0))
+                .height(Fixed(13.0))
+                .style(|t: &Theme, _| svg::Style {
+                    color: Some(acc_green(t)),
+                }),
+            Space::new().width(7),
+            text(label)
+                .size(13)
+                .font(SANS_SEMIBOLD)
+                .style(|t: &Theme| text::Style {
+                    color: Some(acc_green(t))
+                }),
+        ]
+        .align_y(Alignment::Center),
+    )
+    .on_press(Message::CopyRaw)
+    .style(copy_btn_style)
+    .padding(Padding::from([8, 12]));
+
+    column![
+        row![Space::new().width(Fill), copy_btn],
+        Space::new().height(9),
+        container(
+            text(raw)
+                .size(12)
+                .font(MONO)
+                .style(|t: &Theme| text::Style {
+                    color: Some(if is_dark(t) {
+                        c("#E8EBF0")
+                    } else {
+                        c("#161A20")
+                    }),
+                }),
+        )
+        .style(inset_box_style)
+        .padding(Padding::from([11, 13]))
+        .width(Fill),
+    ]
+    .padding(Padding::from([0, 22]))
+    .into()
+}
+
+fn overlay(col: iced::widget::Column<'_, Message>) -> Element<'_, Message> {
     let dialog = container(styled_scroll(col))
         .width(Fixed(516.0))
         .height(Fixed(535.0))
@@ -394,6 +578,15 @@ fn ping_chart<'a>(current_ping: u64, history: &[u64]) -> Element<'a, Message> {
         .into()
 }
 
+fn signal_label(signal: Signal, tr: &Tr) -> String {
+    match signal {
+        Signal::Template => tr.honeypot_template.to_string(),
+        Signal::ImpossiblePlayers => tr.honeypot_players.to_string(),
+        Signal::IdenticalPorts(n) => format!("{n} {}", tr.honeypot_identical),
+        Signal::AllPorts => tr.honeypot_all_ports.to_string(),
+    }
+}
+
 fn is_version_expandable(v: &str) -> bool {
     let v = v.trim();
     !v.is_empty() && (v.contains(',') || v.chars().count() > 14)
@@ -502,13 +695,12 @@ fn version_cell_style(t: &Theme, status: button::Status) -> button::Style {
     }
 }
 
-fn motd_first_line(motd: &str) -> String {
-    strip_section_codes(motd.trim())
-        .split('\n')
-        .next()
-        .unwrap_or("")
-        .trim()
-        .to_string()
+fn motd_first_line(motd: &Motd) -> Motd {
+    motd.trim()
+        .lines()
+        .first()
+        .map(Motd::trim)
+        .unwrap_or_default()
 }
 
 // Deterministic accent for a player nick so a given name keeps its dot color.
@@ -7,7 +7,8 @@ use crate::i18n::Tr;
 use crate::styles::{SANS, SANS_SEMIBOLD, c, is_dark};
 
 use super::{
-    EditionFilter, OnlineModeFilter, ResultsList, ResultsListMessage, SortKey, WhitelistFilter,
+    EditionFilter, OnlineModeFilter, RconFilter, ResultsList, ResultsListMessage, SortKey,
+    WhitelistFilter,
 };
 
 type Msg = ResultsListMessage;
@@ -18,11 +19,12 @@ pub(super) fn render<'a>(
     list: &'a ResultsList,
     tr: &'static Tr,
     show_login_filters: bool,
+    show_rcon_filter: bool,
 ) -> Element<'a, Msg> {
     row![
         search_box(list, tr),
         sort_control(list, tr),
-        filter_control(list, tr, show_login_filters),
+        filter_control(list, tr, show_login_filters, show_rcon_filter),
     ]
     .spacing(10)
     .align_y(Alignment::Center)
@@ -194,6 +196,7 @@ fn filter_control<'a>(
     list: &'a ResultsList,
     tr: &'static Tr,
     show_login_filters: bool,
+    show_rcon_filter: bool,
 ) -> Element<'a, Msg> {
     let count = list.filters.active_count();
     let mut label = row![
@@ -221,7 +224,7 @@ fn filter_control<'a>(
     let trigger = control_button(label, Msg::ToggleFilterMenu, count > 0);
     popover(
         trigger,
-        filter_panel(list, tr, show_login_filters),
+        filter_panel(list, tr, show_login_filters, show_rcon_filter),
         list.filters_open,
         Msg::DismissMenus,
     )
@@ -232,6 +235,7 @@ fn filter_panel<'a>(
     list: &'a ResultsList,
     tr: &'static Tr,
     show_login_filters: bool,
+    show_rcon_filter: bool,
 ) -> Element<'a, Msg> {
     let edition = field(
         tr.edition,
@@ -272,6 +276,19 @@ fn filter_panel<'a>(
         ),
     );
 
+    let rcon = field(
+        tr.rcon,
+        segmented(
+            vec![
+                (tr.online_any, RconFilter::Any),
+                (tr.rcon_exposed, RconFilter::Exposed),
+                (tr.rcon_closed, RconFilter::Closed),
+            ],
+            list.filters.rcon,
+            Msg::RconPicked,
+        ),
+    );
+
     let version = field(
         tr.version,
         search_input(&list.filters.version, tr.version_hint, Msg::VersionFilter)
@@ -285,6 +302,13 @@ fn filter_panel<'a>(
             .into(),
     );
 
+    let network = field(
+        tr.network,
+        search_input(&list.filters.network, tr.network_hint, Msg::NetworkFilter)
+            .width(Fill)
+            .into(),
+    );
+
     let reset = button(
         text(tr.reset)
             .size(12)
@@ -304,8 +328,12 @@ fn filter_panel<'a>(
         children.push(online);
         children.push(whitelist);
     }
+    if show_rcon_filter {
+        children.push(rcon);
+    }
     children.push(version);
     children.push(plugin);
+    children.push(network);
     children.push(footer.into());
 
     panel(Column::with_children(children).spacing(14), 300.0, 16.0)
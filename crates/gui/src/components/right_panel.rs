@@ -5,9 +5,10 @@ use iced::widget::{column, container, row, text};
 use iced::{Alignment, Background, Element, Fill, Padding, Theme};
 
 use crate::app::{McScan, Message, ModalKind};
+use crate::components::address_list::AddressListMessage;
 use crate::components::{
     action_button,
-    ui::{BtnVariant, btn, divider},
+    ui::{BtnVariant, btn, checkbox, divider},
 };
 use crate::styles::{MONO, SANS, SANS_SEMIBOLD, c, is_dark};
 
@@ -29,7 +30,7 @@ pub fn render(app: &McScan) -> Element<'_, Message> {
     ]
     .align_y(Alignment::Center);
 
-    let range_count = app.address_list.values().len();
+    let range_count = app.address_list.len();
     let total_hosts = app.address_list.total_hosts();
     let total_str = if total_hosts == u64::MAX {
         "∞".to_string()
@@ -89,6 +90,10 @@ pub fn render(app: &McScan) -> Element<'_, Message> {
             top_row,
             Space::new().height(16),
             ranges_header,
+            Space::new().height(8),
+            checkbox(tr.lan_label, app.address_list.lan(), |v| {
+                Message::AddressList(AddressListMessage::ToggleLan(v))
+            }),
             Space::new().height(6),
             app.address_list.view().map(Message::AddressList),
             Space::new().height(8),
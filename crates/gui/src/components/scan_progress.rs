@@ -11,11 +11,18 @@ pub fn render(app: &McScan) -> Element<'_, Message> {
     let tr = app.tr();
     let ratio = app.scanned_count as f32 / app.total_targets as f32;
     let pct = (ratio * 100.0) as u32;
-    let ranges = app.address_list.values();
-    let range_str = match ranges.len() {
-        0 => "…".to_string(),
-        1 => ranges[0].to_string(),
-        n => format!("{} (+{})", ranges[0], n - 1),
+    let list = &app.address_list;
+    let first = list
+        .values()
+        .first()
+        .map(ToString::to_string)
+        .or_else(|| list.patterns().first().map(ToString::to_string))
+        .or_else(|| list.endpoints().first().map(ToString::to_string))
+        .or_else(|| list.imports().first().map(|i| i.name.clone()));
+    let range_str = match (first, list.len()) {
+        (None, _) => "…".to_string(),
+        (Some(first), 1) => first,
+        (Some(first), n) => format!("{} (+{})", first, n - 1),
     };
     let scanned = app.scanned_count;
     let total = app.total_targets;
@@ -32,6 +32,12 @@ pub fn render(app: &McScan) -> Element<'_, Message> {
             Space::new().width(8),
             theme_btn(tr.light, ThemePref::Light),
         ],
+        Space::new().height(8),
+        checkbox(
+            tr.plain_motd,
+            app.settings.plain_motd,
+            Message::TogglePlainMotd
+        ),
         Space::new().height(16),
         caption(tr.language, 11),
         Space::new().height(8),
@@ -108,6 +114,30 @@ pub fn render(app: &McScan) -> Element<'_, Message> {
             Message::TimeoutChanged,
             false
         ),
+        Space::new().height(6),
+        labeled_input(
+            tr.advisories,
+            &app.settings.advisory_db,
+            tr.advisories_hint,
+            Message::AdvisoryDbChanged,
+            app.settings.advisory_db_error
+        ),
+        Space::new().height(6),
+        labeled_input(
+            tr.prefix_dir,
+            &app.settings.prefix_dir,
+            tr.prefix_dir_hint,
+            Message::PrefixDirChanged,
+            false
+        ),
+        Space::new().height(6),
+        labeled_input(
+            tr.geoip_db,
+            &app.settings.geoip_db,
+            tr.geoip_db_hint,
+            Message::GeoIpDbChanged,
+            app.settings.geoip_db_error
+        ),
         Space::new().height(16),
         caption(tr.enrichment, 11),
         Space::new().height(8),
@@ -122,6 +152,74 @@ pub fn render(app: &McScan) -> Element<'_, Message> {
             app.settings.online_mode_check,
             Message::ToggleOnlineModeCheck
         ),
+        Space::new().height(8),
+        checkbox(
+            tr.raknet_label,
+            app.settings.raknet_check,
+            Message::ToggleRaknetCheck
+        ),
+        Space::new().height(8),
+        checkbox(
+            tr.rcon_label,
+            app.settings.rcon_check,
+            Message::ToggleRconCheck
+        ),
+        Space::new().height(16),
+        caption(tr.honeypots, 11),
+        Space::new().height(8),
+        labeled_input(
+            tr.honeypot_min_ports,
+            &app.settings.honeypot_min_ports,
+            "5",
+            Message::HoneypotMinPortsChanged,
+            false
+        ),
+        Space::new().height(6),
+        labeled_input(
+            tr.honeypot_player_factor,
+            &app.settings.honeypot_player_factor,
+            "10",
+            Message::HoneypotPlayerFactorChanged,
+            false
+        ),
+        Space::new().height(6),
+        labeled_input(
+            tr.honeypot_templates,
+            &app.settings.honeypot_templates,
+            tr.honeypot_templates_hint,
+            Message::HoneypotTemplatesChanged,
+            app.settings.honeypot_templates_error
+        ),
+        Space::new().height(8),
+        checkbox(
+            tr.hide_honeypots,
+            app.settings.hide_honeypots,
+            Message::ToggleHideHoneypots
+        ),
+        Space::new().height(16),
+        caption(tr.export, 11),
+        Space::new().height(8),
+        checkbox(
+            tr.export_favicons,
+            app.settings.export_favicons,
+            Message::ToggleExportFavicons
+        ),
+        Space::new().height(8),
+        checkbox(
+            tr.merge_servers_dat,
+            app.settings.merge_servers_dat,
+            Message::ToggleMergeServersDat
+        ),
+        Space::new().height(16),
+        caption(tr.diagnostics, 11),
+        Space::new().height(8),
+        checkbox(
+            tr.record_transcripts,
+            app.settings.record_transcripts,
+            Message::ToggleRecordTranscripts
+        ),
+        Space::new().height(8),
+        checkbox(tr.keep_raw, app.settings.keep_raw, Message::ToggleKeepRaw),
     ];
 
     dialog(
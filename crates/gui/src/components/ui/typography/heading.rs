@@ -1,11 +1,13 @@
-use iced::widget::text;
-use iced::{Element, Theme};
+use iced::widget::text::Span;
+use iced::widget::{rich_text, text};
+use iced::{Element, Font, Theme};
 
 use crate::styles::{SANS_SEMIBOLD, c, is_dark};
 
-/// Primary title, single line.
-pub fn heading<'a, M: 'a>(label: impl text::IntoFragment<'a>) -> Element<'a, M> {
-    text(label)
+/// Primary title, single line. Spans without a font or color of their own get
+/// the heading's.
+pub fn heading<'a, M: 'a>(spans: Vec<Span<'a, (), Font>>) -> Element<'a, M> {
+    rich_text(spans)
         .size(17)
         .font(SANS_SEMIBOLD)
         .wrapping(text::Wrapping::None)
@@ -7,13 +7,30 @@ use serde::{Deserialize, Serialize};
 #[derive(Serialize, Deserialize)]
 #[serde(default)]
 pub struct Config {
+    // Ranges and endpoints
     pub ranges: Vec<String>,
+    pub imports: Vec<SavedImport>,
+    pub lan_discovery: bool,
     pub java_ports: String,
     pub bedrock_ports: String,
     pub concurrency: String,
     pub timeout_ms: String,
     pub query_enabled: bool,
     pub online_mode_check: bool,
+    pub raknet_check: bool,
+    pub rcon_check: bool,
+    pub advisory_db: String,
+    pub prefix_dir: String,
+    pub geoip_db: String,
+    pub honeypot_min_ports: String,
+    pub honeypot_player_factor: String,
+    pub honeypot_templates: String,
+    pub hide_honeypots: bool,
+    pub record_transcripts: bool,
+    pub keep_raw: bool,
+    pub plain_motd: bool,
+    pub export_favicons: bool,
+    pub merge_servers_dat: bool,
     pub theme: ThemePref,
     pub language: LangPref,
 }
@@ -22,12 +39,28 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             ranges: Vec::new(),
+            imports: Vec::new(),
+            lan_discovery: false,
             java_ports: "25565".into(),
             bedrock_ports: "19132".into(),
             concurrency: "1024".into(),
             timeout_ms: "1500".into(),
             query_enabled: true,
             online_mode_check: false,
+            raknet_check: false,
+            rcon_check: false,
+            advisory_db: String::new(),
+            prefix_dir: String::new(),
+            geoip_db: String::new(),
+            honeypot_min_ports: "5".into(),
+            honeypot_player_factor: "10".into(),
+            honeypot_templates: String::new(),
+            hide_honeypots: false,
+            record_transcripts: false,
+            keep_raw: false,
+            plain_motd: false,
+            export_favicons: false,
+            merge_servers_dat: true,
             theme: ThemePref::default(),
             language: LangPref::default(),
         }
@@ -57,6 +90,13 @@ impl Config {
     }
 }
 
+// An imported scanner output file, read again on load rather than copied in.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct SavedImport {
+    pub path: PathBuf,
+}
+
 #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum ThemePref {
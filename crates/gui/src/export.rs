@@ -1,19 +1,157 @@
+use crate::components::address_list::Import;
+use scanner::servers_dat::{self, Entry};
+use scanner::transcript::Recorder;
+use scanner::transport::TokioNet;
+use scanner::types::{Edition, Endpoint};
+use std::path::Path;
+use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
 
-pub use scanner::export::to_csv;
+pub use scanner::export::{to_csv, to_json, to_ndjson};
 
-pub async fn save_dialog(csv: String) {
+/// Every format the results export offers, rendered before the dialog opens.
+pub struct ResultExports {
+    pub csv: String,
+    pub json: String,
+    pub ndjson: String,
+    pub servers_dat: Vec<Entry>,
+    // Add to the chosen servers.dat instead of replacing it
+    pub merge_servers_dat: bool,
+}
+
+// The chosen extension picks the rendering.
+pub async fn save_dialog(exports: ResultExports) {
     let file = rfd::AsyncFileDialog::new()
-        .set_file_name(default_filename(SystemTime::now()))
+        .set_file_name(default_filename(SystemTime::now(), "mc-scan", "csv"))
         .add_filter("CSV", &["csv"])
+        .add_filter("JSON", &["json"])
+        .add_filter("NDJSON", &["ndjson", "jsonl"])
+        .add_filter("Minecraft server list", &["dat"])
+        .save_file()
+        .await;
+    let Some(file) = file else {
+        return;
+    };
+    let path = file.path();
+    let body = if is_json(path) {
+        exports.json.into_bytes()
+    } else if has_extension(path, &["ndjson", "jsonl"]) {
+        exports.ndjson.into_bytes()
+    } else if has_extension(path, &["dat"]) {
+        // An unreadable file is left alone rather than overwritten.
+        let existing = match std::fs::read(path) {
+            Ok(bytes) if exports.merge_servers_dat => match servers_dat::read(&bytes) {
+                Some(entries) => entries,
+                None => return,
+            },
+            _ => Vec::new(),
+        };
+        servers_dat::write(&servers_dat::merge(existing, exports.servers_dat))
+    } else {
+        exports.csv.into_bytes()
+    };
+    let _ = std::fs::write(path, body);
+}
+
+/// The literal addresses in a picked `servers.dat` as Java endpoints, one per
+/// line; hostnames are skipped since the address list can't hold them.
+pub async fn open_servers_dat() -> Option<String> {
+    let file = rfd::AsyncFileDialog::new()
+        .add_filter("Minecraft server list", &["dat"])
+        .pick_file()
+        .await?;
+    let entries = servers_dat::read(&file.read().await)?;
+    let mut out = String::new();
+    for addr in entries.iter().filter_map(Entry::addr) {
+        let endpoint = Endpoint {
+            addr,
+            edition: Some(Edition::Java),
+        };
+        out.push_str(&endpoint.to_string());
+        out.push('\n');
+    }
+    Some(out)
+}
+
+/// The exact targets in a picked masscan, nmap or zmap output file; `None`
+/// when it lists no open port.
+pub async fn open_scan_output() -> Option<Import> {
+    let file = rfd::AsyncFileDialog::new()
+        .add_filter(
+            "masscan, nmap or zmap output",
+            &["json", "xml", "gnmap", "txt", "lst", "csv"],
+        )
+        .add_filter("All files", &["*"])
+        .pick_file()
+        .await?;
+    scan_output(file.path(), &file.read().await)
+}
+
+/// An import saved in the config, read again from where it was picked.
+pub fn reread_scan_output(path: &Path) -> Option<Import> {
+    match std::fs::read(path) {
+        Ok(bytes) => scan_output(path, &bytes),
+        Err(error) => {
+            tracing::warn!(path = %path.display(), %error, "import not reread");
+            None
+        }
+    }
+}
+
+fn scan_output(path: &Path, bytes: &[u8]) -> Option<Import> {
+    let targets = scanner::import::parse(&String::from_utf8_lossy(bytes));
+    (!targets.is_empty()).then(|| Import {
+        name: path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        path: path.to_path_buf(),
+        targets,
+    })
+}
+
+pub async fn save_audit_dialog(html: String, json: String) {
+    let file = rfd::AsyncFileDialog::new()
+        .set_file_name(default_filename(SystemTime::now(), "mc-scan-audit", "html"))
+        .add_filter("HTML", &["html", "htm"])
+        .add_filter("JSON", &["json"])
+        .save_file()
+        .await;
+    if let Some(file) = file {
+        let _ = std::fs::write(file.path(), if is_json(file.path()) { json } else { html });
+    }
+}
+
+fn is_json(path: &Path) -> bool {
+    has_extension(path, &["json"])
+}
+
+fn has_extension(path: &Path, exts: &[&str]) -> bool {
+    path.extension()
+        .is_some_and(|e| exts.iter().any(|x| e.eq_ignore_ascii_case(x)))
+}
+
+// Nothing answered means nothing worth attaching, so no dialog either.
+pub async fn save_transcript_dialog(recorder: Arc<Recorder<TokioNet>>) {
+    let transcript = recorder.transcript();
+    if transcript.exchanges.is_empty() {
+        return;
+    }
+    let file = rfd::AsyncFileDialog::new()
+        .set_file_name(default_filename(
+            SystemTime::now(),
+            "mc-scan-transcript",
+            "mctr",
+        ))
+        .add_filter("mc-scan transcript", &["mctr"])
         .save_file()
         .await;
     if let Some(file) = file {
-        let _ = std::fs::write(file.path(), csv);
+        let _ = std::fs::write(file.path(), transcript.to_bytes());
     }
 }
 
-fn default_filename(now: SystemTime) -> String {
+fn default_filename(now: SystemTime, stem: &str, ext: &str) -> String {
     let secs = now
         .duration_since(UNIX_EPOCH)
         .map(|d| d.as_secs())
@@ -37,7 +175,7 @@ fn default_filename(now: SystemTime) -> String {
         let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
         (y, m, d, hh, mm, ss)
     };
-    format!("mc-scan-{y:04}{mo:02}{d:02}-{h:02}{mi:02}{s:02}Z.csv")
+    format!("{stem}-{y:04}{mo:02}{d:02}-{h:02}{mi:02}{s:02}Z.{ext}")
 }
 
 #[cfg(test)]
@@ -48,7 +186,13 @@ mod tests {
     #[test]
     fn filename_formats_utc_timestamp() {
         let t = UNIX_EPOCH + Duration::from_secs(1_609_459_200);
-        assert_eq!(default_filename(t), "mc-scan-20210101-000000Z.csv");
-        assert_eq!(default_filename(UNIX_EPOCH), "mc-scan-19700101-000000Z.csv");
+        assert_eq!(
+            default_filename(t, "mc-scan", "csv"),
+            "mc-scan-20210101-000000Z.csv"
+        );
+        assert_eq!(
+            default_filename(UNIX_EPOCH, "mc-scan-audit", "html"),
+            "mc-scan-audit-19700101-000000Z.html"
+        );
     }
 }
@@ -10,6 +10,12 @@ pub static EN: Tr = Tr {
     parameters: "PARAMETERS",
     threads: "Threads",
     timeout_ms: "Timeout ms",
+    advisories: "Advisories",
+    advisories_hint: "advisories.json path",
+    prefix_dir: "Prefix data",
+    prefix_dir_hint: "folder with .mmdb / ip-ranges.json",
+    geoip_db: "GeoIP",
+    geoip_db_hint: "GeoLite2 .mmdb / .csv path or folder",
     subtitle: "Minecraft server scanner",
     found: "found",
     ip_ranges: "IP RANGES",
@@ -17,7 +23,7 @@ pub static EN: Tr = Tr {
     scan: "\u{25b6}  Scan",
     stop: "\u{25a0}  Stop",
     add_ranges_title: "Add ranges",
-    add_ranges_hint: "CIDR (10.0.0.0/8) \u{b7} range (1.2.3.4-1.2.3.100) \u{b7} single IP",
+    add_ranges_hint: "CIDR (10.0.0.0/8) \u{b7} range (1.2.3.4-1.2.3.100) \u{b7} single IP \u{b7} 1.2.3.4:25570/java \u{b7} asn:24940",
     ranges_rejected: "line(s) couldn't be parsed",
     add: "Add",
     cancel: "Cancel",
@@ -49,6 +55,39 @@ pub static EN: Tr = Tr {
     enrichment: "ADDITIONALLY",
     query_label: "Query (world/plugins/players)",
     online_mode_label: "Detect online-mode / white-list",
+    raknet_label: "Validate Bedrock RakNet handshake",
+    raknet: "RAKNET",
+    rcon_label: "Detect exposed RCON (port 25575)",
+    rcon: "RCON",
+    rcon_exposed: "Exposed",
+    rcon_closed: "Closed",
+    honeypots: "HONEYPOTS",
+    honeypot_min_ports: "Identical ports",
+    honeypot_player_factor: "Player factor",
+    honeypot_templates: "Templates",
+    honeypot_templates_hint: "honeypot status file path",
+    hide_honeypots: "Hide suspected honeypots",
+    honeypot: "SUSPECTED HONEYPOT",
+    honeypot_badge: "HONEYPOT",
+    honeypot_template: "Known honeypot status",
+    honeypot_players: "Impossible player count",
+    honeypot_identical: "ports with identical status",
+    honeypot_all_ports: "Answers on every scanned port",
+    export: "EXPORT",
+    export_favicons: "Include favicons in JSON exports",
+    merge_servers_dat: "Merge into an existing servers.dat",
+    import_servers_dat: "From servers.dat",
+    import_targets: "Import…",
+    diagnostics: "DIAGNOSTICS",
+    record_transcripts: "Record probe transcripts (saved after each scan)",
+    keep_raw: "Keep raw status responses (Raw tab, JSON export)",
+    plain_motd: "Show MOTDs as plain text",
+    overview: "Overview",
+    raw: "Raw",
+    lan_label: "LAN discovery",
+    hybrid: "HYBRID (GEYSER)",
+    endpoints: "ENDPOINTS",
+    dual_stack: "IPv4 + IPv6",
     online_mode: "ONLINE-MODE",
     online_yes: "Online",
     online_no: "Cracked",
@@ -70,4 +109,7 @@ pub static EN: Tr = Tr {
     online_any: "Any",
     version_hint: "e.g. 1.20",
     plugin_hint: "e.g. EssentialsX",
+    network: "NETWORK",
+    location: "LOCATION",
+    network_hint: "e.g. DE, AS24940, Hetzner",
 };
@@ -10,6 +10,12 @@ pub static JA: Tr = Tr {
     parameters: "パラメータ",
     threads: "スレッド",
     timeout_ms: "タイムアウト ms",
+    advisories: "脆弱性DB",
+    advisories_hint: "advisories.json のパス",
+    prefix_dir: "プレフィックス",
+    prefix_dir_hint: ".mmdb / ip-ranges.json のフォルダ",
+    geoip_db: "GeoIP",
+    geoip_db_hint: "GeoLite2 .mmdb / .csv のパスまたはフォルダ",
     subtitle: "Minecraft サーバースキャナー",
     found: "件見つかりました",
     ip_ranges: "IP 範囲",
@@ -17,7 +23,7 @@ pub static JA: Tr = Tr {
     scan: "\u{25b6}  スキャン",
     stop: "\u{25a0}  停止",
     add_ranges_title: "範囲を追加",
-    add_ranges_hint: "CIDR (10.0.0.0/8) \u{b7} 範囲 (1.2.3.4-1.2.3.100) \u{b7} 単一 IP",
+    add_ranges_hint: "CIDR (10.0.0.0/8) \u{b7} 範囲 (1.2.3.4-1.2.3.100) \u{b7} 単一 IP \u{b7} 1.2.3.4:25570/java \u{b7} asn:24940",
     ranges_rejected: "行を解析できませんでした",
     add: "追加",
     cancel: "キャンセル",
@@ -49,6 +55,39 @@ pub static JA: Tr = Tr {
     enrichment: "さらに",
     query_label: "Query（ワールド/プラグイン/プレイヤー）",
     online_mode_label: "online-mode / white-list を判定",
+    raknet_label: "Bedrock の RakNet ハンドシェイクを検証",
+    raknet: "RAKNET",
+    rcon_label: "公開された RCON を検出（ポート 25575）",
+    rcon: "RCON",
+    rcon_exposed: "公開",
+    rcon_closed: "閉鎖",
+    honeypots: "ハニーポット",
+    honeypot_min_ports: "同一ポート数",
+    honeypot_player_factor: "プレイヤー倍率",
+    honeypot_templates: "テンプレート",
+    honeypot_templates_hint: "ステータスファイルのパス",
+    hide_honeypots: "ハニーポットの疑いを隠す",
+    honeypot: "ハニーポットの疑い",
+    honeypot_badge: "ハニーポット",
+    honeypot_template: "既知のハニーポット応答",
+    honeypot_players: "ありえないプレイヤー数",
+    honeypot_identical: "ポートで同一ステータス",
+    honeypot_all_ports: "スキャンした全ポートで応答",
+    export: "エクスポート",
+    export_favicons: "JSONエクスポートにアイコンを含める",
+    merge_servers_dat: "既存のservers.datに追加",
+    import_servers_dat: "servers.datから",
+    import_targets: "インポート…",
+    diagnostics: "診断",
+    record_transcripts: "プローブの通信を記録（スキャン後に保存）",
+    keep_raw: "生のステータス応答を保持（Rawタブ、JSON書き出し）",
+    plain_motd: "MOTDをプレーンテキストで表示",
+    overview: "概要",
+    raw: "生データ",
+    lan_label: "LAN 内の検出",
+    hybrid: "ハイブリッド（GEYSER）",
+    endpoints: "エンドポイント",
+    dual_stack: "IPv4 + IPv6 デュアルスタック",
     online_mode: "ONLINE-MODE",
     online_yes: "正規",
     online_no: "非正規",
@@ -70,4 +109,7 @@ pub static JA: Tr = Tr {
     online_any: "すべて",
     version_hint: "例: 1.20",
     plugin_hint: "例: EssentialsX",
+    network: "ネットワーク",
+    location: "所在地",
+    network_hint: "例: DE, AS24940, Hetzner",
 };
@@ -43,6 +43,12 @@ pub struct Tr {
     pub parameters: &'static str,
     pub threads: &'static str,
     pub timeout_ms: &'static str,
+    pub advisories: &'static str,
+    pub advisories_hint: &'static str,
+    pub prefix_dir: &'static str,
+    pub prefix_dir_hint: &'static str,
+    pub geoip_db: &'static str,
+    pub geoip_db_hint: &'static str,
     pub subtitle: &'static str,
     pub found: &'static str,
     pub ip_ranges: &'static str,
@@ -82,6 +88,39 @@ pub struct Tr {
     pub enrichment: &'static str,
     pub query_label: &'static str,
     pub online_mode_label: &'static str,
+    pub raknet_label: &'static str,
+    pub raknet: &'static str,
+    pub rcon_label: &'static str,
+    pub rcon: &'static str,
+    pub rcon_exposed: &'static str,
+    pub rcon_closed: &'static str,
+    pub honeypots: &'static str,
+    pub honeypot_min_ports: &'static str,
+    pub honeypot_player_factor: &'static str,
+    pub honeypot_templates: &'static str,
+    pub honeypot_templates_hint: &'static str,
+    pub hide_honeypots: &'static str,
+    pub honeypot: &'static str,
+    pub honeypot_badge: &'static str,
+    pub honeypot_template: &'static str,
+    pub honeypot_players: &'static str,
+    pub honeypot_identical: &'static str,
+    pub honeypot_all_ports: &'static str,
+    pub export: &'static str,
+    pub export_favicons: &'static str,
+    pub merge_servers_dat: &'static str,
+    pub import_servers_dat: &'static str,
+    pub import_targets: &'static str,
+    pub diagnostics: &'static str,
+    pub record_transcripts: &'static str,
+    pub keep_raw: &'static str,
+    pub plain_motd: &'static str,
+    pub overview: &'static str,
+    pub raw: &'static str,
+    pub lan_label: &'static str,
+    pub hybrid: &'static str,
+    pub endpoints: &'static str,
+    pub dual_stack: &'static str,
     pub online_mode: &'static str,
     pub online_yes: &'static str,
     pub online_no: &'static str,
@@ -103,6 +142,9 @@ pub struct Tr {
     pub online_any: &'static str,
     pub version_hint: &'static str,
     pub plugin_hint: &'static str,
+    pub network: &'static str,
+    pub location: &'static str,
+    pub network_hint: &'static str,
 }
 
 pub fn tr(lang: Language) -> &'static Tr {
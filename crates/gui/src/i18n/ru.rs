@@ -10,6 +10,12 @@ pub static RU: Tr = Tr {
     parameters: "ПАРАМЕТРЫ",
     threads: "Потоки",
     timeout_ms: "Таймаут мс",
+    advisories: "Уязвимости",
+    advisories_hint: "путь к advisories.json",
+    prefix_dir: "Префиксы",
+    prefix_dir_hint: "папка с .mmdb / ip-ranges.json",
+    geoip_db: "GeoIP",
+    geoip_db_hint: "путь или папка GeoLite2 .mmdb / .csv",
     subtitle: "Сканер Minecraft-серверов",
     found: "найдено",
     ip_ranges: "IP-ДИАПАЗОНЫ",
@@ -17,7 +23,7 @@ pub static RU: Tr = Tr {
     scan: "\u{25b6}  Сканировать",
     stop: "\u{25a0}  Стоп",
     add_ranges_title: "Добавить диапазоны",
-    add_ranges_hint: "CIDR (10.0.0.0/8) \u{b7} диапазон (1.2.3.4-1.2.3.100) \u{b7} одиночный IP",
+    add_ranges_hint: "CIDR (10.0.0.0/8) \u{b7} диапазон (1.2.3.4-1.2.3.100) \u{b7} одиночный IP \u{b7} 1.2.3.4:25570/java \u{b7} asn:24940",
     ranges_rejected: "строк не распознано",
     add: "Добавить",
     cancel: "Отмена",
@@ -49,6 +55,39 @@ pub static RU: Tr = Tr {
     enrichment: "ДОПОЛНИТЕЛЬНО",
     query_label: "Query (мир/плагины/игроки)",
     online_mode_label: "Определять online-mode / white-list",
+    raknet_label: "Проверять RakNet-рукопожатие Bedrock",
+    raknet: "RAKNET",
+    rcon_label: "Искать открытый RCON (порт 25575)",
+    rcon: "RCON",
+    rcon_exposed: "Открыт",
+    rcon_closed: "Закрыт",
+    honeypots: "ХАНИПОТЫ",
+    honeypot_min_ports: "Одинаковых портов",
+    honeypot_player_factor: "Множитель игроков",
+    honeypot_templates: "Шаблоны",
+    honeypot_templates_hint: "путь к файлу статусов",
+    hide_honeypots: "Скрывать подозрительные ханипоты",
+    honeypot: "ПОХОЖЕ НА ХАНИПОТ",
+    honeypot_badge: "ХАНИПОТ",
+    honeypot_template: "Известный статус ханипота",
+    honeypot_players: "Невозможное число игроков",
+    honeypot_identical: "портов с одинаковым статусом",
+    honeypot_all_ports: "Отвечает на всех портах скана",
+    export: "ЭКСПОРТ",
+    export_favicons: "Включать иконки серверов в экспорт JSON",
+    merge_servers_dat: "Дополнять существующий servers.dat",
+    import_servers_dat: "Из servers.dat",
+    import_targets: "Импорт…",
+    diagnostics: "ДИАГНОСТИКА",
+    record_transcripts: "Записывать обмен с серверами (сохраняется после скана)",
+    keep_raw: "Сохранять исходные ответы статуса (вкладка «Raw», экспорт JSON)",
+    plain_motd: "Показывать MOTD без оформления",
+    overview: "Обзор",
+    raw: "Исходный",
+    lan_label: "Поиск в локальной сети",
+    hybrid: "ГИБРИД (GEYSER)",
+    endpoints: "АДРЕСА",
+    dual_stack: "IPv4 + IPv6",
     online_mode: "ONLINE-MODE",
     online_yes: "Online",
     online_no: "Cracked",
@@ -70,4 +109,7 @@ pub static RU: Tr = Tr {
     online_any: "Любой",
     version_hint: "напр. 1.20",
     plugin_hint: "напр. EssentialsX",
+    network: "СЕТЬ",
+    location: "РАСПОЛОЖЕНИЕ",
+    network_hint: "напр. DE, AS24940, Hetzner",
 };
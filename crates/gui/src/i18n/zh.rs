@@ -10,6 +10,12 @@ pub static ZH: Tr = Tr {
     parameters: "参数",
     threads: "线程",
     timeout_ms: "超时 ms",
+    advisories: "漏洞库",
+    advisories_hint: "advisories.json 路径",
+    prefix_dir: "前缀数据",
+    prefix_dir_hint: ".mmdb / ip-ranges.json 所在文件夹",
+    geoip_db: "GeoIP",
+    geoip_db_hint: "GeoLite2 .mmdb / .csv 路径或文件夹",
     subtitle: "Minecraft 服务器扫描器",
     found: "已发现",
     ip_ranges: "IP 范围",
@@ -17,7 +23,7 @@ pub static ZH: Tr = Tr {
     scan: "\u{25b6}  扫描",
     stop: "\u{25a0}  停止",
     add_ranges_title: "添加范围",
-    add_ranges_hint: "CIDR (10.0.0.0/8) \u{b7} 范围 (1.2.3.4-1.2.3.100) \u{b7} 单个 IP",
+    add_ranges_hint: "CIDR (10.0.0.0/8) \u{b7} 范围 (1.2.3.4-1.2.3.100) \u{b7} 单个 IP \u{b7} 1.2.3.4:25570/java \u{b7} asn:24940",
     ranges_rejected: "行无法解析",
     add: "添加",
     cancel: "取消",
@@ -49,6 +55,39 @@ pub static ZH: Tr = Tr {
     enrichment: "此外",
     query_label: "Query（世界/插件/玩家）",
     online_mode_label: "检测 online-mode / white-list",
+    raknet_label: "验证 Bedrock RakNet 握手",
+    raknet: "RAKNET",
+    rcon_label: "检测暴露的 RCON（端口 25575）",
+    rcon: "RCON",
+    rcon_exposed: "暴露",
+    rcon_closed: "关闭",
+    honeypots: "蜜罐",
+    honeypot_min_ports: "相同端口数",
+    honeypot_player_factor: "玩家倍数",
+    honeypot_templates: "模板",
+    honeypot_templates_hint: "蜜罐状态文件路径",
+    hide_honeypots: "隐藏疑似蜜罐",
+    honeypot: "疑似蜜罐",
+    honeypot_badge: "蜜罐",
+    honeypot_template: "已知蜜罐状态",
+    honeypot_players: "不可能的玩家数",
+    honeypot_identical: "个端口状态相同",
+    honeypot_all_ports: "所有扫描端口均有响应",
+    export: "导出",
+    export_favicons: "JSON 导出包含服务器图标",
+    merge_servers_dat: "合并到已有的 servers.dat",
+    import_servers_dat: "从 servers.dat",
+    import_targets: "导入…",
+    diagnostics: "诊断",
+    record_transcripts: "记录探测报文（每次扫描后保存）",
+    keep_raw: "保留原始状态响应（原始标签页、JSON 导出）",
+    plain_motd: "以纯文本显示 MOTD",
+    overview: "概览",
+    raw: "原始",
+    lan_label: "局域网发现",
+    hybrid: "混合服务器（GEYSER）",
+    endpoints: "端点",
+    dual_stack: "IPv4 + IPv6 双栈",
     online_mode: "ONLINE-MODE",
     online_yes: "正版",
     online_no: "离线",
@@ -70,4 +109,7 @@ pub static ZH: Tr = Tr {
     online_any: "任意",
     version_hint: "例如 1.20",
     plugin_hint: "例如 EssentialsX",
+    network: "网络",
+    location: "位置",
+    network_hint: "例如 DE, AS24940, Hetzner",
 };
@@ -1,5 +1,12 @@
-// Strips Minecraft `§X` formatting codes (and a dangling trailing `§`). The
-// scanner keeps them verbatim for CSV export; the GUI strips them for display.
+use crate::styles::{COLOR_THEME, COLOR_THEME_LIGHT};
+use iced::Font;
+use iced::font::{Style as FontStyle, Weight};
+use iced::widget::span;
+use iced::widget::text::Span;
+use scanner::motd::{Motd, Rgb};
+
+// Strips Minecraft `§X` formatting codes (and a dangling trailing `§`) from the
+// fields the scanner keeps verbatim, like version and world names.
 pub fn strip_section_codes(s: &str) -> String {
     let mut result = String::with_capacity(s.len());
     let mut chars = s.chars();
@@ -13,9 +20,112 @@ pub fn strip_section_codes(s: &str) -> String {
     result
 }
 
+// WCAG's floor for large text; MOTDs are mostly short, bold-ish lines.
+const MIN_CONTRAST: f32 = 3.0;
+// Same-width-ish glyphs, like the client's pick for `§k` text.
+const OBFUSCATED: &[u8] = b"ABCDEFGHJKLMNOPQRSTUVWXYZabcdeghkmnopqrsuvwxyz0123456789";
+
+/// How MOTDs are drawn: the theme, whether styles apply at all, and the
+/// animation frame for obfuscated text.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MotdLook {
+    pub dark: bool,
+    pub styled: bool,
+    pub frame: u64,
+}
+
+// A MOTD as rich-text spans on `base`. Uncolored text takes the widget's color.
+pub fn motd_spans<'a>(motd: &Motd, base: Font, look: MotdLook) -> Vec<Span<'a, (), Font>> {
+    if !look.styled {
+        return vec![span(motd.plain()).font(base)];
+    }
+    motd.spans
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            let mut font = base;
+            if s.style.bold {
+                font.weight = Weight::Bold;
+            }
+            if s.style.italic {
+                font.style = FontStyle::Italic;
+            }
+            let text = if s.style.obfuscated {
+                obfuscate(&s.text, look.frame, i as u64)
+            } else {
+                s.text.clone()
+            };
+            span(text)
+                .font(font)
+                .color_maybe(s.style.color.map(|rgb| motd_color(rgb, look.dark)))
+                .underline(s.style.underlined)
+                .strikethrough(s.style.strikethrough)
+        })
+        .collect()
+}
+
+// Server colors are picked for the client's dark backdrop; any that would be
+// hard to read on the current theme are mixed toward white or black.
+fn motd_color(rgb: Rgb, dark: bool) -> iced::Color {
+    let bg = background(dark);
+    let toward = if dark { 1.0 } else { 0.0 };
+    let mut rgb = [rgb.0, rgb.1, rgb.2].map(|v| v as f32 / 255.0);
+    for _ in 0..10 {
+        if contrast(rgb, bg) >= MIN_CONTRAST {
+            break;
+        }
+        rgb = rgb.map(|v| v + (toward - v) * 0.2);
+    }
+    iced::Color::from_rgb(rgb[0], rgb[1], rgb[2])
+}
+
+// The theme's background, which cards and dialogs stay close to.
+fn background(dark: bool) -> [f32; 3] {
+    let theme = if dark {
+        &*COLOR_THEME
+    } else {
+        &*COLOR_THEME_LIGHT
+    };
+    let bg = theme.palette().background;
+    [bg.r, bg.g, bg.b]
+}
+
+fn contrast(rgb: [f32; 3], bg: [f32; 3]) -> f32 {
+    let (a, b) = (luminance(rgb), luminance(bg));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+// WCAG relative luminance of an sRGB color.
+fn luminance(rgb: [f32; 3]) -> f32 {
+    let [r, g, b] = rgb.map(|v| {
+        if v <= 0.039_28 {
+            v / 12.92
+        } else {
+            ((v + 0.055) / 1.055).powf(2.4)
+        }
+    });
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+// Fresh glyphs every frame, spaces kept so the line doesn't reflow.
+fn obfuscate(text: &str, frame: u64, salt: u64) -> String {
+    let mut x = (frame.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ salt.wrapping_add(1)) | 1;
+    text.chars()
+        .map(|c| {
+            if c.is_whitespace() {
+                return c;
+            }
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            OBFUSCATED[(x % OBFUSCATED.len() as u64) as usize] as char
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
-    use super::strip_section_codes;
+    use super::*;
 
     #[test]
     fn strips_color_and_format_codes() {
@@ -31,4 +141,28 @@ mod tests {
     fn drops_a_dangling_section_sign() {
         assert_eq!(strip_section_codes("abc§"), "abc");
     }
+
+    #[test]
+    fn motd_colors_keep_contrast_on_both_themes() {
+        for dark in [true, false] {
+            for rgb in [Rgb(0, 0, 0), Rgb(0xFF, 0xFF, 0x55), Rgb(0x55, 0xFF, 0x55)] {
+                let c = motd_color(rgb, dark);
+                assert!(contrast([c.r, c.g, c.b], background(dark)) >= MIN_CONTRAST);
+            }
+        }
+        // Already readable colors are left alone.
+        assert_eq!(
+            motd_color(Rgb(0xFF, 0x55, 0x55), true),
+            iced::Color::from_rgb8(0xFF, 0x55, 0x55)
+        );
+    }
+
+    #[test]
+    fn obfuscation_changes_per_frame_but_keeps_shape() {
+        let a = obfuscate("ab cd", 1, 0);
+        let b = obfuscate("ab cd", 2, 0);
+        assert_ne!(a, b);
+        assert_eq!(a.chars().count(), 5);
+        assert_eq!(a.chars().nth(2), Some(' '));
+    }
 }
@@ -0,0 +1,392 @@
+//! Security audit rules over the fields probes already collect, plus a local
+//! advisory database for plugins and mods. Nothing here touches the network.
+
+use crate::types::{Edition, ServerInfo};
+use serde_json::{Value, json};
+
+// Java protocols that shipped a Log4j 2 without the CVE-2021-44228 fix:
+// 1.7.2 (4) through 1.18.0, which shares protocol 757 with the patched 1.18.1.
+const LOG4SHELL_PROTOCOLS: std::ops::RangeInclusive<i32> = 4..=757;
+
+// Bedrock 1.21.0; anything older misses a year of client-facing fixes.
+const CURRENT_BEDROCK_PROTOCOL: i32 = 685;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "info" => Some(Severity::Info),
+            "low" => Some(Severity::Low),
+            "medium" | "moderate" => Some(Severity::Medium),
+            "high" => Some(Severity::High),
+            "critical" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    // Stable id for filtering and diffing reports, e.g. "log4shell"
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advisory {
+    // Plugin name or mod id, matched case-insensitively
+    pub name: String,
+    // Version prefixes affected; empty = every version
+    pub versions: Vec<String>,
+    pub severity: Severity,
+    pub summary: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AdvisoryDb {
+    pub advisories: Vec<Advisory>,
+}
+
+impl AdvisoryDb {
+    /// Reads a JSON array of `{"name", "versions"?, "severity"?, "summary"?}`.
+    /// Entries without a name are skipped; severity defaults to medium.
+    pub fn from_json(raw: &str) -> Result<Self, serde_json::Error> {
+        let value: Value = serde_json::from_str(raw)?;
+        let advisories = value
+            .as_array()
+            .map(|entries| entries.iter().filter_map(parse_advisory).collect())
+            .unwrap_or_default();
+        Ok(Self { advisories })
+    }
+
+    fn matching<'a>(&'a self, name: &str, version: &str) -> impl Iterator<Item = &'a Advisory> {
+        let name = name.to_lowercase();
+        self.advisories.iter().filter(move |a| {
+            a.name.to_lowercase() == name
+                && (a.versions.is_empty() || a.versions.iter().any(|v| version.starts_with(v)))
+        })
+    }
+}
+
+fn parse_advisory(entry: &Value) -> Option<Advisory> {
+    let name = entry.get("name")?.as_str()?.trim();
+    if name.is_empty() {
+        return None;
+    }
+    let versions = entry
+        .get("versions")
+        .and_then(Value::as_array)
+        .map(|vs| {
+            vs.iter()
+                .filter_map(Value::as_str)
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    let severity = entry
+        .get("severity")
+        .and_then(Value::as_str)
+        .and_then(Severity::parse)
+        .unwrap_or(Severity::Medium);
+    let summary = entry
+        .get("summary")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    Some(Advisory {
+        name: name.to_string(),
+        versions,
+        severity,
+        summary,
+    })
+}
+
+/// Runs every rule against `s`, most severe finding first.
+pub fn audit(s: &ServerInfo, db: &AdvisoryDb) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    if s.edition == Edition::Java
+        && LOG4SHELL_PROTOCOLS.contains(&s.protocol)
+        && !(s.protocol == 757 && s.version.contains("1.18.1"))
+    {
+        findings.push(Finding {
+            rule: "log4shell",
+            severity: Severity::Critical,
+            detail: format!(
+                "protocol {} predates the Log4Shell fix unless the server jar was patched",
+                s.protocol
+            ),
+        });
+    }
+
+    if s.online_mode == Some(false) && s.whitelist != Some(true) {
+        findings.push(Finding {
+            rule: "cracked_no_whitelist",
+            severity: Severity::High,
+            detail: "offline mode without a whitelist lets anyone join under any name".into(),
+        });
+    }
+
+    if s.rcon_exposed == Some(true) {
+        findings.push(Finding {
+            rule: "rcon_exposed",
+            severity: Severity::High,
+            detail: "RCON answers on the public address".into(),
+        });
+    }
+
+    // For Java, `world` only comes from a Query full stat.
+    if s.edition == Edition::Java && s.world.is_some() {
+        let (severity, detail) = if s.plugins.is_empty() {
+            (Severity::Low, "Query is enabled".to_string())
+        } else {
+            (
+                Severity::Medium,
+                format!("Query is enabled and lists {} plugins", s.plugins.len()),
+            )
+        };
+        findings.push(Finding {
+            rule: "query_exposed",
+            severity,
+            detail,
+        });
+    }
+
+    if s.edition == Edition::Bedrock && s.protocol > 0 && s.protocol < CURRENT_BEDROCK_PROTOCOL {
+        findings.push(Finding {
+            rule: "outdated_bedrock",
+            severity: Severity::Medium,
+            detail: format!(
+                "Bedrock {} (protocol {}) is out of date",
+                s.version, s.protocol
+            ),
+        });
+    }
+
+    let components = s
+        .plugins
+        .iter()
+        .map(|p| split_plugin(p))
+        .chain(s.mods.iter().map(|m| (m.id.as_str(), m.version.as_str())));
+    for (name, version) in components {
+        for a in db.matching(name, version) {
+            findings.push(Finding {
+                rule: "advisory",
+                severity: a.severity,
+                detail: match a.summary.is_empty() {
+                    true => format!("{name} {version}").trim_end().to_string(),
+                    false => format!("{name} {version}: {}", a.summary),
+                },
+            });
+        }
+    }
+
+    findings.sort_by_key(|f| std::cmp::Reverse(f.severity));
+    findings
+}
+
+// Query lists plugins as "Name 1.2.3"; the name itself may contain spaces.
+fn split_plugin(p: &str) -> (&str, &str) {
+    match p.rsplit_once(' ') {
+        Some((name, version)) if version.starts_with(|c: char| c.is_ascii_digit() || c == 'v') => {
+            (name, version)
+        }
+        _ => (p, ""),
+    }
+}
+
+/// Machine-readable report: one entry per server with at least one finding.
+pub fn to_json(items: &[ServerInfo], db: &AdvisoryDb) -> String {
+    let servers: Vec<Value> = audited(items, db)
+        .map(|(s, findings)| {
+            json!({
+                "addr": s.addr.to_string(),
+                "edition": match s.edition {
+                    Edition::Java => "java",
+                    Edition::Bedrock => "bedrock",
+                },
+                "version": s.version,
+                "findings": findings
+                    .iter()
+                    .map(|f| json!({
+                        "rule": f.rule,
+                        "severity": f.severity.as_str(),
+                        "detail": f.detail,
+                    }))
+                    .collect::<Vec<_>>(),
+            })
+        })
+        .collect();
+    let report = json!({
+        "scanned": items.len(),
+        "affected": servers.len(),
+        "servers": servers,
+    });
+    serde_json::to_string_pretty(&report).unwrap_or_default()
+}
+
+/// Self-contained HTML page for handing the report to a customer.
+pub fn to_html(items: &[ServerInfo], db: &AdvisoryDb) -> String {
+    let mut rows = String::new();
+    let mut affected = 0;
+    for (s, findings) in audited(items, db) {
+        affected += 1;
+        for f in &findings {
+            rows.push_str(&format!(
+                "<tr class=\"{sev}\"><td>{addr}</td><td>{ver}</td><td>{sev}</td><td>{rule}</td><td>{detail}</td></tr>\n",
+                sev = f.severity.as_str(),
+                addr = escape_html(&s.addr.to_string()),
+                ver = escape_html(&s.version),
+                rule = f.rule,
+                detail = escape_html(&f.detail),
+            ));
+        }
+    }
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>mc-scan audit</title>\n\
+<style>body{{font-family:sans-serif}}table{{border-collapse:collapse}}td,th{{border:1px solid #ccc;padding:4px 8px}}\
+.critical{{background:#f8d0d0}}.high{{background:#fbe2c8}}.medium{{background:#fbf3c8}}</style></head><body>\n\
+<h1>mc-scan audit</h1>\n<p>{affected} of {total} servers have findings.</p>\n\
+<table><tr><th>Address</th><th>Version</th><th>Severity</th><th>Rule</th><th>Detail</th></tr>\n{rows}</table>\n</body></html>\n",
+        total = items.len(),
+    )
+}
+
+fn audited<'a>(
+    items: &'a [ServerInfo],
+    db: &'a AdvisoryDb,
+) -> impl Iterator<Item = (&'a ServerInfo, Vec<Finding>)> {
+    items
+        .iter()
+        .map(move |s| (s, audit(s, db)))
+        .filter(|(_, f)| !f.is_empty())
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::ModInfo;
+    use std::net::SocketAddr;
+
+    fn java(protocol: i32, version: &str) -> ServerInfo {
+        let mut s = ServerInfo::base(SocketAddr::from(([1, 2, 3, 4], 25565)), Edition::Java);
+        s.protocol = protocol;
+        s.version = version.into();
+        s
+    }
+
+    fn rules(findings: &[Finding]) -> Vec<&'static str> {
+        findings.iter().map(|f| f.rule).collect()
+    }
+
+    #[test]
+    fn log4shell_covers_1_7_through_1_18_0_only() {
+        let db = AdvisoryDb::default();
+        assert_eq!(rules(&audit(&java(340, "1.12.2"), &db)), ["log4shell"]);
+        assert_eq!(rules(&audit(&java(757, "Paper 1.18"), &db)), ["log4shell"]);
+        assert!(audit(&java(757, "Paper 1.18.1"), &db).is_empty());
+        assert!(audit(&java(758, "1.18.2"), &db).is_empty());
+    }
+
+    #[test]
+    fn cracked_server_needs_a_confirmed_whitelist() {
+        let db = AdvisoryDb::default();
+        let mut s = java(767, "1.21");
+        s.online_mode = Some(false);
+        assert_eq!(rules(&audit(&s, &db)), ["cracked_no_whitelist"]);
+        s.whitelist = Some(true);
+        assert!(audit(&s, &db).is_empty());
+    }
+
+    #[test]
+    fn findings_are_ordered_by_severity() {
+        let db = AdvisoryDb::default();
+        let mut s = java(340, "1.12.2");
+        s.world = Some("world".into());
+        s.rcon_exposed = Some(true);
+        let severities: Vec<_> = audit(&s, &db).iter().map(|f| f.severity).collect();
+        assert_eq!(
+            severities,
+            [Severity::Critical, Severity::High, Severity::Low]
+        );
+    }
+
+    #[test]
+    fn outdated_bedrock_is_flagged() {
+        let db = AdvisoryDb::default();
+        let mut s = ServerInfo::base(SocketAddr::from(([1, 2, 3, 4], 19132)), Edition::Bedrock);
+        s.protocol = 390;
+        assert_eq!(rules(&audit(&s, &db)), ["outdated_bedrock"]);
+        s.protocol = 800;
+        assert!(audit(&s, &db).is_empty());
+    }
+
+    #[test]
+    fn advisory_db_matches_plugins_and_mods_by_name_and_version() {
+        let db = AdvisoryDb::from_json(
+            r#"[
+                {"name": "FastLogin", "versions": ["1.10"], "severity": "high", "summary": "auth bypass"},
+                {"name": "somemod"},
+                {"versions": ["1.0"]}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(db.advisories.len(), 2);
+
+        let mut s = java(767, "1.21");
+        s.plugins = vec!["FastLogin 1.10.2".into(), "WorldEdit 7.3.0".into()];
+        s.mods = vec![ModInfo {
+            id: "somemod".into(),
+            version: "2.0".into(),
+        }];
+        let findings = audit(&s, &db);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].severity, Severity::High);
+        assert_eq!(findings[0].detail, "FastLogin 1.10.2: auth bypass");
+        assert_eq!(findings[1].severity, Severity::Medium);
+
+        s.plugins = vec!["FastLogin 1.11".into()];
+        assert_eq!(audit(&s, &db).len(), 1);
+    }
+
+    #[test]
+    fn reports_list_only_affected_servers() {
+        let db = AdvisoryDb::default();
+        let items = [java(340, "<b>1.12.2</b>"), java(767, "1.21")];
+        let json: Value = serde_json::from_str(&to_json(&items, &db)).unwrap();
+        assert_eq!(json["scanned"], 2);
+        assert_eq!(json["affected"], 1);
+        assert_eq!(json["servers"][0]["findings"][0]["rule"], "log4shell");
+
+        let html = to_html(&items, &db);
+        assert!(html.contains("1 of 2 servers"));
+        assert!(html.contains("<td>critical</td>"));
+        assert!(html.contains("<td>&lt;b&gt;1.12.2&lt;/b&gt;</td>"));
+    }
+}
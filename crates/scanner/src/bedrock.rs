@@ -1,17 +1,33 @@
 use super::Miss;
+use super::motd::Motd;
+use super::transport::{Datagram, Transport};
 use super::types::{Edition, ServerInfo};
 use std::net::SocketAddr;
-use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
-use tokio::net::UdpSocket;
-use tokio::time::timeout;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::{Instant, timeout};
 use tracing::{debug, trace};
 
-const MAGIC: [u8; 16] = [
+pub(crate) const MAGIC: [u8; 16] = [
     0x00, 0xFF, 0xFF, 0x00, 0xFE, 0xFE, 0xFE, 0xFE, 0xFD, 0xFD, 0xFD, 0xFD, 0x12, 0x34, 0x56, 0x78,
 ];
 
-pub async fn probe(addr: SocketAddr, timeout_ms: u64) -> Option<ServerInfo> {
-    match probe_inner(addr, timeout_ms).await {
+// RakNet protocol spoken by every Bedrock release since 1.16.
+const RAKNET_PROTOCOL: u8 = 11;
+
+// Tried largest first, like the client: a too-big request is dropped on the path.
+const MTU_STEPS: [u16; 3] = [1492, 1200, 576];
+
+#[derive(Debug, PartialEq)]
+pub struct OpenConnectionReply {
+    pub protocol: u8,
+    pub guid: u64,
+    pub security: Option<bool>,
+    pub cookie: Option<u32>,
+    pub mtu: Option<u16>,
+}
+
+pub async fn probe(net: &dyn Transport, addr: SocketAddr, dur: Duration) -> Option<ServerInfo> {
+    match probe_inner(net, addr, dur).await {
         Ok(info) => {
             debug!(%addr, edition = "bedrock", version = %info.version, online = info.online, "found");
             Some(info)
@@ -27,17 +43,17 @@ pub async fn probe(addr: SocketAddr, timeout_ms: u64) -> Option<ServerInfo> {
     }
 }
 
-async fn probe_inner(addr: SocketAddr, timeout_ms: u64) -> Result<ServerInfo, Miss> {
-    let dur = Duration::from_millis(timeout_ms);
+pub(crate) async fn probe_inner(
+    net: &dyn Transport,
+    addr: SocketAddr,
+    dur: Duration,
+) -> Result<ServerInfo, Miss> {
     let start = Instant::now();
 
-    let socket = UdpSocket::bind(super::local_bind_addr(&addr))
+    let socket = net
+        .datagram(addr)
         .await
         .map_err(|_| Miss::Unreachable("bind"))?;
-    socket
-        .connect(addr)
-        .await
-        .map_err(|_| Miss::Unreachable("connect"))?;
 
     timeout(dur, socket.send(&build_ping()))
         .await
@@ -54,7 +70,145 @@ async fn probe_inner(addr: SocketAddr, timeout_ms: u64) -> Result<ServerInfo, Mi
     parse_pong(&buf[..n], addr, latency_ms).ok_or(Miss::Unparsed("pong"))
 }
 
-fn build_ping() -> [u8; 33] {
+/// Probes the port the pong advertises for the scanned address family when it
+/// differs from the scanned one, and records it if the same server answers.
+pub async fn follow_advertised_port(
+    net: &dyn Transport,
+    info: &mut ServerInfo,
+    skip: &[u16],
+    dur: Duration,
+) {
+    let advertised = if info.addr.is_ipv4() {
+        info.port_v4
+    } else {
+        info.port_v6
+    };
+    let Some(port) = advertised.filter(|&p| p != 0 && p != info.addr.port() && !skip.contains(&p))
+    else {
+        return;
+    };
+    let target = SocketAddr::new(info.addr.ip(), port);
+    match probe(net, target, dur).await {
+        Some(other) if info.same_server(&other) => info.add_endpoint(target),
+        Some(_) => trace!(%target, "advertised port answered with another guid"),
+        None => trace!(%target, "advertised port is silent"),
+    }
+}
+
+/// Sends OpenConnectionRequest1 with shrinking MTUs. Only a real RakNet stack
+/// answers it, so a reply separates servers from reflected or spoofed pongs.
+pub async fn open_connection(
+    net: &dyn Transport,
+    addr: SocketAddr,
+    dur: Duration,
+) -> Option<OpenConnectionReply> {
+    match open_connection_inner(net, addr, dur).await {
+        Ok(reply) => {
+            debug!(%addr, protocol = reply.protocol, mtu = ?reply.mtu, "raknet confirmed");
+            Some(reply)
+        }
+        Err(Miss::Unparsed(stage)) => {
+            debug!(%addr, stage, "raknet reply did not parse");
+            None
+        }
+        Err(Miss::Unreachable(stage)) => {
+            trace!(%addr, stage, "raknet unreachable");
+            None
+        }
+    }
+}
+
+async fn open_connection_inner(
+    net: &dyn Transport,
+    addr: SocketAddr,
+    dur: Duration,
+) -> Result<OpenConnectionReply, Miss> {
+    let socket = net
+        .datagram(addr)
+        .await
+        .map_err(|_| Miss::Unreachable("bind"))?;
+    negotiate_mtu(socket.as_ref(), addr.is_ipv6(), dur).await
+}
+
+// Shared with the login probe, which continues the handshake on the same socket.
+pub(crate) async fn negotiate_mtu(
+    socket: &dyn Datagram,
+    ipv6: bool,
+    dur: Duration,
+) -> Result<OpenConnectionReply, Miss> {
+    let mut buf = [0u8; 2048];
+    for mtu in MTU_STEPS {
+        let request = build_open_connection_request(mtu, ipv6);
+        timeout(dur, socket.send(&request))
+            .await
+            .map_err(|_| Miss::Unreachable("send_timeout"))?
+            .map_err(|_| Miss::Unreachable("send"))?;
+        match timeout(dur, socket.recv(&mut buf)).await {
+            Ok(Ok(n)) => {
+                return parse_open_connection_reply(&buf[..n]).ok_or(Miss::Unparsed("ocr1"));
+            }
+            // ICMP port unreachable: nothing is listening, smaller MTUs won't help.
+            Ok(Err(_)) => return Err(Miss::Unreachable("recv")),
+            Err(_) => continue,
+        }
+    }
+    Err(Miss::Unreachable("recv_timeout"))
+}
+
+// The request is zero-padded so the whole datagram, headers included, is `mtu` bytes.
+fn build_open_connection_request(mtu: u16, ipv6: bool) -> Vec<u8> {
+    let headers = if ipv6 { 48 } else { 28 };
+    let len = (mtu as usize).saturating_sub(headers).max(18);
+    let mut buf = vec![0u8; len];
+    buf[0] = 0x05;
+    buf[1..17].copy_from_slice(&MAGIC);
+    buf[17] = RAKNET_PROTOCOL;
+    buf
+}
+
+// 0x06: MAGIC + guid(8) + security(1) [+ cookie(4)] + mtu(2).
+// 0x19: protocol(1) + MAGIC + guid(8), sent when our RakNet version is refused.
+fn parse_open_connection_reply(data: &[u8]) -> Option<OpenConnectionReply> {
+    match *data.first()? {
+        0x06 => {
+            if data.get(1..17)? != MAGIC {
+                return None;
+            }
+            let guid = u64::from_be_bytes(data.get(17..25)?.try_into().ok()?);
+            let security = *data.get(25)? != 0;
+            let cookie = match security {
+                true => Some(u32::from_be_bytes(data.get(26..30)?.try_into().ok()?)),
+                false => None,
+            };
+            let mtu_at = if security { 30 } else { 26 };
+            let mtu = u16::from_be_bytes(data.get(mtu_at..mtu_at + 2)?.try_into().ok()?);
+            Some(OpenConnectionReply {
+                protocol: RAKNET_PROTOCOL,
+                guid,
+                security: Some(security),
+                cookie,
+                mtu: Some(mtu),
+            })
+        }
+        0x19 => {
+            let protocol = *data.get(1)?;
+            if data.get(2..18)? != MAGIC {
+                return None;
+            }
+            let guid = u64::from_be_bytes(data.get(18..26)?.try_into().ok()?);
+            Some(OpenConnectionReply {
+                protocol,
+                guid,
+                security: None,
+                cookie: None,
+                mtu: None,
+            })
+        }
+        _ => None,
+    }
+}
+
+pub(crate) fn build_ping() -> [u8; 33] {
     let mut buf = [0u8; 33];
     buf[0] = 0x01;
     let ts = SystemTime::now()
@@ -66,7 +220,18 @@ fn build_ping() -> [u8; 33] {
     buf
 }
 
-fn parse_pong(data: &[u8], addr: SocketAddr, latency_ms: u64) -> Option<ServerInfo> {
+// Server side of the unconnected ping, for simulated hosts and tests.
+pub(crate) fn build_pong(motd: &str) -> Vec<u8> {
+    let mut p = vec![0x1C];
+    p.extend_from_slice(&0u64.to_be_bytes());
+    p.extend_from_slice(&0u64.to_be_bytes());
+    p.extend_from_slice(&MAGIC);
+    p.extend_from_slice(&(motd.len() as u16).to_be_bytes());
+    p.extend_from_slice(motd.as_bytes());
+    p
+}
+
+pub(crate) fn parse_pong(data: &[u8], addr: SocketAddr, latency_ms: u64) -> Option<ServerInfo> {
     if data.first()? != &0x1C || data.len() < 35 {
         return None;
     }
@@ -76,7 +241,9 @@ fn parse_pong(data: &[u8], addr: SocketAddr, latency_ms: u64) -> Option<ServerIn
         return None;
     }
     let raw = std::str::from_utf8(&data[35..end]).ok()?;
-    parse_motd(raw, addr, latency_ms)
+    let mut info = parse_motd(raw, addr, latency_ms)?;
+    info.raw_status = Some(raw.to_string());
+    Some(info)
 }
 
 fn parse_motd(raw: &str, addr: SocketAddr, latency_ms: u64) -> Option<ServerInfo> {
@@ -93,7 +260,7 @@ fn parse_motd(raw: &str, addr: SocketAddr, latency_ms: u64) -> Option<ServerInfo
     };
 
     let mut info = ServerInfo::base(addr, Edition::Bedrock);
-    info.motd = parts[1].to_string();
+    info.motd = Motd::from_bedrock(parts[1]);
     info.protocol = parts[2].parse().unwrap_or(0);
     info.version = parts[3].to_string();
     info.online = parts[4].parse().unwrap_or(0);
@@ -112,28 +279,22 @@ fn parse_motd(raw: &str, addr: SocketAddr, latency_ms: u64) -> Option<ServerInfo
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::motd::Rgb;
+    use crate::transport::TokioNet;
+    use tokio::net::UdpSocket;
 
     fn addr() -> SocketAddr {
         SocketAddr::from(([127, 0, 0, 1], 19132))
     }
 
     // 0x1C + timestamp(8) + GUID(8) + MAGIC(16) + len(u16) + motd.
-    fn pong(motd: &str) -> Vec<u8> {
-        let mut p = vec![0x1C];
-        p.extend_from_slice(&0u64.to_be_bytes());
-        p.extend_from_slice(&0u64.to_be_bytes());
-        p.extend_from_slice(&MAGIC);
-        p.extend_from_slice(&(motd.len() as u16).to_be_bytes());
-        p.extend_from_slice(motd.as_bytes());
-        p
-    }
-
     #[test]
     fn parses_full_pong() {
         let motd = "MCPE;§eDedicated Server;390;1.14.60;5;10;1234567890;Bedrock level;Survival;1;19132;19133";
-        let info = parse_pong(&pong(motd), addr(), 7).expect("should parse");
+        let info = parse_pong(&build_pong(motd), addr(), 7).expect("should parse");
         assert_eq!(info.edition, Edition::Bedrock);
-        assert_eq!(info.motd, "§eDedicated Server");
+        assert_eq!(info.motd.plain(), "Dedicated Server");
+        assert_eq!(info.motd.spans[0].style.color, Rgb::from_code('e'));
         assert_eq!(info.protocol, 390);
         assert_eq!(info.version, "1.14.60");
         assert_eq!(info.online, 5);
@@ -145,12 +306,14 @@ mod tests {
         assert_eq!(info.gamemode.as_deref(), Some("Survival"));
         assert_eq!(info.port_v4, Some(19132));
         assert_eq!(info.port_v6, Some(19133));
+        assert_eq!(info.raw_status.as_deref(), Some(motd));
     }
 
     #[test]
     fn parses_minimal_pong_without_optional_fields() {
-        let info = parse_pong(&pong("MCPE;Hi;390;1.14.60;0;20"), addr(), 0).expect("should parse");
-        assert_eq!(info.motd, "Hi");
+        let info =
+            parse_pong(&build_pong("MCPE;Hi;390;1.14.60;0;20"), addr(), 0).expect("should parse");
+        assert_eq!(info.motd.plain(), "Hi");
         assert_eq!(info.max_players, 20);
         assert_eq!(info.server_guid, None);
         assert_eq!(info.gamemode, None);
@@ -159,7 +322,7 @@ mod tests {
 
     #[test]
     fn rejects_wrong_packet_id() {
-        let mut bytes = pong("MCPE;Hi;390;1.0;0;1");
+        let mut bytes = build_pong("MCPE;Hi;390;1.0;0;1");
         bytes[0] = 0x00;
         assert!(parse_pong(&bytes, addr(), 0).is_none());
     }
@@ -171,13 +334,187 @@ mod tests {
 
     #[test]
     fn rejects_length_past_end_of_buffer() {
-        let mut bytes = pong("MCPE;Hi;390;1.0;0;1");
+        let mut bytes = build_pong("MCPE;Hi;390;1.0;0;1");
         bytes[33..35].copy_from_slice(&9999u16.to_be_bytes());
         assert!(parse_pong(&bytes, addr(), 0).is_none());
     }
 
+    async fn pong_server(motd: &'static str) -> SocketAddr {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = server.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 64];
+            while let Ok((_, peer)) = server.recv_from(&mut buf).await {
+                let _ = server.send_to(&build_pong(motd), peer).await;
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn follows_advertised_port_with_matching_guid() {
+        let other = pong_server("MCPE;Hi;390;1.0;0;1;77;L;S;1;0;0").await;
+        let mut info = ServerInfo::base(addr(), Edition::Bedrock);
+        info.server_guid = Some("77".into());
+        info.port_v4 = Some(other.port());
+        follow_advertised_port(&TokioNet, &mut info, &[], Duration::from_millis(300)).await;
+        assert_eq!(info.endpoints, vec![other]);
+
+        let mut skipped = ServerInfo::base(addr(), Edition::Bedrock);
+        skipped.server_guid = Some("77".into());
+        skipped.port_v4 = Some(other.port());
+        follow_advertised_port(
+            &TokioNet,
+            &mut skipped,
+            &[other.port()],
+            Duration::from_millis(300),
+        )
+        .await;
+        assert!(skipped.endpoints.is_empty());
+    }
+
+    #[tokio::test]
+    async fn ignores_advertised_port_serving_another_guid() {
+        let other = pong_server("MCPE;Hi;390;1.0;0;1;99;L;S;1;0;0").await;
+        let mut info = ServerInfo::base(addr(), Edition::Bedrock);
+        info.server_guid = Some("77".into());
+        info.port_v4 = Some(other.port());
+        follow_advertised_port(&TokioNet, &mut info, &[], Duration::from_millis(300)).await;
+        assert!(info.endpoints.is_empty());
+    }
+
     #[test]
     fn rejects_motd_with_too_few_fields() {
         assert!(parse_motd("MCPE;Hi;390", addr(), 0).is_none());
     }
+
+    fn reply1(guid: u64, security: bool, mtu: u16) -> Vec<u8> {
+        let mut p = vec![0x06];
+        p.extend_from_slice(&MAGIC);
+        p.extend_from_slice(&guid.to_be_bytes());
+        p.push(security as u8);
+        if security {
+            p.extend_from_slice(&[0xAA; 4]);
+        }
+        p.extend_from_slice(&mtu.to_be_bytes());
+        p
+    }
+
+    #[test]
+    fn open_connection_request_is_padded_to_mtu() {
+        let req = build_open_connection_request(1492, false);
+        assert_eq!(req.len(), 1492 - 28);
+        assert_eq!(req[0], 0x05);
+        assert_eq!(req[1..17], MAGIC);
+        assert_eq!(req[17], RAKNET_PROTOCOL);
+        assert_eq!(build_open_connection_request(1200, true).len(), 1200 - 48);
+    }
+
+    #[test]
+    fn parses_open_connection_reply() {
+        let reply = parse_open_connection_reply(&reply1(42, false, 1400)).expect("should parse");
+        assert_eq!(
+            reply,
+            OpenConnectionReply {
+                protocol: RAKNET_PROTOCOL,
+                guid: 42,
+                security: Some(false),
+                cookie: None,
+                mtu: Some(1400),
+            }
+        );
+    }
+
+    #[test]
+    fn open_connection_reply_skips_security_cookie() {
+        let reply = parse_open_connection_reply(&reply1(7, true, 1200)).expect("should parse");
+        assert_eq!(reply.security, Some(true));
+        assert_eq!(reply.cookie, Some(0xAAAA_AAAA));
+        assert_eq!(reply.mtu, Some(1200));
+    }
+
+    #[test]
+    fn incompatible_protocol_still_confirms_raknet() {
+        let mut p = vec![0x19, 10];
+        p.extend_from_slice(&MAGIC);
+        p.extend_from_slice(&9u64.to_be_bytes());
+        let reply = parse_open_connection_reply(&p).expect("should parse");
+        assert_eq!(reply.protocol, 10);
+        assert_eq!(reply.guid, 9);
+        assert_eq!(reply.mtu, None);
+    }
+
+    #[test]
+    fn open_connection_reply_rejects_bad_magic_and_truncation() {
+        let mut bad = reply1(1, false, 1400);
+        bad[1] = 0x01;
+        assert!(parse_open_connection_reply(&bad).is_none());
+        assert!(parse_open_connection_reply(&reply1(1, false, 1400)[..20]).is_none());
+        assert!(parse_open_connection_reply(&build_pong("MCPE;Hi;390;1.0;0;1")).is_none());
+    }
+
+    #[tokio::test]
+    async fn open_connection_falls_back_to_smaller_mtu() {
+        let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let target = server.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 2048];
+            loop {
+                let Ok((n, peer)) = server.recv_from(&mut buf).await else {
+                    return;
+                };
+                // Behave like a path that drops anything over 1200 bytes.
+                if n + 28 <= 1200 {
+                    let _ = server
+                        .send_to(&reply1(5, false, (n + 28) as u16), peer)
+                        .await;
+                }
+            }
+        });
+        let reply = open_connection(&TokioNet, target, Duration::from_millis(200))
+            .await
+            .expect("should confirm");
+        assert_eq!(reply.guid, 5);
+        assert_eq!(reply.mtu, Some(1200));
+    }
+
+    #[tokio::test]
+    async fn probes_fake_server() {
+        use testkit::BedrockServer;
+
+        let dur = Duration::from_millis(300);
+        let motd =
+            "MCPE;Fake;766;1.21.50;4;40;81985529216486895;Fake level;Creative;1;19132;19133;";
+        let real = BedrockServer::new(motd).raknet(true).spawn().await.unwrap();
+        let info = probe_inner(&TokioNet, real.addr(), dur)
+            .await
+            .expect("should answer");
+        assert_eq!(info.motd.plain(), "Fake");
+        assert_eq!(info.version, "1.21.50");
+        assert_eq!((info.online, info.max_players), (4, 40));
+        assert_eq!(info.gamemode.as_deref(), Some("Creative"));
+        let reply = open_connection(&TokioNet, real.addr(), dur)
+            .await
+            .expect("should confirm");
+        assert_eq!(reply.guid, 0x0123_4567_89AB_CDEF);
+        assert_eq!(reply.mtu, Some(1492));
+
+        // A pong reflector answers pings but has no RakNet stack behind it.
+        let reflector = BedrockServer::new(motd).spawn().await.unwrap();
+        assert!(probe_inner(&TokioNet, reflector.addr(), dur).await.is_ok());
+        assert!(
+            open_connection(&TokioNet, reflector.addr(), dur)
+                .await
+                .is_none()
+        );
+
+        let garbage = BedrockServer::raw_pong(*b"\x1cnot a pong")
+            .spawn()
+            .await
+            .unwrap();
+        assert_eq!(
+            probe_inner(&TokioNet, garbage.addr(), dur).await.err(),
+            Some(Miss::Unparsed("pong"))
+        );
+    }
 }
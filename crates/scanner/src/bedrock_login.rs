@@ -0,0 +1,1023 @@
+use super::Miss;
+use super::bedrock::{MAGIC, negotiate_mtu};
+use super::login::{LoginOutcome, offline_uuid};
+use super::transport::{Datagram, Transport};
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use flate2::Compression;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use p384::ecdsa::signature::Signer;
+use p384::ecdsa::{Signature, SigningKey};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::timeout;
+use tracing::{debug, trace};
+
+const ID_OPEN_CONNECTION_REQUEST_2: u8 = 0x07;
+const ID_OPEN_CONNECTION_REPLY_2: u8 = 0x08;
+const ID_CONNECTION_REQUEST: u8 = 0x09;
+const ID_CONNECTION_REQUEST_ACCEPTED: u8 = 0x10;
+const ID_NEW_INCOMING_CONNECTION: u8 = 0x13;
+const ID_DISCONNECT_NOTIFICATION: u8 = 0x15;
+const ID_ACK: u8 = 0xC0;
+const ID_GAME: u8 = 0xFE;
+const FRAME_SET: u8 = 0x84;
+
+const RELIABLE_ORDERED: u8 = 3;
+
+const PACKET_LOGIN: u32 = 0x01;
+const PACKET_PLAY_STATUS: u32 = 0x02;
+const PACKET_SERVER_TO_CLIENT_HANDSHAKE: u32 = 0x03;
+const PACKET_DISCONNECT: u32 = 0x05;
+const PACKET_NETWORK_SETTINGS: u32 = 0x8F;
+const PACKET_REQUEST_NETWORK_SETTINGS: u32 = 0xC1;
+
+// 1.19.30 added RequestNetworkSettings; 1.20.60 prefixed batches with the algorithm.
+const NETWORK_SETTINGS_PROTOCOL: i32 = 554;
+const COMPRESSION_HEADER_PROTOCOL: i32 = 649;
+
+// Frame-set id + sequence, frame header, split header, plus IP/UDP headers.
+const DATAGRAM_OVERHEAD: usize = 4 + 10 + 10 + 48;
+const MAX_SPLIT_COUNT: u32 = 64;
+// Split packets reassembled at once; a login has one or two in flight
+const MAX_PENDING_SPLITS: usize = 8;
+const MAX_BATCH_BYTES: u64 = 1024 * 1024;
+
+// DER SubjectPublicKeyInfo header for an uncompressed secp384r1 point.
+const P384_SPKI_PREFIX: [u8; 23] = [
+    0x30, 0x76, 0x30, 0x10, 0x06, 0x07, 0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x02, 0x01, 0x06, 0x05, 0x2B,
+    0x81, 0x04, 0x00, 0x22, 0x03, 0x62, 0x00,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LoginClass {
+    XboxAuthRequired,
+    NotWhitelisted,
+    Outdated,
+    Accepted,
+    Undetermined,
+}
+
+impl LoginClass {
+    fn outcome(self) -> LoginOutcome {
+        match self {
+            LoginClass::XboxAuthRequired => LoginOutcome {
+                online_mode: Some(true),
+                whitelist: None,
+            },
+            LoginClass::NotWhitelisted => LoginOutcome {
+                online_mode: Some(false),
+                whitelist: Some(true),
+            },
+            LoginClass::Accepted => LoginOutcome {
+                online_mode: Some(false),
+                whitelist: Some(false),
+            },
+            LoginClass::Outdated | LoginClass::Undetermined => LoginOutcome::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Batch {
+    Plain,
+    // Algorithm announced in NetworkSettings: 0 = zlib, 1 = snappy.
+    Headered(u16),
+    Deflated,
+}
+
+/// Joins as an offline (self-signed) player and classifies the server's answer.
+pub async fn probe(
+    net: &dyn Transport,
+    addr: SocketAddr,
+    protocol: i32,
+    version: &str,
+    dur: Duration,
+) -> LoginOutcome {
+    match probe_inner(net, addr, protocol, version, dur).await {
+        Ok(class) => {
+            debug!(%addr, ?class, "bedrock login");
+            class.outcome()
+        }
+        Err(Miss::Unparsed(stage)) => {
+            debug!(%addr, stage, "bedrock login reply did not parse");
+            LoginOutcome::default()
+        }
+        Err(Miss::Unreachable(stage)) => {
+            trace!(%addr, stage, "bedrock login unreachable");
+            LoginOutcome::default()
+        }
+    }
+}
+
+async fn probe_inner(
+    net: &dyn Transport,
+    addr: SocketAddr,
+    protocol: i32,
+    version: &str,
+    dur: Duration,
+) -> Result<LoginClass, Miss> {
+    let socket = net
+        .datagram(addr)
+        .await
+        .map_err(|_| Miss::Unreachable("bind"))?;
+
+    let reply1 = negotiate_mtu(socket.as_ref(), addr.is_ipv6(), dur).await?;
+    let mtu = reply1.mtu.ok_or(Miss::Unparsed("raknet_protocol"))?;
+    let client_guid = super::entropy_u64(0);
+
+    let mut session = Session::new(socket, dur);
+    session
+        .send_raw(&build_open_connection_request_2(
+            addr,
+            mtu,
+            client_guid,
+            reply1.cookie,
+        ))
+        .await?;
+    session.mtu = session.expect_reply_2().await?.min(mtu);
+
+    session
+        .send_reliable(&build_connection_request(client_guid))
+        .await?;
+    session
+        .expect("connection_accepted", |body| {
+            (body.first() == Some(&ID_CONNECTION_REQUEST_ACCEPTED)).then_some(())
+        })
+        .await?;
+    session
+        .send_reliable(&build_new_incoming_connection(addr))
+        .await?;
+
+    let mut batch = Batch::Deflated;
+    if protocol >= NETWORK_SETTINGS_PROTOCOL {
+        let request = encode_packet(PACKET_REQUEST_NETWORK_SETTINGS, &protocol.to_be_bytes());
+        session
+            .send_reliable(&encode_batch(&request, Batch::Plain))
+            .await?;
+        let algorithm = session
+            .expect("network_settings", |body| {
+                decode_batch(body, Batch::Plain)?
+                    .into_iter()
+                    .find(|(id, _)| *id == PACKET_NETWORK_SETTINGS)
+                    .and_then(|(_, p)| Some(u16::from_le_bytes(p.get(2..4)?.try_into().ok()?)))
+            })
+            .await?;
+        if protocol >= COMPRESSION_HEADER_PROTOCOL {
+            batch = Batch::Headered(algorithm);
+        }
+    }
+
+    let login = build_login(protocol, version, addr);
+    session.send_reliable(&encode_batch(&login, batch)).await?;
+    session
+        .expect("login_reply", |body| {
+            decode_batch(body, batch)?
+                .into_iter()
+                .find_map(|(id, p)| classify_game_packet(id, &p))
+        })
+        .await
+}
+
+fn classify_game_packet(id: u32, payload: &[u8]) -> Option<LoginClass> {
+    match id {
+        // Sent only once the login chain was accepted, before encryption starts.
+        PACKET_SERVER_TO_CLIENT_HANDSHAKE => Some(LoginClass::Accepted),
+        PACKET_PLAY_STATUS => {
+            let status = i32::from_be_bytes(payload.get(..4)?.try_into().ok()?);
+            Some(match status {
+                0 | 3 => LoginClass::Accepted,
+                1 | 2 => LoginClass::Outdated,
+                _ => LoginClass::Undetermined,
+            })
+        }
+        PACKET_DISCONNECT => Some(classify_disconnect(payload)),
+        _ => None,
+    }
+}
+
+// The Disconnect layout changed across versions; the reason key is all we need.
+fn classify_disconnect(payload: &[u8]) -> LoginClass {
+    let msg = String::from_utf8_lossy(payload).to_lowercase();
+    const AUTH: [&str; 2] = ["notauthenticated", "xbox live"];
+    const WHITELIST: [&str; 6] = [
+        "notallowed",
+        "not invited",
+        "allowlist",
+        "allow-list",
+        "whitelist",
+        "white-list",
+    ];
+    const OUTDATED: [&str; 2] = ["outdatedclient", "outdatedserver"];
+    if AUTH.iter().any(|n| msg.contains(n)) {
+        LoginClass::XboxAuthRequired
+    } else if WHITELIST.iter().any(|n| msg.contains(n)) {
+        LoginClass::NotWhitelisted
+    } else if OUTDATED.iter().any(|n| msg.contains(n)) {
+        LoginClass::Outdated
+    } else {
+        LoginClass::Undetermined
+    }
+}
+
+struct Session {
+    socket: Box<dyn Datagram>,
+    dur: Duration,
+    mtu: u16,
+    seq: u32,
+    reliable_index: u32,
+    order_index: u32,
+    split_id: u16,
+    splits: HashMap<u16, Vec<Option<Vec<u8>>>>,
+}
+
+impl Session {
+    fn new(socket: Box<dyn Datagram>, dur: Duration) -> Self {
+        Self {
+            socket,
+            dur,
+            mtu: 576,
+            seq: 0,
+            reliable_index: 0,
+            order_index: 0,
+            split_id: 0,
+            splits: HashMap::new(),
+        }
+    }
+
+    async fn send_raw(&self, datagram: &[u8]) -> Result<(), Miss> {
+        timeout(self.dur, self.socket.send(datagram))
+            .await
+            .map_err(|_| Miss::Unreachable("send_timeout"))?
+            .map_err(|_| Miss::Unreachable("send"))?;
+        Ok(())
+    }
+
+    // No retransmission: a lost frame just ends the probe as a timeout.
+    async fn send_reliable(&mut self, body: &[u8]) -> Result<(), Miss> {
+        let capacity = (self.mtu as usize)
+            .saturating_sub(DATAGRAM_OVERHEAD)
+            .max(64);
+        let order_index = self.order_index;
+        self.order_index += 1;
+        if body.len() <= capacity {
+            let frame = encode_frame(body, self.reliable_index, order_index, None);
+            self.reliable_index += 1;
+            return self.send_frame(&frame).await;
+        }
+        let id = self.split_id;
+        self.split_id = self.split_id.wrapping_add(1);
+        let count = body.len().div_ceil(capacity) as u32;
+        for (i, part) in body.chunks(capacity).enumerate() {
+            let split = Some((count, id, i as u32));
+            let frame = encode_frame(part, self.reliable_index, order_index, split);
+            self.reliable_index += 1;
+            self.send_frame(&frame).await?;
+        }
+        Ok(())
+    }
+
+    async fn send_frame(&mut self, frame: &[u8]) -> Result<(), Miss> {
+        let datagram = frame_set(self.seq, frame);
+        self.seq += 1;
+        self.send_raw(&datagram).await
+    }
+
+    async fn expect_reply_2(&mut self) -> Result<u16, Miss> {
+        let mut buf = [0u8; 2048];
+        let wait = async {
+            loop {
+                let n = self
+                    .socket
+                    .recv(&mut buf)
+                    .await
+                    .map_err(|_| Miss::Unreachable("recv"))?;
+                if n > 0 && buf[0] == ID_OPEN_CONNECTION_REPLY_2 {
+                    return parse_open_connection_reply_2(&buf[..n]).ok_or(Miss::Unparsed("ocr2"));
+                }
+            }
+        };
+        timeout(self.dur, wait)
+            .await
+            .map_err(|_| Miss::Unreachable("ocr2_timeout"))?
+    }
+
+    async fn expect<T>(
+        &mut self,
+        stage: &'static str,
+        mut pick: impl FnMut(&[u8]) -> Option<T>,
+    ) -> Result<T, Miss> {
+        let dur = self.dur;
+        let wait = async {
+            loop {
+                for body in self.recv_bodies().await? {
+                    if body.first() == Some(&ID_DISCONNECT_NOTIFICATION) {
+                        return Err(Miss::Unreachable("disconnected"));
+                    }
+                    if let Some(v) = pick(&body) {
+                        return Ok(v);
+                    }
+                }
+            }
+        };
+        timeout(dur, wait)
+            .await
+            .map_err(|_| Miss::Unreachable(stage))?
+    }
+
+    async fn recv_bodies(&mut self) -> Result<Vec<Vec<u8>>, Miss> {
+        let mut buf = [0u8; 2048];
+        let n = self
+            .socket
+            .recv(&mut buf)
+            .await
+            .map_err(|_| Miss::Unreachable("recv"))?;
+        let data = &buf[..n];
+        if n < 4 || !(0x80..=0x8D).contains(&data[0]) {
+            return Ok(Vec::new());
+        }
+        let seq = u32::from_le_bytes([data[1], data[2], data[3], 0]);
+        self.send_raw(&build_ack(seq)).await?;
+        let frames = parse_frame_set(data).ok_or(Miss::Unparsed("frame_set"))?;
+        Ok(frames
+            .into_iter()
+            .filter_map(|f| self.reassemble(f))
+            .collect())
+    }
+
+    fn reassemble(&mut self, frame: Frame) -> Option<Vec<u8>> {
+        let Some((count, id, index)) = frame.split else {
+            return Some(frame.body);
+        };
+        if count == 0 || count > MAX_SPLIT_COUNT || index >= count {
+            return None;
+        }
+        if !self.splits.contains_key(&id) && self.splits.len() >= MAX_PENDING_SPLITS {
+            return None;
+        }
+        let parts = self
+            .splits
+            .entry(id)
+            .or_insert_with(|| vec![None; count as usize]);
+        *parts.get_mut(index as usize)? = Some(frame.body);
+        if parts.iter().any(Option::is_none) {
+            return None;
+        }
+        let parts = self.splits.remove(&id)?;
+        Some(parts.into_iter().flatten().flatten().collect())
+    }
+}
+
+#[derive(Debug)]
+struct Frame {
+    split: Option<(u32, u16, u32)>,
+    body: Vec<u8>,
+}
+
+fn frame_set(seq: u32, frame: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(frame.len() + 4);
+    out.push(FRAME_SET);
+    write_u24(&mut out, seq);
+    out.extend_from_slice(frame);
+    out
+}
+
+fn encode_frame(
+    body: &[u8],
+    reliable_index: u32,
+    order_index: u32,
+    split: Option<(u32, u16, u32)>,
+) -> Vec<u8> {
+    let mut out = Vec::with_capacity(body.len() + 20);
+    out.push(RELIABLE_ORDERED << 5 | if split.is_some() { 0x10 } else { 0 });
+    out.extend_from_slice(&((body.len() * 8) as u16).to_be_bytes());
+    write_u24(&mut out, reliable_index);
+    write_u24(&mut out, order_index);
+    out.push(0); // order channel
+    if let Some((count, id, index)) = split {
+        out.extend_from_slice(&count.to_be_bytes());
+        out.extend_from_slice(&id.to_be_bytes());
+        out.extend_from_slice(&index.to_be_bytes());
+    }
+    out.extend_from_slice(body);
+    out
+}
+
+fn parse_frame_set(data: &[u8]) -> Option<Vec<Frame>> {
+    let mut cur = data.get(4..)?;
+    let mut frames = Vec::new();
+    while !cur.is_empty() {
+        let flags = take(&mut cur, 1)?[0];
+        let reliability = flags >> 5;
+        let bits = u16::from_be_bytes(take(&mut cur, 2)?.try_into().ok()?);
+        let len = (bits as usize).div_ceil(8);
+        if matches!(reliability, 2 | 3 | 4 | 6 | 7) {
+            take(&mut cur, 3)?; // reliable message index
+        }
+        if matches!(reliability, 1 | 4) {
+            take(&mut cur, 3)?; // sequence index
+        }
+        if matches!(reliability, 1 | 3 | 4 | 7) {
+            take(&mut cur, 4)?; // order index + channel
+        }
+        let split = if flags & 0x10 != 0 {
+            let count = u32::from_be_bytes(take(&mut cur, 4)?.try_into().ok()?);
+            let id = u16::from_be_bytes(take(&mut cur, 2)?.try_into().ok()?);
+            let index = u32::from_be_bytes(take(&mut cur, 4)?.try_into().ok()?);
+            Some((count, id, index))
+        } else {
+            None
+        };
+        let body = take(&mut cur, len)?.to_vec();
+        frames.push(Frame { split, body });
+    }
+    Some(frames)
+}
+
+fn build_ack(seq: u32) -> Vec<u8> {
+    let mut out = vec![ID_ACK, 0x00, 0x01, 0x01];
+    write_u24(&mut out, seq);
+    out
+}
+
+fn build_open_connection_request_2(
+    server: SocketAddr,
+    mtu: u16,
+    client_guid: u64,
+    cookie: Option<u32>,
+) -> Vec<u8> {
+    let mut out = vec![ID_OPEN_CONNECTION_REQUEST_2];
+    out.extend_from_slice(&MAGIC);
+    if let Some(cookie) = cookie {
+        out.extend_from_slice(&cookie.to_be_bytes());
+        out.push(0x00); // client doesn't offer its own security challenge
+    }
+    write_address(&mut out, &server);
+    out.extend_from_slice(&mtu.to_be_bytes());
+    out.extend_from_slice(&client_guid.to_be_bytes());
+    out
+}
+
+// 0x08: MAGIC + guid(8) + client address + mtu(2) + security(1).
+fn parse_open_connection_reply_2(data: &[u8]) -> Option<u16> {
+    if data.first()? != &ID_OPEN_CONNECTION_REPLY_2 || data.get(1..17)? != MAGIC {
+        return None;
+    }
+    let mtu_at = 25 + address_len(*data.get(25)?)?;
+    Some(u16::from_be_bytes(
+        data.get(mtu_at..mtu_at + 2)?.try_into().ok()?,
+    ))
+}
+
+fn build_connection_request(client_guid: u64) -> Vec<u8> {
+    let mut out = vec![ID_CONNECTION_REQUEST];
+    out.extend_from_slice(&client_guid.to_be_bytes());
+    out.extend_from_slice(&unix_millis().to_be_bytes());
+    out.push(0x00); // no security
+    out
+}
+
+fn build_new_incoming_connection(server: SocketAddr) -> Vec<u8> {
+    let mut out = vec![ID_NEW_INCOMING_CONNECTION];
+    write_address(&mut out, &server);
+    let unspecified = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0));
+    for _ in 0..20 {
+        write_address(&mut out, &unspecified);
+    }
+    let now = unix_millis();
+    out.extend_from_slice(&now.to_be_bytes());
+    out.extend_from_slice(&now.to_be_bytes());
+    out
+}
+
+fn write_address(out: &mut Vec<u8>, addr: &SocketAddr) {
+    match addr {
+        SocketAddr::V4(a) => {
+            out.push(4);
+            out.extend(a.ip().octets().iter().map(|b| !b));
+            out.extend_from_slice(&a.port().to_be_bytes());
+        }
+        SocketAddr::V6(a) => {
+            out.push(6);
+            out.extend_from_slice(&23u16.to_le_bytes()); // AF_INET6 as Windows numbers it
+            out.extend_from_slice(&a.port().to_be_bytes());
+            out.extend_from_slice(&a.flowinfo().to_be_bytes());
+            out.extend_from_slice(&a.ip().octets());
+            out.extend_from_slice(&a.scope_id().to_be_bytes());
+        }
+    }
+}
+
+fn address_len(version: u8) -> Option<usize> {
+    match version {
+        4 => Some(7),
+        6 => Some(29),
+        _ => None,
+    }
+}
+
+fn encode_packet(id: u32, payload: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(payload.len() + 2);
+    write_varint(&mut out, id);
+    out.extend_from_slice(payload);
+    out
+}
+
+fn encode_batch(packet: &[u8], batch: Batch) -> Vec<u8> {
+    let mut data = Vec::with_capacity(packet.len() + 5);
+    write_varint(&mut data, packet.len() as u32);
+    data.extend_from_slice(packet);
+
+    let mut out = vec![ID_GAME];
+    match batch {
+        Batch::Plain => out.extend_from_slice(&data),
+        Batch::Headered(0) => {
+            out.push(0x00);
+            out.extend_from_slice(&deflate(&data));
+        }
+        // Snappy servers still accept an explicitly uncompressed batch.
+        Batch::Headered(_) => {
+            out.push(0xFF);
+            out.extend_from_slice(&data);
+        }
+        Batch::Deflated => out.extend_from_slice(&deflate(&data)),
+    }
+    out
+}
+
+fn decode_batch(body: &[u8], batch: Batch) -> Option<Vec<(u32, Vec<u8>)>> {
+    let rest = body.strip_prefix(&[ID_GAME])?;
+    let data = match batch {
+        Batch::Plain => rest.to_vec(),
+        Batch::Headered(_) => match rest.split_first()? {
+            (0xFF, d) => d.to_vec(),
+            (0x00, d) => inflate(d)?,
+            _ => return None,
+        },
+        Batch::Deflated => inflate(rest)?,
+    };
+    let mut cur = &data[..];
+    let mut packets = Vec::new();
+    while !cur.is_empty() {
+        let len = read_varint(&mut cur)? as usize;
+        let mut packet = take(&mut cur, len)?;
+        let header = read_varint(&mut packet)?;
+        packets.push((header & 0x3FF, packet.to_vec()));
+    }
+    Some(packets)
+}
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut enc = DeflateEncoder::new(Vec::new(), Compression::default());
+    let _ = enc.write_all(data);
+    enc.finish().unwrap_or_default()
+}
+
+fn inflate(data: &[u8]) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    DeflateDecoder::new(data)
+        .take(MAX_BATCH_BYTES)
+        .read_to_end(&mut out)
+        .ok()?;
+    Some(out)
+}
+
+fn build_login(protocol: i32, version: &str, server: SocketAddr) -> Vec<u8> {
+    let name = env!("CARGO_PKG_NAME");
+    let key = throwaway_key();
+    let mut spki = P384_SPKI_PREFIX.to_vec();
+    spki.extend_from_slice(key.verifying_key().to_encoded_point(false).as_bytes());
+    let x5u = STANDARD.encode(spki);
+
+    let now = (unix_millis() / 1000) as u64;
+    let uuid = format_uuid(&offline_uuid(name));
+    let identity = sign_jwt(
+        &key,
+        &x5u,
+        &json!({
+            "extraData": { "displayName": name, "identity": uuid, "XUID": "" },
+            "identityPublicKey": x5u,
+            "nbf": now - 60,
+            "exp": now + 86_400,
+        }),
+    );
+    let chain = json!({ "chain": [identity] }).to_string();
+    let client_data = sign_jwt(&key, &x5u, &client_data(name, &uuid, version, server));
+
+    let mut request = Vec::with_capacity(chain.len() + client_data.len() + 8);
+    request.extend_from_slice(&(chain.len() as u32).to_le_bytes());
+    request.extend_from_slice(chain.as_bytes());
+    request.extend_from_slice(&(client_data.len() as u32).to_le_bytes());
+    request.extend_from_slice(client_data.as_bytes());
+
+    let mut payload = protocol.to_be_bytes().to_vec();
+    write_varint(&mut payload, request.len() as u32);
+    payload.extend_from_slice(&request);
+    encode_packet(PACKET_LOGIN, &payload)
+}
+
+// Servers validate the skin before spawning, so send a blank but well-formed one.
+fn client_data(name: &str, uuid: &str, version: &str, server: SocketAddr) -> Value {
+    let resource_patch = r#"{"geometry":{"default":"geometry.humanoid.custom"}}"#;
+    json!({
+        "ClientRandomId": super::entropy_u64(1) as i64,
+        "CurrentInputMode": 1,
+        "DefaultInputMode": 1,
+        "DeviceId": uuid,
+        "DeviceModel": "",
+        "DeviceOS": 7,
+        "GameVersion": version,
+        "GuiScale": 0,
+        "LanguageCode": "en_US",
+        "PlatformOfflineId": "",
+        "PlatformOnlineId": "",
+        "PlayFabId": "",
+        "SelfSignedId": uuid,
+        "ServerAddress": server.to_string(),
+        "ThirdPartyName": name,
+        "UIProfile": 0,
+        "SkinId": "Standard_Custom",
+        "SkinData": STANDARD.encode([0u8; 64 * 32 * 4]),
+        "SkinImageWidth": 64,
+        "SkinImageHeight": 32,
+        "SkinResourcePatch": STANDARD.encode(resource_patch),
+        "SkinGeometryData": "",
+        "SkinAnimationData": "",
+        "SkinColor": "#0",
+        "ArmSize": "wide",
+        "AnimatedImageData": [],
+        "PersonaPieces": [],
+        "PieceTintColors": [],
+        "CapeData": "",
+        "CapeId": "",
+        "CapeImageWidth": 0,
+        "CapeImageHeight": 0,
+        "CapeOnClassicSkin": false,
+        "PremiumSkin": false,
+        "PersonaSkin": false,
+        "TrustedSkin": false,
+    })
+}
+
+fn sign_jwt(key: &SigningKey, x5u: &str, claims: &Value) -> String {
+    let header = json!({ "alg": "ES384", "x5u": x5u });
+    let signing_input = format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(header.to_string()),
+        URL_SAFE_NO_PAD.encode(claims.to_string())
+    );
+    let signature: Signature = key.sign(signing_input.as_bytes());
+    format!(
+        "{signing_input}.{}",
+        URL_SAFE_NO_PAD.encode(signature.to_bytes())
+    )
+}
+
+// A one-off identity that never leaves the probe, so clock-seeded hashing is
+// enough entropy; a real CSPRNG would only add a dependency.
+fn throwaway_key() -> SigningKey {
+    let mut salt = 0u64;
+    loop {
+        let bytes: Vec<u8> = (0..6)
+            .flat_map(|i| super::entropy_u64(salt * 6 + i).to_be_bytes())
+            .collect();
+        if let Ok(key) = SigningKey::from_slice(&bytes) {
+            return key;
+        }
+        salt += 1;
+    }
+}
+
+fn format_uuid(b: &[u8; 16]) -> String {
+    let hex: String = b.iter().map(|x| format!("{x:02x}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+fn unix_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+fn take<'a>(cur: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+    if cur.len() < n {
+        return None;
+    }
+    let (head, rest) = cur.split_at(n);
+    *cur = rest;
+    Some(head)
+}
+
+fn write_u24(buf: &mut Vec<u8>, v: u32) {
+    buf.extend_from_slice(&v.to_le_bytes()[..3]);
+}
+
+fn write_varint(buf: &mut Vec<u8>, value: u32) {
+    let mut v = value;
+    loop {
+        if v & !0x7F == 0 {
+            buf.push(v as u8);
+            return;
+        }
+        buf.push((v & 0x7F | 0x80) as u8);
+        v >>= 7;
+    }
+}
+
+fn read_varint(buf: &mut &[u8]) -> Option<u32> {
+    let mut result = 0u32;
+    let mut shift = 0u32;
+    loop {
+        let (&b, rest) = buf.split_first()?;
+        *buf = rest;
+        result |= ((b & 0x7F) as u32) << shift;
+        if b & 0x80 == 0 {
+            return Some(result);
+        }
+        shift += 7;
+        if shift >= 35 {
+            return None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::TokioNet;
+    use p384::ecdsa::VerifyingKey;
+    use p384::ecdsa::signature::Verifier;
+    use tokio::net::UdpSocket;
+
+    #[test]
+    fn frames_roundtrip_through_parser() {
+        let mut data = frame_set(7, &encode_frame(b"hello", 1, 2, None));
+        data.extend_from_slice(&encode_frame(b"part", 3, 2, Some((2, 9, 1))));
+        let frames = parse_frame_set(&data).expect("should parse");
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].body, b"hello");
+        assert_eq!(frames[0].split, None);
+        assert_eq!(frames[1].body, b"part");
+        assert_eq!(frames[1].split, Some((2, 9, 1)));
+    }
+
+    #[test]
+    fn parse_frame_set_rejects_truncated_body() {
+        let data = frame_set(0, &encode_frame(b"hello", 0, 0, None));
+        assert!(parse_frame_set(&data[..data.len() - 1]).is_none());
+    }
+
+    #[test]
+    fn batches_roundtrip_in_every_mode() {
+        let packet = encode_packet(PACKET_PLAY_STATUS, &0i32.to_be_bytes());
+        for mode in [
+            Batch::Plain,
+            Batch::Headered(0),
+            Batch::Headered(1),
+            Batch::Deflated,
+        ] {
+            let decoded = decode_batch(&encode_batch(&packet, mode), mode).expect("roundtrip");
+            assert_eq!(decoded, vec![(PACKET_PLAY_STATUS, vec![0, 0, 0, 0])]);
+        }
+    }
+
+    #[test]
+    fn classifies_play_status_and_disconnect() {
+        let status = |s: i32| classify_game_packet(PACKET_PLAY_STATUS, &s.to_be_bytes());
+        assert_eq!(status(0), Some(LoginClass::Accepted));
+        assert_eq!(status(1), Some(LoginClass::Outdated));
+        assert_eq!(status(7), Some(LoginClass::Undetermined));
+        assert_eq!(
+            classify_game_packet(PACKET_SERVER_TO_CLIENT_HANDSHAKE, &[]),
+            Some(LoginClass::Accepted)
+        );
+        assert_eq!(
+            classify_disconnect(b"\x00\x00\x24disconnectionScreen.notAuthenticated"),
+            LoginClass::XboxAuthRequired
+        );
+        assert_eq!(
+            classify_disconnect(b"\x00You are not invited to play on this server."),
+            LoginClass::NotWhitelisted
+        );
+        assert_eq!(
+            classify_disconnect(b"\x00disconnectionScreen.outdatedServer"),
+            LoginClass::Outdated
+        );
+        assert_eq!(classify_disconnect(b"\x00bye"), LoginClass::Undetermined);
+        assert_eq!(classify_game_packet(0x8F, &[]), None);
+    }
+
+    #[test]
+    fn login_outcomes_map_onto_shared_fields() {
+        assert_eq!(
+            LoginClass::XboxAuthRequired.outcome().online_mode,
+            Some(true)
+        );
+        assert_eq!(LoginClass::NotWhitelisted.outcome().whitelist, Some(true));
+        assert_eq!(LoginClass::Accepted.outcome().whitelist, Some(false));
+        assert_eq!(LoginClass::Outdated.outcome(), LoginOutcome::default());
+    }
+
+    #[test]
+    fn address_encoding_matches_declared_lengths() {
+        for addr in ["1.2.3.4:19132", "[::1]:19133"] {
+            let addr: SocketAddr = addr.parse().unwrap();
+            let mut out = Vec::new();
+            write_address(&mut out, &addr);
+            assert_eq!(Some(out.len()), address_len(out[0]));
+        }
+        let mut v4 = Vec::new();
+        write_address(&mut v4, &"1.2.3.4:19132".parse().unwrap());
+        assert_eq!(v4, [4, !1, !2, !3, !4, 0x4A, 0xBC]);
+    }
+
+    // Pulls the JWTs back out of a Login packet and checks both signatures.
+    fn verify_login(packet: &[u8]) -> Value {
+        let mut cur = packet;
+        assert_eq!(read_varint(&mut cur), Some(PACKET_LOGIN));
+        let _protocol = take(&mut cur, 4).unwrap();
+        let _len = read_varint(&mut cur).unwrap();
+        let chain_len = u32::from_le_bytes(take(&mut cur, 4).unwrap().try_into().unwrap());
+        let chain: Value = serde_json::from_slice(take(&mut cur, chain_len as usize).unwrap())
+            .expect("chain json");
+        let client_len = u32::from_le_bytes(take(&mut cur, 4).unwrap().try_into().unwrap());
+        let client = std::str::from_utf8(take(&mut cur, client_len as usize).unwrap()).unwrap();
+
+        let identity = chain["chain"][0].as_str().unwrap();
+        let mut claims = Value::Null;
+        for jwt in [identity, client] {
+            let (input, sig) = jwt.rsplit_once('.').unwrap();
+            let header: Value = serde_json::from_slice(
+                &URL_SAFE_NO_PAD
+                    .decode(input.split('.').next().unwrap())
+                    .unwrap(),
+            )
+            .unwrap();
+            let spki = STANDARD.decode(header["x5u"].as_str().unwrap()).unwrap();
+            let key = VerifyingKey::from_sec1_bytes(&spki[P384_SPKI_PREFIX.len()..]).unwrap();
+            let sig = Signature::from_slice(&URL_SAFE_NO_PAD.decode(sig).unwrap()).unwrap();
+            key.verify(input.as_bytes(), &sig)
+                .expect("signature verifies");
+            if jwt == identity {
+                claims = serde_json::from_slice(
+                    &URL_SAFE_NO_PAD
+                        .decode(input.split('.').nth(1).unwrap())
+                        .unwrap(),
+                )
+                .unwrap();
+            }
+        }
+        claims
+    }
+
+    #[test]
+    fn login_chain_is_self_signed_and_verifiable() {
+        let packet = build_login(712, "1.21.20", "127.0.0.1:19132".parse().unwrap());
+        let claims = verify_login(&packet);
+        assert_eq!(claims["extraData"]["displayName"], env!("CARGO_PKG_NAME"));
+        assert!(claims["identityPublicKey"].is_string());
+    }
+
+    // Minimal RakNet + Bedrock server: answers the handshake, then replies to
+    // Login with `answer` (a game packet) after checking the chain signatures.
+    async fn fake_server(answer: Vec<u8>) -> SocketAddr {
+        fake_server_with(answer, false).await
+    }
+
+    // With `noise`, an empty datagram goes ahead of every reply.
+    async fn fake_server_with(answer: Vec<u8>, noise: bool) -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 2048];
+            let mut seq = 0u32;
+            let mut splits: HashMap<u16, Vec<Option<Vec<u8>>>> = HashMap::new();
+            let mut settings_sent = false;
+            loop {
+                let Ok((n, peer)) = socket.recv_from(&mut buf).await else {
+                    return;
+                };
+                let data = buf[..n].to_vec();
+                let mut replies: Vec<Vec<u8>> = Vec::new();
+                match data[0] {
+                    0x05 => {
+                        let mut p = vec![0x06];
+                        p.extend_from_slice(&MAGIC);
+                        p.extend_from_slice(&1u64.to_be_bytes());
+                        p.push(0);
+                        p.extend_from_slice(&((n + 28) as u16).to_be_bytes());
+                        let _ = socket.send_to(&p, peer).await;
+                    }
+                    ID_OPEN_CONNECTION_REQUEST_2 => {
+                        if noise {
+                            let _ = socket.send_to(&[], peer).await;
+                        }
+                        let mut p = vec![ID_OPEN_CONNECTION_REPLY_2];
+                        p.extend_from_slice(&MAGIC);
+                        p.extend_from_slice(&1u64.to_be_bytes());
+                        write_address(&mut p, &peer);
+                        p.extend_from_slice(&1400u16.to_be_bytes());
+                        p.push(0);
+                        let _ = socket.send_to(&p, peer).await;
+                    }
+                    0x80..=0x8D => {
+                        for frame in parse_frame_set(&data).unwrap() {
+                            let body = match frame.split {
+                                None => frame.body,
+                                Some((count, id, index)) => {
+                                    let parts = splits
+                                        .entry(id)
+                                        .or_insert_with(|| vec![None; count as usize]);
+                                    parts[index as usize] = Some(frame.body);
+                                    if parts.iter().any(Option::is_none) {
+                                        continue;
+                                    }
+                                    splits
+                                        .remove(&id)
+                                        .unwrap()
+                                        .into_iter()
+                                        .flatten()
+                                        .flatten()
+                                        .collect()
+                                }
+                            };
+                            match body[0] {
+                                ID_CONNECTION_REQUEST => {
+                                    replies.push(vec![ID_CONNECTION_REQUEST_ACCEPTED, 0, 0])
+                                }
+                                ID_GAME if !settings_sent => {
+                                    let packets = decode_batch(&body, Batch::Plain).unwrap();
+                                    assert_eq!(packets[0].0, PACKET_REQUEST_NETWORK_SETTINGS);
+                                    let mut settings = 256u16.to_le_bytes().to_vec();
+                                    settings.extend_from_slice(&0u16.to_le_bytes());
+                                    let p = encode_packet(PACKET_NETWORK_SETTINGS, &settings);
+                                    replies.push(encode_batch(&p, Batch::Plain));
+                                    settings_sent = true;
+                                }
+                                ID_GAME => {
+                                    let packets = decode_batch(&body, Batch::Headered(0)).unwrap();
+                                    verify_login(&encode_packet(packets[0].0, &packets[0].1));
+                                    replies.push(encode_batch(&answer, Batch::Headered(0)));
+                                }
+                                _ => {}
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                for body in replies {
+                    if noise {
+                        let _ = socket.send_to(&[], peer).await;
+                    }
+                    let frame = encode_frame(&body, seq, seq, None);
+                    let _ = socket.send_to(&frame_set(seq, &frame), peer).await;
+                    seq += 1;
+                }
+            }
+        });
+        addr
+    }
+
+    #[tokio::test]
+    async fn probe_detects_xbox_auth_requirement() {
+        let mut payload = vec![0x00];
+        payload.extend_from_slice(b"\x24disconnectionScreen.notAuthenticated");
+        let addr = fake_server(encode_packet(PACKET_DISCONNECT, &payload)).await;
+        let outcome = probe(&TokioNet, addr, 712, "1.21.20", Duration::from_millis(500)).await;
+        assert_eq!(outcome.online_mode, Some(true));
+        assert_eq!(outcome.whitelist, None);
+    }
+
+    #[tokio::test]
+    async fn probe_reports_offline_server_that_accepts_login() {
+        let addr = fake_server(encode_packet(PACKET_PLAY_STATUS, &0i32.to_be_bytes())).await;
+        let outcome = probe(&TokioNet, addr, 712, "1.21.20", Duration::from_millis(500)).await;
+        assert_eq!(
+            outcome,
+            LoginOutcome {
+                online_mode: Some(false),
+                whitelist: Some(false),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn probe_skips_empty_datagrams() {
+        let answer = encode_packet(PACKET_PLAY_STATUS, &0i32.to_be_bytes());
+        let addr = fake_server_with(answer, true).await;
+        let outcome = probe(&TokioNet, addr, 712, "1.21.20", Duration::from_millis(500)).await;
+        assert_eq!(outcome.online_mode, Some(false));
+    }
+}
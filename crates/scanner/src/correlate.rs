@@ -0,0 +1,107 @@
+//! Cross-edition correlation: a Java and a Bedrock server on one host that look
+//! alike are usually one world, with Geyser translating for Bedrock clients.
+
+use crate::types::{Edition, ServerInfo};
+use std::collections::HashSet;
+
+// Token overlap (Jaccard) above which two MOTDs count as the same text.
+const MOTD_SIMILARITY: f32 = 0.6;
+
+/// Whether `java` and `bedrock` look like the two faces of one hybrid server.
+pub fn is_hybrid_pair(java: &ServerInfo, bedrock: &ServerInfo) -> bool {
+    if java.edition != Edition::Java
+        || bedrock.edition != Edition::Bedrock
+        || java.addr.ip() != bedrock.addr.ip()
+    {
+        return false;
+    }
+    // Geyser relays the Java player counts, so both must be live to mean anything.
+    let same_counts =
+        java.online > 0 && java.online == bedrock.online && java.max_players == bedrock.max_players;
+    has_geyser_marker(java)
+        || has_geyser_marker(bedrock)
+        || same_counts
+        || similar_motd(java, bedrock)
+}
+
+// Default Geyser config names the Bedrock side "Geyser"; standalone builds say so in the version.
+fn has_geyser_marker(s: &ServerInfo) -> bool {
+    [Some(&s.version), Some(&s.motd.plain()), s.sub_motd.as_ref()]
+        .into_iter()
+        .flatten()
+        .any(|t| t.to_lowercase().contains("geyser"))
+}
+
+fn similar_motd(java: &ServerInfo, bedrock: &ServerInfo) -> bool {
+    // Bedrock shows a single line, which Geyser takes from the first Java one.
+    let java_plain = java.motd.plain();
+    let a = tokens(java_plain.lines().next().unwrap_or_default());
+    let b = tokens(&bedrock.motd.plain());
+    if a.is_empty() || b.is_empty() {
+        return false;
+    }
+    let shared = a.intersection(&b).count() as f32;
+    shared / a.union(&b).count() as f32 >= MOTD_SIMILARITY
+}
+
+fn tokens(plain: &str) -> HashSet<String> {
+    plain
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::SocketAddr;
+
+    fn server(ip: [u8; 4], port: u16, edition: Edition, motd: &str) -> ServerInfo {
+        let mut s = ServerInfo::base(SocketAddr::from((ip, port)), edition);
+        s.motd = motd.into();
+        s
+    }
+
+    #[test]
+    fn matching_motd_across_color_codes_is_hybrid() {
+        let java = server(
+            [1, 2, 3, 4],
+            25565,
+            Edition::Java,
+            "§aMy Cool Server\n§7join us",
+        );
+        let bedrock = server([1, 2, 3, 4], 19132, Edition::Bedrock, "My §lCool Server");
+        assert!(is_hybrid_pair(&java, &bedrock));
+    }
+
+    #[test]
+    fn geyser_sub_motd_is_enough() {
+        let java = server([1, 2, 3, 4], 25565, Edition::Java, "Lobby");
+        let mut bedrock = server([1, 2, 3, 4], 19132, Edition::Bedrock, "Another world");
+        bedrock.sub_motd = Some("Geyser".into());
+        assert!(is_hybrid_pair(&java, &bedrock));
+    }
+
+    #[test]
+    fn identical_live_counts_are_enough() {
+        let mut java = server([1, 2, 3, 4], 25565, Edition::Java, "A");
+        let mut bedrock = server([1, 2, 3, 4], 19132, Edition::Bedrock, "B");
+        (java.online, java.max_players) = (7, 100);
+        (bedrock.online, bedrock.max_players) = (7, 100);
+        assert!(is_hybrid_pair(&java, &bedrock));
+
+        // Two empty servers with default limits say nothing.
+        (java.online, bedrock.online) = (0, 0);
+        assert!(!is_hybrid_pair(&java, &bedrock));
+    }
+
+    #[test]
+    fn different_hosts_or_same_edition_never_pair() {
+        let java = server([1, 2, 3, 4], 25565, Edition::Java, "Same");
+        let elsewhere = server([1, 2, 3, 5], 19132, Edition::Bedrock, "Same");
+        let also_java = server([1, 2, 3, 4], 25566, Edition::Java, "Same");
+        assert!(!is_hybrid_pair(&java, &elsewhere));
+        assert!(!is_hybrid_pair(&java, &also_java));
+    }
+}
@@ -1,7 +1,37 @@
+//! Result exports. CSV keeps one row per server with lists joined into `;`
+//! cells. JSON (an array) and NDJSON (one object per line) carry every
+//! [`ServerInfo`] field in schema objects, version [`SCHEMA_VERSION`]:
+//!
+//! | key | value |
+//! |---|---|
+//! | `schema` | schema version, currently `1` |
+//! | `addr`, `edition` | `"ip:port"`, `"Java"` or `"Bedrock"`, as in the CSV |
+//! | `endpoints`, `hybrid_with` | other `"ip:port"`s of this server; the other edition's, or null |
+//! | `motd` | `{text, spans: [{text, color, bold, italic, underlined, strikethrough, obfuscated}]}`, `color` as `"#rrggbb"` or null |
+//! | `version`, `protocol`, `online`, `max_players`, `latency_ms`, `ping_history` | as probed |
+//! | `players` | `[{name, id}]`, `id` null when the server sent none |
+//! | `raw_status` | Java status JSON nested, Bedrock pong string; the key is absent unless raw statuses were asked for and kept |
+//! | `favicon` | data URL or null; the key is absent unless favicons were asked for |
+//! | `secure_chat`, `mods` | Java extras; mods as `[{id, version}]` |
+//! | `status_hash` | status fingerprint as 16 hex digits, or null |
+//! | `world`, `plugins` | from query |
+//! | `online_mode`, `whitelist`, `rcon_exposed` | true, false, or null when not checked |
+//! | `honeypot` | `[{signal}]`, plus `ports` for `identical_ports` |
+//! | `asn`, `as_org`, `country`, `city` | from the GeoIP database, or null; `country` as ISO 3166 alpha-2 |
+//! | `bedrock_edition`, `server_guid`, `sub_motd`, `gamemode`, `port_v4`, `port_v6` | Bedrock pong fields |
+//! | `raknet_protocol`, `raknet_mtu`, `raknet_security` | RakNet handshake, or null |
+//!
+//! Tri-state and optional fields are always present, as null when unknown.
+
+use crate::honeypot::Signal;
+use crate::motd::{Motd, Rgb};
 use crate::types::{Edition, ServerInfo};
+use serde_json::{Value, json};
+use std::net::SocketAddr;
 
 const HEADER: &str = "addr,edition,version,protocol,online,max_players,latency_ms,\
-online_mode,secure_chat,motd,players,world,plugins,mods,gamemode,bedrock_edition,sub_motd";
+online_mode,secure_chat,motd,players,world,plugins,mods,gamemode,bedrock_edition,sub_motd,endpoints,rcon_exposed,\
+asn,as_org,country,city";
 
 // RFC 4180 CSV; nested fields (players, plugins, mods) join into one `;`-cell.
 pub fn to_csv(items: &[ServerInfo]) -> String {
@@ -33,7 +63,7 @@ pub fn to_csv(items: &[ServerInfo]) -> String {
             s.latency_ms.to_string(),
             tristate(s.online_mode, "online", "cracked"),
             tristate(s.secure_chat, "true", "false"),
-            s.motd.clone(),
+            s.motd.plain(),
             s.samples.join(";"),
             s.world.clone().unwrap_or_default(),
             s.plugins.join(";"),
@@ -41,6 +71,16 @@ pub fn to_csv(items: &[ServerInfo]) -> String {
             s.gamemode.clone().unwrap_or_default(),
             s.bedrock_edition.clone().unwrap_or_default(),
             s.sub_motd.clone().unwrap_or_default(),
+            s.endpoints
+                .iter()
+                .map(|e| e.to_string())
+                .collect::<Vec<_>>()
+                .join(";"),
+            tristate(s.rcon_exposed, "true", "false"),
+            s.asn.map(|n| n.to_string()).unwrap_or_default(),
+            s.as_org.clone().unwrap_or_default(),
+            s.country.clone().unwrap_or_default(),
+            s.city.clone().unwrap_or_default(),
         ];
         for (i, field) in fields.iter().enumerate() {
             if i > 0 {
@@ -53,6 +93,123 @@ pub fn to_csv(items: &[ServerInfo]) -> String {
     out
 }
 
+/// Version of the JSON/NDJSON server object below; bumped when a field is
+/// renamed, removed or changes meaning, not when one is added.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Every server as one pretty-printed JSON array of schema objects.
+/// `include_raw` adds each server's raw status; `favicons` its icon.
+pub fn to_json(items: &[ServerInfo], include_raw: bool, favicons: bool) -> String {
+    let servers: Vec<Value> = items
+        .iter()
+        .map(|s| server_json(s, include_raw, favicons))
+        .collect();
+    serde_json::to_string_pretty(&servers).unwrap_or_default()
+}
+
+/// One compact schema object per line, for streaming and `jq`.
+pub fn to_ndjson(items: &[ServerInfo], include_raw: bool, favicons: bool) -> String {
+    let mut out = String::new();
+    for s in items {
+        out.push_str(&server_json(s, include_raw, favicons).to_string());
+        out.push('\n');
+    }
+    out
+}
+
+fn server_json(s: &ServerInfo, include_raw: bool, favicons: bool) -> Value {
+    let addrs = |list: &[SocketAddr]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+    let players: Vec<Value> = s
+        .samples
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let id = s.sample_ids.get(i).filter(|id| !id.is_empty());
+            json!({"name": name, "id": id})
+        })
+        .collect();
+    let mods: Vec<Value> = s
+        .mods
+        .iter()
+        .map(|m| json!({"id": m.id, "version": m.version}))
+        .collect();
+    let honeypot: Vec<Value> = s
+        .honeypot
+        .iter()
+        .map(|&signal| match signal {
+            Signal::IdenticalPorts(n) => json!({"signal": signal.as_str(), "ports": n}),
+            _ => json!({"signal": signal.as_str()}),
+        })
+        .collect();
+    let mut v = json!({
+        "schema": SCHEMA_VERSION,
+        "addr": s.addr.to_string(),
+        "edition": edition_name(&s.edition),
+        "endpoints": addrs(&s.endpoints),
+        "hybrid_with": s.hybrid_with.map(|a| a.to_string()),
+        "motd": motd_json(&s.motd),
+        "version": s.version,
+        "protocol": s.protocol,
+        "online": s.online,
+        "max_players": s.max_players,
+        "latency_ms": s.latency_ms,
+        "ping_history": s.ping_history,
+        "players": players,
+        "secure_chat": s.secure_chat,
+        "mods": mods,
+        "status_hash": s.status_hash.map(|h| format!("{h:016x}")),
+        "world": s.world,
+        "plugins": s.plugins,
+        "online_mode": s.online_mode,
+        "whitelist": s.whitelist,
+        "rcon_exposed": s.rcon_exposed,
+        "honeypot": honeypot,
+        "asn": s.asn,
+        "as_org": s.as_org,
+        "country": s.country,
+        "city": s.city,
+        "bedrock_edition": s.bedrock_edition,
+        "server_guid": s.server_guid,
+        "sub_motd": s.sub_motd,
+        "gamemode": s.gamemode,
+        "port_v4": s.port_v4,
+        "port_v6": s.port_v6,
+        "raknet_protocol": s.raknet_protocol,
+        "raknet_mtu": s.raknet_mtu,
+        "raknet_security": s.raknet_security,
+    });
+    if include_raw && let Some(raw) = &s.raw_status {
+        v["raw_status"] = match s.edition {
+            Edition::Java => serde_json::from_str(raw).unwrap_or_else(|_| json!(raw)),
+            Edition::Bedrock => json!(raw),
+        };
+    }
+    if favicons {
+        v["favicon"] = json!(s.favicon);
+    }
+    v
+}
+
+fn motd_json(motd: &Motd) -> Value {
+    let spans: Vec<Value> = motd
+        .spans
+        .iter()
+        .map(|span| {
+            let st = &span.style;
+            json!({
+                "text": span.text,
+                "color": st.color.map(|Rgb(r, g, b)| format!("#{r:02x}{g:02x}{b:02x}")),
+                "bold": st.bold,
+                "italic": st.italic,
+                "underlined": st.underlined,
+                "strikethrough": st.strikethrough,
+                "obfuscated": st.obfuscated,
+            })
+        })
+        .collect();
+    json!({"text": motd.plain(), "spans": spans})
+}
+
 fn edition_name(e: &Edition) -> &'static str {
     match e {
         Edition::Java => "Java",
@@ -123,9 +280,107 @@ mod tests {
         assert!(csv.contains("\"A, \"\"great\"\" server\nline2\""));
     }
 
+    #[test]
+    fn joins_extra_endpoints_into_one_column() {
+        let mut s = server(19132);
+        s.add_endpoint(SocketAddr::from(([1, 2, 3, 4], 19133)));
+        s.add_endpoint("[2001:db8::1]:19133".parse().unwrap());
+        let csv = to_csv(&[s]);
+        let row = csv.lines().nth(1).unwrap();
+        assert!(row.ends_with(",1.2.3.4:19133;[2001:db8::1]:19133,,,,,"));
+    }
+
+    #[test]
+    fn rcon_exposure_comes_before_geoip_columns() {
+        let mut s = server(25565);
+        s.rcon_exposed = Some(true);
+        let csv = to_csv(&[s]);
+        assert!(csv.lines().nth(1).unwrap().ends_with(",true,,,,"));
+    }
+
+    #[test]
+    fn geoip_fields_are_the_last_columns() {
+        let mut s = server(25565);
+        s.asn = Some(24940);
+        s.as_org = Some("Hetzner Online GmbH".into());
+        s.country = Some("DE".into());
+        s.city = Some("Falkenstein".into());
+        let csv = to_csv(std::slice::from_ref(&s));
+        assert!(
+            csv.lines()
+                .nth(1)
+                .unwrap()
+                .ends_with(",24940,Hetzner Online GmbH,DE,Falkenstein")
+        );
+        let v = server_json(&s, false, false);
+        assert_eq!(v["asn"], 24940);
+        assert_eq!(v["country"], "DE");
+        assert!(server_json(&server(25565), false, false)["city"].is_null());
+    }
+
     #[test]
     fn empty_results_still_have_a_header() {
         let csv = to_csv(&[]);
         assert_eq!(csv, format!("{HEADER}\n"));
     }
+
+    #[test]
+    fn json_nests_raw_status_and_keeps_lists() {
+        let mut java = server(25565);
+        java.raw_status = Some(r#"{"isModded":true}"#.into());
+        java.motd = "§aHi".into();
+        java.samples = vec!["alice".into(), "bob".into()];
+        java.sample_ids = vec!["0000-1".into(), String::new()];
+        java.honeypot = vec![Signal::IdenticalPorts(4)];
+        java.status_hash = Some(0xAB);
+        let mut bedrock =
+            ServerInfo::base(SocketAddr::from(([1, 2, 3, 4], 19132)), Edition::Bedrock);
+        bedrock.raw_status = Some("MCPE;Hi;390;1.14.60;0;20".into());
+
+        let items = [java, bedrock];
+        let v: Value = serde_json::from_str(&to_json(&items, true, false)).unwrap();
+        assert_eq!(v[0]["schema"], SCHEMA_VERSION);
+        assert_eq!(v[0]["addr"], "1.2.3.4:25565");
+        assert_eq!(v[0]["raw_status"]["isModded"], true);
+        assert_eq!(v[0]["motd"]["text"], "Hi");
+        assert_eq!(v[0]["motd"]["spans"][0]["color"], "#55ff55");
+        assert_eq!(v[0]["players"][0], json!({"name": "alice", "id": "0000-1"}));
+        assert_eq!(v[0]["players"][1]["id"], Value::Null);
+        assert_eq!(
+            v[0]["honeypot"][0],
+            json!({"signal": "identical_ports", "ports": 4})
+        );
+        assert_eq!(v[0]["status_hash"], "00000000000000ab");
+        assert!(v[0]["whitelist"].is_null());
+        assert_eq!(v[1]["edition"], "Bedrock");
+        assert_eq!(v[1]["raw_status"], "MCPE;Hi;390;1.14.60;0;20");
+
+        let without: Value = serde_json::from_str(&to_json(&items, false, false)).unwrap();
+        assert!(without[0].get("raw_status").is_none());
+    }
+
+    #[test]
+    fn favicons_only_when_asked() {
+        let mut s = server(25565);
+        s.favicon = Some("data:image/png;base64,AAAA".into());
+        let items = [s];
+        let with: Value = serde_json::from_str(&to_json(&items, false, true)).unwrap();
+        assert_eq!(with[0]["favicon"], "data:image/png;base64,AAAA");
+        let without: Value = serde_json::from_str(&to_json(&items, false, false)).unwrap();
+        assert!(without[0].get("favicon").is_none());
+    }
+
+    #[test]
+    fn ndjson_writes_one_object_per_line() {
+        let items = [server(25565), server(25566)];
+        let out = to_ndjson(&items, false, false);
+        let lines: Vec<Value> = out
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1]["addr"], "1.2.3.4:25566");
+        let array: Value = serde_json::from_str(&to_json(&items, false, false)).unwrap();
+        assert_eq!(array[1], lines[1]);
+    }
 }
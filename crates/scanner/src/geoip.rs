@@ -0,0 +1,327 @@
+//! Offline hosting and location lookups for results: ASN, AS organisation,
+//! country and city from local databases, never the network.
+//!
+//! A database is a MaxMind-format file (GeoLite2 ASN/Country/City, DB-IP lite,
+//! ipinfo) or a CSV with a `network` column, or `start_ip` and `end_ip`, and
+//! any of these:
+//!
+//! | Field     | MMDB keys                                              | CSV columns                                        |
+//! |-----------|--------------------------------------------------------|----------------------------------------------------|
+//! | `asn`     | `autonomous_system_number`, `asn` (number or `AS…`)    | `autonomous_system_number`, `asn`                  |
+//! | `as_org`  | `autonomous_system_organization`, `as_name`            | `autonomous_system_organization`, `as_name`, `as_org` |
+//! | `country` | `country.iso_code`, `country_code`, `country`          | `country_iso_code`, `country_code`, `country`      |
+//! | `city`    | `city.names.en`, `city`                                | `city_name`, `city`                                |
+//!
+//! GeoLite2 splits ASN and location into separate files, so a [`GeoDb`] holds
+//! several; each field comes from the first one that knows it.
+
+use crate::mmdb;
+use crate::prefixes::{
+    ASN_COLUMNS, COUNTRY_COLUMNS, csv_fields, parse_asn, record_asn, record_country,
+};
+use crate::types::ServerInfo;
+use ipnet::IpNet;
+use serde_json::Value;
+use std::io;
+use std::net::IpAddr;
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Geo {
+    pub asn: Option<u32>,
+    pub as_org: Option<String>,
+    pub country: Option<String>,
+    pub city: Option<String>,
+}
+
+impl Geo {
+    fn from_record(record: &Value) -> Self {
+        let text = |v: &Value| v.as_str().filter(|s| !s.is_empty()).map(str::to_string);
+        Self {
+            asn: record_asn(record),
+            as_org: text(&record["autonomous_system_organization"])
+                .or_else(|| text(&record["as_name"])),
+            country: record_country(record),
+            city: text(&record["city"]["names"]["en"]).or_else(|| text(&record["city"])),
+        }
+    }
+
+    fn fill_from(&mut self, other: Geo) {
+        self.asn = self.asn.or(other.asn);
+        self.as_org = self.as_org.take().or(other.as_org);
+        self.country = self.country.take().or(other.country);
+        self.city = self.city.take().or(other.city);
+    }
+
+    fn is_complete(&self) -> bool {
+        self.asn.is_some() && self.as_org.is_some() && self.country.is_some() && self.city.is_some()
+    }
+}
+
+enum Source {
+    Mmdb(mmdb::Reader<Vec<u8>>),
+    // Inclusive spans per family, sorted by start
+    Csv {
+        v4: Vec<(u128, u128, Geo)>,
+        v6: Vec<(u128, u128, Geo)>,
+    },
+}
+
+impl Source {
+    fn lookup(&self, ip: IpAddr) -> Option<Geo> {
+        match self {
+            Self::Mmdb(reader) => reader.lookup(ip).map(|record| Geo::from_record(&record)),
+            Self::Csv { v4, v6 } => {
+                let (spans, key) = match ip.to_canonical() {
+                    IpAddr::V4(ip) => (v4, u32::from(ip) as u128),
+                    IpAddr::V6(ip) => (v6, u128::from(ip)),
+                };
+                let at = spans.partition_point(|(start, _, _)| *start <= key);
+                let (_, end, geo) = spans.get(at.checked_sub(1)?)?;
+                (key <= *end).then(|| geo.clone())
+            }
+        }
+    }
+}
+
+/// The databases results are looked up in; empty means no enrichment.
+#[derive(Default)]
+pub struct GeoDb {
+    sources: Vec<Source>,
+}
+
+impl std::fmt::Debug for GeoDb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GeoDb")
+            .field("sources", &self.sources.len())
+            .finish()
+    }
+}
+
+impl GeoDb {
+    /// A database file, or every `.mmdb` and `.csv` in a folder. A file
+    /// that isn't a database is an error; in a folder it's skipped.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut db = Self::default();
+        if !path.is_dir() {
+            db.add(std::fs::read(path)?)?;
+            return Ok(db);
+        }
+        let mut files: Vec<_> = std::fs::read_dir(path)?
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| {
+                let ext = p.extension().and_then(|e| e.to_str()).unwrap_or_default();
+                ext.eq_ignore_ascii_case("mmdb") || ext.eq_ignore_ascii_case("csv")
+            })
+            .collect();
+        files.sort();
+        for file in files {
+            if let Err(error) = std::fs::read(&file).and_then(|bytes| db.add(bytes)) {
+                tracing::warn!(path = %file.display(), %error, "geoip file skipped");
+            }
+        }
+        Ok(db)
+    }
+
+    /// Adds an MMDB file's bytes, or a CSV's.
+    pub fn add(&mut self, bytes: Vec<u8>) -> io::Result<()> {
+        // The metadata marker isn't valid UTF-8, so text can only be a CSV.
+        let source = match String::from_utf8(bytes) {
+            Ok(text) => csv_source(&text),
+            Err(bytes) => mmdb::Reader::new(bytes.into_bytes()).map(Source::Mmdb),
+        };
+        let Some(source) = source else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "neither a MaxMind database nor a CSV with networks",
+            ));
+        };
+        self.sources.push(source);
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty()
+    }
+
+    pub fn lookup(&self, ip: IpAddr) -> Geo {
+        let mut geo = Geo::default();
+        for source in &self.sources {
+            if let Some(found) = source.lookup(ip) {
+                geo.fill_from(found);
+            }
+            if geo.is_complete() {
+                break;
+            }
+        }
+        geo
+    }
+
+    /// Sets `server`'s ASN and location fields from its address.
+    pub fn annotate(&self, server: &mut ServerInfo) {
+        let geo = self.lookup(server.addr.ip());
+        server.asn = geo.asn;
+        server.as_org = geo.as_org;
+        server.country = geo.country;
+        server.city = geo.city;
+    }
+}
+
+// Rows of a CSV database as sorted spans; None when the header has no network
+// or no field worth keeping.
+fn csv_source(text: &str) -> Option<Source> {
+    let mut lines = text.lines();
+    let header: Vec<String> = csv_fields(lines.next()?)
+        .into_iter()
+        .map(|h| h.trim().to_ascii_lowercase())
+        .collect();
+    let column = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| header.iter().position(|h| h == name))
+    };
+    let network = column(&["network"]);
+    let span = column(&["start_ip"]).zip(column(&["end_ip"]));
+    let asn = column(ASN_COLUMNS);
+    let as_org = column(&["autonomous_system_organization", "as_name", "as_org"]);
+    let country = column(COUNTRY_COLUMNS);
+    let city = column(&["city_name", "city"]);
+    if network.is_none() && span.is_none()
+        || [asn, as_org, country, city].iter().all(Option::is_none)
+    {
+        return None;
+    }
+    let (mut v4, mut v6) = (Vec::new(), Vec::new());
+    for line in lines {
+        let fields = csv_fields(line);
+        let get = |col: Option<usize>| {
+            col.and_then(|i| fields.get(i))
+                .map(|f| f.trim())
+                .filter(|f| !f.is_empty())
+        };
+        let bounds = match (network, span) {
+            (Some(n), _) => get(Some(n))
+                .and_then(|n| n.parse::<IpNet>().ok())
+                .map(|n| (n.network(), n.broadcast())),
+            (None, Some((start, end))) => get(Some(start))
+                .zip(get(Some(end)))
+                .and_then(|(s, e)| Some((s.parse().ok()?, e.parse().ok()?))),
+            (None, None) => None,
+        };
+        let geo = Geo {
+            asn: get(asn).and_then(parse_asn),
+            as_org: get(as_org).map(str::to_string),
+            country: get(country).map(|c| c.to_ascii_uppercase()),
+            city: get(city).map(str::to_string),
+        };
+        match bounds {
+            Some((IpAddr::V4(s), IpAddr::V4(e))) => {
+                v4.push((u32::from(s) as u128, u32::from(e) as u128, geo))
+            }
+            Some((IpAddr::V6(s), IpAddr::V6(e))) => v6.push((u128::from(s), u128::from(e), geo)),
+            _ => {}
+        }
+    }
+    v4.sort_by_key(|&(start, _, _)| start);
+    v6.sort_by_key(|&(start, _, _)| start);
+    Some(Source::Csv { v4, v6 })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Edition;
+    use serde_json::json;
+
+    fn db(files: Vec<Vec<u8>>) -> GeoDb {
+        let mut db = GeoDb::default();
+        for file in files {
+            db.add(file).unwrap();
+        }
+        db
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn merges_asn_and_city_databases() {
+        let asn = mmdb::tests::build(
+            false,
+            &[(
+                "5.9.0.0/16",
+                json!({"autonomous_system_number": 24940,
+                       "autonomous_system_organization": "Hetzner Online GmbH"}),
+            )],
+        );
+        let city = mmdb::tests::build(
+            true,
+            &[(
+                "5.9.0.0/20",
+                json!({"country": {"iso_code": "DE"}, "city": {"names": {"en": "Falkenstein"}}}),
+            )],
+        );
+        let db = db(vec![asn, city]);
+        assert_eq!(
+            db.lookup(ip("5.9.1.2")),
+            Geo {
+                asn: Some(24940),
+                as_org: Some("Hetzner Online GmbH".into()),
+                country: Some("DE".into()),
+                city: Some("Falkenstein".into()),
+            }
+        );
+        let outside_city = db.lookup(ip("5.9.200.1"));
+        assert_eq!(outside_city.asn, Some(24940));
+        assert_eq!(outside_city.country, None);
+        assert_eq!(db.lookup(ip("9.9.9.9")), Geo::default());
+    }
+
+    #[test]
+    fn reads_ipinfo_style_records() {
+        let ipinfo = mmdb::tests::build(
+            false,
+            &[(
+                "1.1.1.0/24",
+                json!({"asn": "AS13335", "as_name": "Cloudflare, Inc.", "country_code": "au"}),
+            )],
+        );
+        let geo = db(vec![ipinfo]).lookup(ip("1.1.1.1"));
+        assert_eq!(geo.asn, Some(13335));
+        assert_eq!(geo.as_org.as_deref(), Some("Cloudflare, Inc."));
+        assert_eq!(geo.country.as_deref(), Some("AU"));
+    }
+
+    #[test]
+    fn reads_csv_networks_and_spans() {
+        let geolite = "network,autonomous_system_number,autonomous_system_organization\n\
+                       5.9.0.0/16,24940,\"Hetzner Online GmbH\"\n\
+                       2a01:4f8::/32,24940,\"Hetzner Online GmbH\"\n";
+        let spans = "start_ip,end_ip,country,city\n\
+                     10.0.0.0,10.0.1.255,de,Berlin\n\
+                     10.0.3.0,10.0.3.255,FR,Paris\n";
+        let db = db(vec![geolite.into(), spans.into()]);
+        assert_eq!(db.lookup(ip("2a01:4f8::1")).asn, Some(24940));
+        let berlin = db.lookup(ip("10.0.1.7"));
+        assert_eq!(berlin.country.as_deref(), Some("DE"));
+        assert_eq!(berlin.city.as_deref(), Some("Berlin"));
+        assert_eq!(db.lookup(ip("10.0.2.1")), Geo::default());
+        assert!(
+            GeoDb::default()
+                .add(b"network,geoname_id\n".to_vec())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn annotates_servers() {
+        let csv = "network,asn,country\n203.0.113.0/24,AS64500,NL\n";
+        let db = db(vec![csv.into()]);
+        let mut server = ServerInfo::base("203.0.113.7:25565".parse().unwrap(), Edition::Java);
+        db.annotate(&mut server);
+        assert_eq!(server.asn, Some(64500));
+        assert_eq!(server.country.as_deref(), Some("NL"));
+        assert_eq!(server.city, None);
+    }
+}
@@ -0,0 +1,179 @@
+//! Heuristics for hosts that fake Minecraft servers: tarpits that accept any
+//! port, and honeypots replaying a canned status. All thresholds are tunable.
+
+use crate::types::{Edition, ServerInfo};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signal {
+    // Status JSON byte-identical to a known honeypot template
+    Template,
+    // `online` far beyond `max_players`
+    ImpossiblePlayers,
+    // Same status on this many ports of the host
+    IdenticalPorts(usize),
+    // Answered on every scanned Java port
+    AllPorts,
+}
+
+impl Signal {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Signal::Template => "template",
+            Signal::ImpossiblePlayers => "impossible_players",
+            Signal::IdenticalPorts(_) => "identical_ports",
+            Signal::AllPorts => "all_ports",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HoneypotConfig {
+    // Ports answering alike before the host counts as one fake; also the
+    // fewest scanned Java ports for "answers on all of them" to mean anything.
+    pub min_ports: usize,
+    // `online` may exceed `max_players` by this factor before it's impossible.
+    pub player_factor: u32,
+    // Fingerprints of known honeypot status bodies
+    pub templates: HashSet<u64>,
+}
+
+impl HoneypotConfig {
+    pub const DEFAULT_MIN_PORTS: usize = 5;
+    pub const DEFAULT_PLAYER_FACTOR: u32 = 10;
+
+    /// Adds one template per non-empty line, each a raw status JSON body.
+    pub fn add_templates(&mut self, raw: &str) {
+        self.templates.extend(
+            raw.lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty())
+                .map(|l| fingerprint(l.as_bytes())),
+        );
+    }
+}
+
+impl Default for HoneypotConfig {
+    fn default() -> Self {
+        Self {
+            min_ports: Self::DEFAULT_MIN_PORTS,
+            player_factor: Self::DEFAULT_PLAYER_FACTOR,
+            templates: HashSet::new(),
+        }
+    }
+}
+
+/// FNV-1a: stable across builds, so template files stay valid.
+pub fn fingerprint(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |h, &b| {
+        (h ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Signals that need only the server itself.
+pub fn server_signals(s: &ServerInfo, cfg: &HoneypotConfig) -> Vec<Signal> {
+    let mut signals = Vec::new();
+    if s.status_hash.is_some_and(|h| cfg.templates.contains(&h)) {
+        signals.push(Signal::Template);
+    }
+    // A zero limit still allows a few players on some proxies, hence max(1).
+    if s.online as u64 > s.max_players.max(1) as u64 * cfg.player_factor as u64 {
+        signals.push(Signal::ImpossiblePlayers);
+    }
+    signals
+}
+
+/// Signals from every result on one host; `java_ports` is what the scan tried.
+pub fn host_signals(host: &[&ServerInfo], java_ports: &[u16], cfg: &HoneypotConfig) -> Vec<Signal> {
+    let mut signals = Vec::new();
+    let java: Vec<&ServerInfo> = host
+        .iter()
+        .copied()
+        .filter(|s| s.edition == Edition::Java)
+        .collect();
+
+    let mut same_status: HashMap<u64, usize> = HashMap::new();
+    for h in java.iter().filter_map(|s| s.status_hash) {
+        *same_status.entry(h).or_default() += 1;
+    }
+    if let Some(&n) = same_status.values().max()
+        && n >= cfg.min_ports
+    {
+        signals.push(Signal::IdenticalPorts(n));
+    }
+
+    let answered: HashSet<u16> = java.iter().map(|s| s.addr.port()).collect();
+    if java_ports.len() >= cfg.min_ports && java_ports.iter().all(|p| answered.contains(p)) {
+        signals.push(Signal::AllPorts);
+    }
+    signals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::SocketAddr;
+
+    fn java(port: u16, hash: u64) -> ServerInfo {
+        let mut s = ServerInfo::base(SocketAddr::from(([1, 2, 3, 4], port)), Edition::Java);
+        s.status_hash = Some(hash);
+        s.max_players = 20;
+        s
+    }
+
+    #[test]
+    fn fingerprint_is_stable() {
+        assert_eq!(fingerprint(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fingerprint(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn template_and_player_count_signals() {
+        let mut cfg = HoneypotConfig::default();
+        cfg.add_templates("\n  {\"version\":{\"name\":\"1.8\"}}  \n");
+        let canned = fingerprint(br#"{"version":{"name":"1.8"}}"#);
+
+        let mut s = java(25565, canned);
+        s.online = 201;
+        assert_eq!(
+            server_signals(&s, &cfg),
+            [Signal::Template, Signal::ImpossiblePlayers]
+        );
+
+        // Exactly at the factor is still plausible.
+        s.status_hash = Some(1);
+        s.online = 200;
+        assert_eq!(server_signals(&s, &cfg), []);
+    }
+
+    #[test]
+    fn identical_statuses_across_ports_need_the_threshold() {
+        let cfg = HoneypotConfig {
+            min_ports: 3,
+            ..Default::default()
+        };
+        let a = java(1, 7);
+        let b = java(2, 7);
+        let c = java(3, 7);
+        let d = java(4, 8);
+        assert_eq!(host_signals(&[&a, &b, &d], &[], &cfg), []);
+        assert_eq!(
+            host_signals(&[&a, &b, &c, &d], &[], &cfg),
+            [Signal::IdenticalPorts(3)]
+        );
+    }
+
+    #[test]
+    fn answering_every_scanned_port_is_flagged() {
+        let cfg = HoneypotConfig {
+            min_ports: 3,
+            ..Default::default()
+        };
+        let servers: Vec<ServerInfo> = (1..=3).map(|p| java(p, p as u64)).collect();
+        let host: Vec<&ServerInfo> = servers.iter().collect();
+        assert_eq!(host_signals(&host, &[1, 2, 3], &cfg), [Signal::AllPorts]);
+        assert_eq!(host_signals(&host, &[1, 2, 3, 4], &cfg), []);
+        // Too few ports scanned to tell a honeypot from a small network.
+        assert_eq!(host_signals(&host[..2], &[1, 2], &cfg), []);
+    }
+}
@@ -0,0 +1,346 @@
+//! Exact `ip:port` targets from other scanners' output, so a sweep done with a
+//! faster tool can be followed up without rescanning whole ranges:
+//!
+//! | Tool    | Output                          | Flag        |
+//! |---------|---------------------------------|-------------|
+//! | masscan | JSON, one object per line       | `-oJ`       |
+//! | masscan | list (`open tcp 25565 ip ts`)   | `-oL`       |
+//! | nmap    | XML                             | `-oX`       |
+//! | nmap    | grepable                        | `-oG`       |
+//! | zmap    | CSV with `saddr` (and `sport`)  | `-O csv`    |
+//!
+//! Open TCP ports become Java targets and open UDP ports Bedrock ones. Only
+//! ports reported `open` count; nmap's `open|filtered` means nothing answered.
+
+use crate::types::{Edition, Endpoint};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+
+// zmap's default output is bare addresses without the port it scanned.
+const JAVA_PORT: u16 = 25565;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    MasscanJson,
+    MasscanList,
+    NmapXml,
+    NmapGrepable,
+    Zmap,
+}
+
+impl Format {
+    /// Guesses from the content; anything unrecognised is read as zmap CSV.
+    pub fn detect(text: &str) -> Self {
+        let head = text.trim_start();
+        if head.starts_with("<?xml") || head.starts_with("<nmaprun") {
+            Self::NmapXml
+        } else if head.starts_with('[') || head.starts_with('{') {
+            Self::MasscanJson
+        } else if head.starts_with("#masscan") || text.lines().any(|l| l.starts_with("open ")) {
+            Self::MasscanList
+        } else if head.starts_with("# Nmap") || text.lines().any(|l| l.starts_with("Host: ")) {
+            Self::NmapGrepable
+        } else {
+            Self::Zmap
+        }
+    }
+
+    /// Each target once, in the order the file lists them.
+    pub fn parse(self, text: &str) -> Vec<Endpoint> {
+        let found = match self {
+            Self::MasscanJson => masscan_json(text),
+            Self::MasscanList => masscan_list(text),
+            Self::NmapXml => nmap_xml(text),
+            Self::NmapGrepable => nmap_grepable(text),
+            Self::Zmap => zmap_csv(text),
+        };
+        let mut seen = HashSet::new();
+        found.into_iter().filter(|t| seen.insert(*t)).collect()
+    }
+}
+
+/// [`Format::parse`] in the [`Format::detect`]ed format.
+pub fn parse(text: &str) -> Vec<Endpoint> {
+    Format::detect(text).parse(text)
+}
+
+fn edition(proto: &str) -> Option<Edition> {
+    match proto {
+        "tcp" => Some(Edition::Java),
+        "udp" => Some(Edition::Bedrock),
+        _ => None,
+    }
+}
+
+fn target(ip: &str, port: &str, proto: &str) -> Option<Endpoint> {
+    let ip: IpAddr = ip.trim().parse().ok()?;
+    let port: u16 = port.trim().parse().ok()?;
+    Some(endpoint(SocketAddr::new(ip, port), edition(proto.trim())?))
+}
+
+fn endpoint(addr: SocketAddr, edition: Edition) -> Endpoint {
+    Endpoint {
+        addr,
+        edition: Some(edition),
+    }
+}
+
+// masscan writes a bracketed list with trailing commas, and older versions end
+// it with an unquoted `{finished: 1}`, so objects are read a line at a time.
+fn masscan_json(text: &str) -> Vec<Endpoint> {
+    let hosts: Vec<Value> = match serde_json::from_str(text) {
+        Ok(Value::Array(hosts)) => hosts,
+        _ => text
+            .lines()
+            .filter_map(|l| serde_json::from_str(l.trim().trim_end_matches(',')).ok())
+            .collect(),
+    };
+    let mut out = Vec::new();
+    for host in &hosts {
+        let Some(ip) = host.get("ip").and_then(Value::as_str) else {
+            continue;
+        };
+        let ports = host.get("ports").and_then(Value::as_array);
+        for port in ports.into_iter().flatten() {
+            let open = port
+                .get("status")
+                .and_then(Value::as_str)
+                .is_none_or(|s| s == "open");
+            let number = port.get("port").and_then(Value::as_u64);
+            let proto = port.get("proto").and_then(Value::as_str);
+            if let (true, Some(number), Some(proto)) = (open, number, proto) {
+                out.extend(target(ip, &number.to_string(), proto));
+            }
+        }
+    }
+    out
+}
+
+fn masscan_list(text: &str) -> Vec<Endpoint> {
+    text.lines()
+        .filter_map(
+            |line| match line.split_whitespace().collect::<Vec<_>>()[..] {
+                ["open", proto, port, ip, ..] => target(ip, port, proto),
+                _ => None,
+            },
+        )
+        .collect()
+}
+
+// Not a real XML parser: nmap's output is flat enough to follow tag by tag.
+fn nmap_xml(text: &str) -> Vec<Endpoint> {
+    let mut out = Vec::new();
+    let mut host: Option<IpAddr> = None;
+    let mut port: Option<(&str, &str)> = None;
+    let mut rest = text;
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+        let end = rest.find('>').unwrap_or(rest.len());
+        let tag = &rest[..end];
+        rest = &rest[end..];
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '>')
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches('/');
+        match name {
+            "host" | "/host" => host = None,
+            "/port" => port = None,
+            "address" if attr(tag, "addrtype") != Some("mac") => {
+                host = attr(tag, "addr").and_then(|a| a.parse().ok());
+            }
+            "port" => port = attr(tag, "protocol").zip(attr(tag, "portid")),
+            "state" if attr(tag, "state") == Some("open") => {
+                if let (Some(ip), Some((proto, id))) = (host, port) {
+                    out.extend(target(&ip.to_string(), id, proto));
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+// A double-quoted attribute's value, escapes left as they are.
+fn attr<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let mut from = 0;
+    while let Some(i) = tag[from..].find(name).map(|i| i + from) {
+        let after = &tag[i + name.len()..];
+        let standalone = tag[..i].ends_with(char::is_whitespace);
+        if standalone && let Some(value) = after.strip_prefix("=\"") {
+            return value.split('"').next();
+        }
+        from = i + name.len();
+    }
+    None
+}
+
+// `Host: 1.2.3.4 ()\tPorts: 25565/open/tcp//minecraft///, 19132/open/udp//...`
+fn nmap_grepable(text: &str) -> Vec<Endpoint> {
+    let mut out = Vec::new();
+    for line in text.lines() {
+        let Some(rest) = line.strip_prefix("Host: ") else {
+            continue;
+        };
+        let Some(ip) = rest.split_whitespace().next() else {
+            continue;
+        };
+        let ports = rest.split('\t').find_map(|f| f.strip_prefix("Ports: "));
+        for entry in ports.into_iter().flat_map(|p| p.split(',')) {
+            if let [port, "open", proto, ..] = entry.trim().split('/').collect::<Vec<_>>()[..] {
+                out.extend(target(ip, port, proto));
+            }
+        }
+    }
+    out
+}
+
+// With a header, `saddr` and `sport` (the port that answered) are found by
+// name and the UDP module's `classification` marks Bedrock. Without one, lines
+// are `ip` or `ip,port` and taken as Java.
+fn zmap_csv(text: &str) -> Vec<Endpoint> {
+    let mut lines = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'));
+    let Some(first) = lines.clone().next() else {
+        return Vec::new();
+    };
+    let header: Vec<&str> = first.split(',').map(str::trim).collect();
+    let column = |name: &str| header.iter().position(|&h| h == name);
+    let Some(saddr) = column("saddr") else {
+        return lines
+            .filter_map(|line| {
+                let mut fields = line.split(',');
+                let ip = fields.next()?;
+                let port = match fields.next() {
+                    Some(port) => port.trim().parse().ok()?,
+                    None => JAVA_PORT,
+                };
+                let addr = SocketAddr::new(ip.trim().parse().ok()?, port);
+                Some(endpoint(addr, Edition::Java))
+            })
+            .collect();
+    };
+    let sport = column("sport");
+    let class = column("classification");
+    let success = column("success");
+    lines.next();
+    let mut out = Vec::new();
+    for line in lines {
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let field = |i: Option<usize>| i.and_then(|i| fields.get(i).copied());
+        if field(success) == Some("0") {
+            continue;
+        }
+        let Some(ip) = field(Some(saddr)).and_then(|ip| ip.parse::<IpAddr>().ok()) else {
+            continue;
+        };
+        let port = field(sport)
+            .and_then(|p| p.parse().ok())
+            .unwrap_or(JAVA_PORT);
+        let edition = match field(class) {
+            Some(c) if c.starts_with("udp") => Edition::Bedrock,
+            _ => Edition::Java,
+        };
+        out.push(endpoint(SocketAddr::new(ip, port), edition));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn java(addr: &str) -> Endpoint {
+        endpoint(addr.parse().unwrap(), Edition::Java)
+    }
+
+    fn bedrock(addr: &str) -> Endpoint {
+        endpoint(addr.parse().unwrap(), Edition::Bedrock)
+    }
+
+    #[test]
+    fn reads_masscan_json_with_trailing_commas() {
+        let text = r#"[
+{   "ip": "1.2.3.4",   "timestamp": "1700000000", "ports": [ {"port": 25565, "proto": "tcp", "status": "open", "reason": "syn-ack", "ttl": 52} ] },
+{   "ip": "1.2.3.4",   "timestamp": "1700000001", "ports": [ {"port": 25565, "proto": "tcp", "service": {"name": "title"}} ] },
+{   "ip": "2001:db8::1", "timestamp": "1700000002", "ports": [ {"port": 19132, "proto": "udp", "status": "open"} ] },
+{   "ip": "5.6.7.8",   "timestamp": "1700000003", "ports": [ {"port": 25566, "proto": "tcp", "status": "closed"} ] },
+{finished: 1}
+]"#;
+        assert_eq!(Format::detect(text), Format::MasscanJson);
+        assert_eq!(
+            parse(text),
+            [java("1.2.3.4:25565"), bedrock("[2001:db8::1]:19132")]
+        );
+    }
+
+    #[test]
+    fn reads_strict_masscan_json() {
+        let text = r#"[{"ip":"1.2.3.4","ports":[{"port":25570,"proto":"tcp","status":"open"}]}]"#;
+        assert_eq!(parse(text), [java("1.2.3.4:25570")]);
+    }
+
+    #[test]
+    fn reads_masscan_lists() {
+        let text = "#masscan\nopen tcp 25565 1.2.3.4 1700000000\nbanner tcp 25565 1.2.3.4 1700000001 title x\nopen udp 19133 5.6.7.8 1700000002\n# end\n";
+        assert_eq!(Format::detect(text), Format::MasscanList);
+        assert_eq!(
+            parse(text),
+            [java("1.2.3.4:25565"), bedrock("5.6.7.8:19133")]
+        );
+    }
+
+    #[test]
+    fn reads_nmap_xml() {
+        let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+<nmaprun scanner="nmap" args="nmap -p 25565,25566 -sU -sS 1.2.3.4">
+<host starttime="1700000000"><status state="up" reason="arp-response"/>
+<address addr="1.2.3.4" addrtype="ipv4"/>
+<address addr="00:11:22:33:44:55" addrtype="mac"/>
+<ports>
+<port protocol="tcp" portid="25565"><state state="open" reason="syn-ack"/><service name="minecraft"/></port>
+<port protocol="tcp" portid="25566"><state state="closed" reason="reset"/></port>
+<port protocol="udp" portid="19132"><state state="open|filtered" reason="no-response"/></port>
+<port protocol="udp" portid="19133"><state state="open" reason="udp-response"/></port>
+</ports>
+</host>
+<host><address addr="2001:db8::1" addrtype="ipv6"/><ports><port protocol="tcp" portid="25565"><state state="open"/></port></ports></host>
+</nmaprun>"#;
+        assert_eq!(Format::detect(text), Format::NmapXml);
+        assert_eq!(
+            parse(text),
+            [
+                java("1.2.3.4:25565"),
+                bedrock("1.2.3.4:19133"),
+                java("[2001:db8::1]:25565")
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_nmap_grepable() {
+        let text = "# Nmap 7.94 scan initiated as: nmap -oG - 1.2.3.4\nHost: 1.2.3.4 (mc.example.com)\tStatus: Up\nHost: 1.2.3.4 (mc.example.com)\tPorts: 25565/open/tcp//minecraft///, 25566/closed/tcp/////, 19132/open|filtered/udp/////, 19133/open/udp/////\tIgnored State: closed (997)\n# Nmap done\n";
+        assert_eq!(Format::detect(text), Format::NmapGrepable);
+        assert_eq!(
+            parse(text),
+            [java("1.2.3.4:25565"), bedrock("1.2.3.4:19133")]
+        );
+    }
+
+    #[test]
+    fn reads_zmap_csv_with_and_without_headers() {
+        let text = "saddr,daddr,sport,dport,classification,success\n1.2.3.4,10.0.0.1,25565,40000,synack,1\n1.2.3.5,10.0.0.1,25565,40000,rst,0\n5.6.7.8,10.0.0.1,19132,40001,udp,1\n";
+        assert_eq!(Format::detect(text), Format::Zmap);
+        assert_eq!(
+            parse(text),
+            [java("1.2.3.4:25565"), bedrock("5.6.7.8:19132")]
+        );
+        assert_eq!(
+            parse("1.2.3.4\n1.2.3.4\n5.6.7.8,25570\nnot an ip\n"),
+            [java("1.2.3.4:25565"), java("5.6.7.8:25570")]
+        );
+    }
+}
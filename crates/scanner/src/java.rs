@@ -1,41 +1,26 @@
 use super::Miss;
-use super::types::{Edition, ModInfo, ServerInfo};
+use super::motd::Motd;
+use super::transport::Transport;
+use super::types::{Edition, ModInfo, ServerInfo, Timeouts};
 use serde_json::Value;
 use std::net::SocketAddr;
-use std::time::{Duration, Instant};
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt, BufReader};
-use tokio::net::TcpStream;
-use tokio::time::timeout;
-use tracing::{debug, trace};
+use tokio::time::{Instant, timeout};
 
-pub async fn probe(addr: SocketAddr, timeout_ms: u64) -> Option<ServerInfo> {
-    match probe_inner(addr, timeout_ms).await {
-        Ok(info) => {
-            debug!(%addr, edition = "java", version = %info.version, online = info.online, "found");
-            Some(info)
-        }
-        Err(Miss::Unparsed(stage)) => {
-            debug!(%addr, edition = "java", stage, "response did not parse");
-            None
-        }
-        // The common case on a wide scan, kept at trace.
-        Err(Miss::Unreachable(stage)) => {
-            trace!(%addr, edition = "java", stage, "unreachable");
-            None
-        }
-    }
-}
-
-async fn probe_inner(addr: SocketAddr, timeout_ms: u64) -> Result<ServerInfo, Miss> {
-    let dur = Duration::from_millis(timeout_ms);
+pub(crate) async fn probe_inner(
+    net: &dyn Transport,
+    addr: SocketAddr,
+    host: &str,
+    timeouts: Timeouts,
+) -> Result<ServerInfo, Miss> {
     let start = Instant::now();
 
-    let mut stream = timeout(dur, TcpStream::connect(addr))
+    let mut stream = timeout(timeouts.connect, net.connect(addr))
         .await
         .map_err(|_| Miss::Unreachable("connect_timeout"))?
         .map_err(|_| Miss::Unreachable("connect"))?;
 
-    let handshake = build_handshake(&addr.ip().to_string(), addr.port());
+    let handshake = build_handshake(host, addr.port());
     stream
         .write_all(&handshake)
         .await
@@ -45,7 +30,7 @@ async fn probe_inner(addr: SocketAddr, timeout_ms: u64) -> Result<ServerInfo, Mi
         .await
         .map_err(|_| Miss::Unreachable("write"))?;
 
-    let json: Value = timeout(dur, read_response(&mut stream))
+    let (json, raw) = timeout(timeouts.read, read_response(&mut stream))
         .await
         .map_err(|_| Miss::Unreachable("read_timeout"))??;
     let latency_ms = start.elapsed().as_millis() as u64;
@@ -53,7 +38,7 @@ async fn probe_inner(addr: SocketAddr, timeout_ms: u64) -> Result<ServerInfo, Mi
     let (samples, sample_ids) = parse_samples(&json["players"]["sample"]);
 
     let mut info = ServerInfo::base(addr, Edition::Java);
-    info.motd = parse_description(&json["description"]);
+    info.motd = Motd::from_component(&json["description"]);
     info.version = json["version"]["name"].as_str().unwrap_or("").to_string();
     info.protocol = json["version"]["protocol"].as_i64().unwrap_or(0) as i32;
     info.online = json["players"]["online"].as_u64().unwrap_or(0) as u32;
@@ -65,6 +50,9 @@ async fn probe_inner(addr: SocketAddr, timeout_ms: u64) -> Result<ServerInfo, Mi
     info.favicon = json["favicon"].as_str().map(|s| s.to_string());
     info.secure_chat = json["enforcesSecureChat"].as_bool();
     info.mods = parse_mods(&json);
+    info.status_hash = Some(super::honeypot::fingerprint(&raw));
+    // serde_json only accepts UTF-8, so this never fails.
+    info.raw_status = String::from_utf8(raw).ok();
     Ok(info)
 }
 
@@ -86,7 +74,8 @@ fn build_handshake(host: &str, port: u16) -> Vec<u8> {
 // otherwise OOM or abort on capacity overflow. Real statuses stay well below.
 const MAX_STATUS_BYTES: usize = 4 * 1024 * 1024;
 
-async fn read_response(stream: &mut TcpStream) -> Result<Value, Miss> {
+// Returns the parsed status with its exact bytes.
+async fn read_response<S: AsyncRead + Unpin>(stream: &mut S) -> Result<(Value, Vec<u8>), Miss> {
     let mut reader = BufReader::new(stream);
     let _len = read_varint(&mut reader)
         .await
@@ -109,7 +98,8 @@ async fn read_response(stream: &mut TcpStream) -> Result<Value, Miss> {
         .read_exact(&mut buf)
         .await
         .map_err(|_| Miss::Unreachable("body"))?;
-    serde_json::from_slice(&buf).map_err(|_| Miss::Unparsed("json"))
+    let json = serde_json::from_slice(&buf).map_err(|_| Miss::Unparsed("json"))?;
+    Ok((json, buf))
 }
 
 async fn read_varint<R: AsyncRead + Unpin>(reader: &mut R) -> Option<i32> {
@@ -128,6 +118,17 @@ async fn read_varint<R: AsyncRead + Unpin>(reader: &mut R) -> Option<i32> {
     }
 }
 
+// Server side of the status exchange, for simulated hosts and tests.
+pub(crate) fn build_status_response(json: &[u8]) -> Vec<u8> {
+    let mut inner = vec![0x00];
+    write_varint(&mut inner, json.len() as i32);
+    inner.extend_from_slice(json);
+    let mut packet = Vec::new();
+    write_varint(&mut packet, inner.len() as i32);
+    packet.extend_from_slice(&inner);
+    packet
+}
+
 fn write_varint(buf: &mut Vec<u8>, value: i32) {
     let mut v = value as u32;
     loop {
@@ -194,34 +195,13 @@ fn parse_mods(json: &Value) -> Vec<ModInfo> {
     Vec::new()
 }
 
-// Keeps raw `§` codes intact so CSV export retains them; the GUI strips them.
-fn parse_description(v: &Value) -> String {
-    match v {
-        Value::String(s) => s.clone(),
-        Value::Array(arr) => arr.iter().map(parse_description).collect(),
-        Value::Object(map) => {
-            let text = map
-                .get("text")
-                .and_then(|v| v.as_str())
-                .unwrap_or_default()
-                .to_string();
-            let extras = map
-                .get("extra")
-                .and_then(|v| v.as_array())
-                .map(|arr| arr.iter().map(parse_description).collect::<String>())
-                .unwrap_or_default();
-            format!("{}{}", text, extras)
-        }
-        _ => String::new(),
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use tokio::net::TcpListener;
+    use crate::transport::TokioNet;
+    use tokio::net::{TcpListener, TcpStream};
 
-    async fn read_response_of(bytes: Vec<u8>) -> Result<Value, Miss> {
+    async fn read_response_of(bytes: Vec<u8>) -> Result<(Value, Vec<u8>), Miss> {
         let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
         let addr = listener.local_addr().unwrap();
         let server = tokio::spawn(async move {
@@ -234,16 +214,6 @@ mod tests {
         out
     }
 
-    fn framed_status(json: &[u8]) -> Vec<u8> {
-        let mut inner = vec![0x00];
-        write_varint(&mut inner, json.len() as i32);
-        inner.extend_from_slice(json);
-        let mut packet = Vec::new();
-        write_varint(&mut packet, inner.len() as i32);
-        packet.extend_from_slice(&inner);
-        packet
-    }
-
     #[tokio::test]
     async fn rejects_negative_status_length() {
         // str_len = VarInt(-1); without the guard this aborts on capacity overflow.
@@ -267,31 +237,33 @@ mod tests {
     #[tokio::test]
     async fn parses_within_cap() {
         let json = br#"{"players":{"online":3,"max":20}}"#;
-        let v = read_response_of(framed_status(json))
+        let (v, raw) = read_response_of(build_status_response(json))
             .await
             .expect("should parse");
         assert_eq!(v["players"]["online"].as_u64(), Some(3));
+        assert_eq!(raw, json);
     }
 
     #[test]
     fn description_array_is_concatenated() {
         use serde_json::json;
-        assert_eq!(parse_description(&json!(["a", "b"])), "ab");
-        assert_eq!(
-            parse_description(&json!([{"text": "x"}, {"text": "y"}])),
-            "xy"
-        );
+        let plain = |v| Motd::from_component(&v).plain();
+        assert_eq!(plain(json!(["a", "b"])), "ab");
+        assert_eq!(plain(json!([{"text": "x"}, {"text": "y"}])), "xy");
     }
 
     #[test]
-    fn description_keeps_raw_codes_and_walks_extra() {
+    fn description_reads_codes_and_walks_extra() {
         use serde_json::json;
-        assert_eq!(parse_description(&json!("§aHello")), "§aHello");
+        let plain = |v| Motd::from_component(&v).plain();
+        assert_eq!(plain(json!("§aHello")), "Hello");
         assert_eq!(
-            parse_description(&json!({"text": "A", "extra": [{"text": "B"}, "C"]})),
+            plain(json!({"text": "A", "extra": [{"text": "B"}, "C"]})),
             "ABC"
         );
-        assert_eq!(parse_description(&json!(42)), "");
+        // Bare numbers and booleans render as text, as in the client.
+        assert_eq!(plain(json!(42)), "42");
+        assert_eq!(plain(json!(null)), "");
     }
 
     async fn varint_roundtrip(v: i32) -> Option<i32> {
@@ -329,4 +301,55 @@ mod tests {
         let mut slice: &[u8] = &[0x80, 0x80, 0x80, 0x80, 0x80, 0x01];
         assert!(read_varint(&mut slice).await.is_none());
     }
+
+    fn timeouts() -> Timeouts {
+        Timeouts::uniform(std::time::Duration::from_secs(2))
+    }
+
+    #[tokio::test]
+    async fn probes_fake_server() {
+        let json = r#"{"version":{"name":"Paper 1.21.1","protocol":767},"players":{"online":1,"max":50,"sample":[{"name":"Steve","id":"8667ba71-b85a-4004-af54-457a9734eed7"}]},"description":{"text":"Hi"},"forgeData":{"mods":[{"modId":"create","modmarker":"0.5.1"}]}}"#;
+        let server = testkit::JavaServer::new(json).spawn().await.unwrap();
+        let info = probe_inner(&TokioNet, server.addr(), "localhost", timeouts())
+            .await
+            .expect("should answer");
+        assert_eq!(info.version, "Paper 1.21.1");
+        assert_eq!(info.protocol, 767);
+        assert_eq!((info.online, info.max_players), (1, 50));
+        assert_eq!(info.motd.plain(), "Hi");
+        assert_eq!(info.samples, ["Steve"]);
+        assert_eq!(info.mods[0].id, "create");
+        assert_eq!(
+            info.status_hash,
+            Some(crate::honeypot::fingerprint(json.as_bytes()))
+        );
+        assert_eq!(info.raw_status.as_deref(), Some(json));
+    }
+
+    #[tokio::test]
+    async fn fake_server_odd_replies_are_misses() {
+        let wrong_id = testkit::JavaServer::raw_status([0x03, 0x05, 0x01, b'{'])
+            .spawn()
+            .await
+            .unwrap();
+        let not_json = testkit::JavaServer::new("<html>").spawn().await.unwrap();
+        let slow = testkit::JavaServer::new("{}")
+            .delay(std::time::Duration::from_secs(5))
+            .spawn()
+            .await
+            .unwrap();
+        let probe = |addr| probe_inner(&TokioNet, addr, "localhost", timeouts());
+        assert_eq!(
+            probe(wrong_id.addr()).await.err(),
+            Some(Miss::Unparsed("packet_id"))
+        );
+        assert_eq!(
+            probe(not_json.addr()).await.err(),
+            Some(Miss::Unparsed("json"))
+        );
+        assert_eq!(
+            probe(slow.addr()).await.err(),
+            Some(Miss::Unreachable("read_timeout"))
+        );
+    }
 }
@@ -0,0 +1,180 @@
+use super::bedrock;
+use futures::{Stream, StreamExt, stream};
+use ipnet::Ipv4Net;
+use socket2::{Domain, Protocol, Socket, Type};
+use std::future::ready;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time::{Instant, timeout_at};
+use tracing::{debug, trace};
+
+// Where "Open to LAN" worlds announce themselves, every 1.5 s.
+pub const JAVA_GROUP: SocketAddrV4 = SocketAddrV4::new(Ipv4Addr::new(224, 0, 2, 60), 4445);
+
+// Broadcast pings are repeated so a dropped datagram doesn't hide a server.
+const PING_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Yields the game address of every Java LAN announcement heard before `deadline`.
+/// Repeats are not filtered: a world announces itself every 1.5 s.
+pub(crate) fn java_announcements(deadline: Instant) -> impl Stream<Item = SocketAddr> {
+    stream::once(async {
+        join_java_group()
+            .map_err(|e| debug!(error = %e, "java lan listener unavailable"))
+            .ok()
+    })
+    .filter_map(ready)
+    .flat_map(move |socket| {
+        stream::unfold(socket, move |socket| async move {
+            let mut buf = [0u8; 1024];
+            loop {
+                let (n, from) = timeout_at(deadline, socket.recv_from(&mut buf))
+                    .await
+                    .ok()?
+                    .ok()?;
+                match parse_announcement(&String::from_utf8_lossy(&buf[..n])) {
+                    Some((_, port)) => return Some((SocketAddr::new(from.ip(), port), socket)),
+                    None => trace!(%from, "ignoring non-announcement on lan group"),
+                }
+            }
+        })
+    })
+}
+
+/// Broadcasts unconnected pings to `ports` on the local subnet and yields the
+/// source of every valid pong received before `deadline`.
+pub(crate) fn bedrock_pongs(ports: Vec<u16>, deadline: Instant) -> impl Stream<Item = SocketAddr> {
+    stream::once(async {
+        bind_broadcast()
+            .await
+            .map_err(|e| debug!(error = %e, "bedrock lan broadcast unavailable"))
+            .ok()
+    })
+    .filter_map(ready)
+    .flat_map(move |socket| {
+        let targets = broadcast_targets(&ports);
+        stream::unfold((socket, Instant::now()), move |(socket, mut next_ping)| {
+            let targets = targets.clone();
+            async move {
+                let mut buf = [0u8; 2048];
+                loop {
+                    if Instant::now() >= next_ping {
+                        for target in &targets {
+                            if let Err(e) = socket.send_to(&bedrock::build_ping(), target).await {
+                                trace!(%target, error = %e, "broadcast ping not sent");
+                            }
+                        }
+                        next_ping += PING_INTERVAL;
+                    }
+                    match timeout_at(next_ping.min(deadline), socket.recv_from(&mut buf)).await {
+                        Ok(Ok((n, from))) if bedrock::parse_pong(&buf[..n], from, 0).is_some() => {
+                            return Some((from, (socket, next_ping)));
+                        }
+                        Ok(Ok((_, from))) => trace!(%from, "ignoring non-pong reply"),
+                        Ok(Err(e)) => trace!(error = %e, "broadcast recv failed"),
+                        Err(_) if Instant::now() >= deadline => return None,
+                        Err(_) => {}
+                    }
+                }
+            }
+        })
+    })
+}
+
+fn join_java_group() -> std::io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    // A game client on this machine may already be listening on the port.
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, JAVA_GROUP.port())).into())?;
+    socket.join_multicast_v4(JAVA_GROUP.ip(), &Ipv4Addr::UNSPECIFIED)?;
+    UdpSocket::from_std(socket.into())
+}
+
+async fn bind_broadcast() -> std::io::Result<UdpSocket> {
+    let socket = UdpSocket::bind("0.0.0.0:0").await?;
+    socket.set_broadcast(true)?;
+    Ok(socket)
+}
+
+fn broadcast_targets(ports: &[u16]) -> Vec<SocketAddr> {
+    let mut ips = vec![Ipv4Addr::BROADCAST];
+    ips.extend(subnet_broadcast());
+    ips.iter()
+        .flat_map(|&ip| ports.iter().map(move |&port| SocketAddr::from((ip, port))))
+        .collect()
+}
+
+// Connecting a UDP socket only selects the outgoing interface, nothing is sent.
+// The prefix isn't known without enumerating interfaces, so assume the usual /24;
+// the limited broadcast above still covers other layouts on the same segment.
+fn subnet_broadcast() -> Option<Ipv4Addr> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("192.0.2.1:9").ok()?;
+    match socket.local_addr().ok()?.ip() {
+        IpAddr::V4(ip) if !ip.is_loopback() && !ip.is_unspecified() => {
+            Some(Ipv4Net::new(ip, 24).ok()?.broadcast())
+        }
+        _ => None,
+    }
+}
+
+// `[MOTD]name[/MOTD][AD]port[/AD]`; some servers put `host:port` in the AD tag.
+pub(crate) fn parse_announcement(msg: &str) -> Option<(String, u16)> {
+    let motd = between(msg, "[MOTD]", "[/MOTD]")?;
+    let ad = between(msg, "[AD]", "[/AD]")?.trim();
+    let port = ad.rsplit(':').next()?.parse().ok()?;
+    Some((motd.to_string(), port))
+}
+
+fn between<'a>(s: &'a str, open: &str, close: &str) -> Option<&'a str> {
+    let start = s.find(open)? + open.len();
+    let len = s[start..].find(close)?;
+    Some(&s[start..start + len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_vanilla_announcement() {
+        assert_eq!(
+            parse_announcement("[MOTD]Steve - New World[/MOTD][AD]51234[/AD]"),
+            Some(("Steve - New World".to_string(), 51234))
+        );
+    }
+
+    #[test]
+    fn parses_host_port_in_ad_tag() {
+        assert_eq!(
+            parse_announcement("[MOTD]Proxy[/MOTD][AD]10.0.0.5:25565[/AD]"),
+            Some(("Proxy".to_string(), 25565))
+        );
+    }
+
+    #[test]
+    fn rejects_incomplete_announcements() {
+        assert_eq!(parse_announcement("[MOTD]x[/MOTD]"), None);
+        assert_eq!(parse_announcement("[MOTD]x[/MOTD][AD]port[/AD]"), None);
+        assert_eq!(parse_announcement("[AD]25565[/AD]"), None);
+    }
+
+    #[test]
+    fn broadcast_targets_cover_every_port() {
+        let targets = broadcast_targets(&[19132, 19133]);
+        assert!(targets.contains(&"255.255.255.255:19132".parse().unwrap()));
+        assert!(targets.contains(&"255.255.255.255:19133".parse().unwrap()));
+        assert_eq!(targets.len() % 2, 0);
+    }
+
+    // No ports, so nothing is broadcast from the test machine.
+    #[tokio::test]
+    async fn listeners_end_at_the_deadline() {
+        let deadline = Instant::now() + Duration::from_millis(200);
+        let started = Instant::now();
+        let _: Vec<_> = bedrock_pongs(Vec::new(), deadline).collect().await;
+        let _: Vec<_> = java_announcements(deadline).collect().await;
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
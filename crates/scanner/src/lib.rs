@@ -1,94 +1,250 @@
+pub mod audit;
 mod bedrock;
+mod bedrock_login;
+pub mod correlate;
 pub mod export;
+pub mod geoip;
+pub mod honeypot;
+pub mod import;
 mod java;
+mod lan;
 pub mod limits;
 mod login;
+pub mod mmdb;
+pub mod motd;
+pub mod nbt;
+pub mod octets;
 pub mod parse;
+pub mod prefixes;
+pub mod probe;
 mod query;
+mod rcon;
+pub mod servers_dat;
+pub mod sim;
+pub mod transcript;
+pub mod transport;
 pub mod types;
 
 use futures::{Stream, StreamExt, stream};
+use limits::Ports;
+use probe::{BedrockStatus, Registry, Target};
+use std::collections::HashSet;
+use std::future::ready;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tracing::trace;
-use types::{Edition, ScanConfig, ServerInfo};
+use transport::{TokioNet, Transport};
+use types::{Edition, LanConfig, ProbeOptions, ScanConfig, ServerInfo};
 
-#[derive(Debug)]
-pub(crate) enum Miss {
+// LAN discovery turns up a handful of servers, no need for the scan's fan-out.
+const LAN_CONCURRENCY: usize = 16;
+
+/// Why a probe came back empty, with the stage it stopped at: nothing
+/// answered, or something answered that isn't the expected protocol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Miss {
+    Unreachable(&'static str),
+    Unparsed(&'static str),
+}
+
+/// Why [`probe_server`] or [`Registry::probe`] has no result. The first two
+/// carry the [`Miss`] stage of the most telling discoverer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeError {
     Unreachable(&'static str),
     Unparsed(&'static str),
+    // Nothing registered discovers this edition
+    NoProber(Edition),
+}
+
+impl From<Miss> for ProbeError {
+    fn from(miss: Miss) -> Self {
+        match miss {
+            Miss::Unreachable(stage) => Self::Unreachable(stage),
+            Miss::Unparsed(stage) => Self::Unparsed(stage),
+        }
+    }
+}
+
+impl std::fmt::Display for ProbeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Unreachable(stage) => write!(f, "no answer ({stage})"),
+            Self::Unparsed(stage) => write!(f, "unexpected reply ({stage})"),
+            Self::NoProber(edition) => write!(f, "no prober for edition {edition}"),
+        }
+    }
 }
 
+impl std::error::Error for ProbeError {}
+
+/// Probes `config`'s ranges and ports, then its exact targets, as
+/// `config.options` asks; Bedrock pongs that advertise a port outside the scan
+/// are followed up.
 pub fn scan(config: Arc<ScanConfig>) -> impl Stream<Item = Option<ServerInfo>> + Send + 'static {
-    let timeout_ms = config.timeout_ms.get();
+    scan_over(config, Arc::new(TokioNet))
+}
+
+/// [`scan`] over any [`Transport`], such as a simulated network.
+pub fn scan_over<T: Transport + 'static>(
+    config: Arc<ScanConfig>,
+    transport: Arc<T>,
+) -> impl Stream<Item = Option<ServerInfo>> + Send + 'static {
+    let registry = scan_registry(&config);
+    scan_with(config, Arc::new(registry), transport)
+}
+
+// The probers a scan runs, following Bedrock pongs that point elsewhere.
+pub(crate) fn scan_registry(config: &ScanConfig) -> Registry {
+    let bedrock = BedrockStatus {
+        follow: true,
+        // Ports already in the scan are found and merged on their own.
+        skip: config.bedrock_ports.to_vec(),
+    };
+    Registry::standard_with(&config.options, bedrock)
+}
+
+type Hosts = Box<dyn Iterator<Item = std::net::IpAddr> + Send>;
+
+/// Like [`scan_over`], but every target goes through `registry`: Java ports to its
+/// Java probers, Bedrock ports to its Bedrock ones.
+pub fn scan_with<T: Transport + 'static>(
+    config: Arc<ScanConfig>,
+    registry: Arc<Registry>,
+    transport: Arc<T>,
+) -> impl Stream<Item = Option<ServerInfo>> + Send + 'static {
+    sweep(config.clone(), move |addr, edition| {
+        let registry = registry.clone();
+        let config = config.clone();
+        let transport = transport.clone();
+        async move {
+            let target = Target {
+                addr,
+                edition,
+                options: &config.options,
+                net: transport.as_ref(),
+            };
+            registry.probe(target).await.ok()
+        }
+    })
+}
+
+/// Runs `probe` on every target of `config`, as many at once as it allows.
+pub(crate) fn sweep<P, F>(
+    config: Arc<ScanConfig>,
+    probe: P,
+) -> impl Stream<Item = Option<ServerInfo>> + Send + 'static
+where
+    P: Fn(SocketAddr, Edition) -> F + Send + 'static,
+    F: Future<Output = Option<ServerInfo>> + Send + 'static,
+{
     let concurrency = config.concurrency.get();
 
-    let mut ports: Vec<(u16, Edition)> =
-        Vec::with_capacity(config.java_ports.len() + config.bedrock_ports.len());
-    ports.extend(config.java_ports.iter().map(|&p| (p, Edition::Java)));
-    ports.extend(config.bedrock_ports.iter().map(|&p| (p, Edition::Bedrock)));
-    let ports = Arc::new(ports);
+    // A range's hosts share one port list; overridden ranges get their own.
+    let port_list = |java: &Ports, bedrock: &Ports| {
+        let mut ports: Vec<(u16, Edition)> = Vec::with_capacity(java.len() + bedrock.len());
+        ports.extend(java.iter().map(|&p| (p, Edition::Java)));
+        ports.extend(bedrock.iter().map(|&p| (p, Edition::Bedrock)));
+        Arc::new(ports)
+    };
+    let global = port_list(&config.java_ports, &config.bedrock_ports);
+    // Hosts a more specific range with its own ports covers are skipped, so
+    // each is probed on one set of ports.
+    let ranges: Vec<_> = config
+        .ranges
+        .iter()
+        .map(|net| {
+            let ports = if config.overrides.iter().any(|o| o.net == *net) {
+                let (java, bedrock) = config.ports_for(net);
+                port_list(java, bedrock)
+            } else {
+                global.clone()
+            };
+            (*net, config.taken_over(net), ports)
+        })
+        .collect();
 
-    let ranges = config.ranges.clone();
+    let patterns = config.patterns.clone();
+    let hosts = ranges
+        .into_iter()
+        .map(|(net, taken, ports)| {
+            let hosts: Hosts = if taken.is_empty() {
+                Box::new(net.hosts())
+            } else {
+                Box::new(net.hosts().filter(move |ip| !taken.contains(*ip)))
+            };
+            (hosts, ports)
+        })
+        .chain(
+            patterns
+                .into_iter()
+                .map(move |p| (Box::new(p.hosts()) as Hosts, global.clone())),
+        );
 
     // Lazy: a /8 or IPv6 range would be tens of millions of tuples if collected.
-    let targets = ranges.into_iter().flat_map(move |net| {
-        let ports = ports.clone();
-        net.hosts().flat_map(move |ip| {
+    let targets = hosts.flat_map(move |(hosts, ports)| {
+        hosts.flat_map(move |ip| {
             let ports = ports.clone();
             (0..ports.len()).map(move |i| {
-                let (port, edition) = ports[i].clone();
-                (ip, port, edition)
+                let (port, edition) = ports[i];
+                (SocketAddr::new(ip, port), edition)
             })
         })
     });
 
-    stream::iter(targets)
-        .map(move |(ip, port, edition)| async move {
-            let addr = SocketAddr::new(ip, port);
-            match edition {
-                Edition::Java => java::probe(addr, timeout_ms).await,
-                Edition::Bedrock => bedrock::probe(addr, timeout_ms).await,
+    let exact = config.targets.clone().into_iter().flat_map(|endpoint| {
+        endpoint
+            .editions()
+            .map(move |edition| (endpoint.addr, edition))
+    });
+
+    stream::iter(targets.chain(exact))
+        .map(move |(addr, edition)| probe(addr, edition))
+        .buffer_unordered(concurrency)
+}
+
+/// Listens for Java LAN announcements and broadcasts Bedrock pings for
+/// `config.listen`, then enriches each newly seen server like [`probe_server`].
+pub fn discover_lan(config: Arc<LanConfig>) -> impl Stream<Item = ServerInfo> + Send + 'static {
+    let deadline = tokio::time::Instant::now() + config.listen;
+    let found = stream::select(
+        lan::java_announcements(deadline).map(|addr| (addr, Edition::Java)),
+        lan::bedrock_pongs(config.bedrock_ports.to_vec(), deadline)
+            .map(|addr| (addr, Edition::Bedrock)),
+    );
+    let registry = Arc::new(Registry::standard(&config.options));
+    let mut seen = HashSet::new();
+    found
+        .filter(move |target| ready(seen.insert(*target)))
+        .map(move |(addr, edition)| {
+            let registry = registry.clone();
+            let config = config.clone();
+            async move {
+                let target = Target {
+                    addr,
+                    edition,
+                    options: &config.options,
+                    net: &TokioNet,
+                };
+                registry.probe(target).await.ok()
             }
         })
-        .buffer_unordered(concurrency)
+        .buffer_unordered(LAN_CONCURRENCY)
+        .filter_map(ready)
 }
 
+/// One server through [`Registry::standard`] for `options`.
 pub async fn probe_server(
-    addr: std::net::SocketAddr,
-    edition: types::Edition,
-    timeout_ms: u64,
-    query_enabled: bool,
-    online_mode_check: bool,
-    creds: Option<&types::Credentials>,
-) -> Option<types::ServerInfo> {
-    match edition {
-        Edition::Java => {
-            let mut info = java::probe(addr, timeout_ms).await?;
-            if query_enabled {
-                match query::probe(addr, timeout_ms).await {
-                    Some(q) => {
-                        info.world = q.world;
-                        info.plugins = q.plugins;
-                        // The ids belong to the SLP sample we're replacing, so drop them too.
-                        if !q.players.is_empty() {
-                            info.samples = q.players;
-                            info.sample_ids.clear();
-                        }
-                    }
-                    None => trace!(%addr, "query enrichment returned nothing"),
-                }
-            }
-            if online_mode_check {
-                let outcome = login::probe(addr, info.protocol, timeout_ms, creds).await;
-                info.online_mode = outcome.online_mode;
-                info.whitelist = outcome.whitelist;
-                trace!(%addr, online_mode = ?info.online_mode, whitelist = ?info.whitelist, "login probe");
-            }
-            Some(info)
-        }
-        Edition::Bedrock => bedrock::probe(addr, timeout_ms).await,
-    }
+    addr: SocketAddr,
+    edition: Edition,
+    options: &ProbeOptions,
+) -> Result<ServerInfo, ProbeError> {
+    let target = Target {
+        addr,
+        edition,
+        options,
+        net: &TokioNet,
+    };
+    Registry::standard(options).probe(target).await
 }
 
 // A 0.0.0.0 socket can't connect to an IPv6 target, so match the family.
@@ -100,6 +256,21 @@ pub(crate) fn local_bind_addr(target: &SocketAddr) -> &'static str {
     }
 }
 
+// Unpredictable enough for throwaway keys and passwords, without an RNG dependency.
+pub(crate) fn entropy_u64(salt: u64) -> u64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+    let mut h = RandomState::new().build_hasher();
+    h.write_u64(salt);
+    h.write_u128(
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos(),
+    );
+    h.finish()
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
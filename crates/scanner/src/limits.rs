@@ -60,6 +60,27 @@ impl Ports {
     }
 }
 
+// Runs of consecutive ports collapse back into spans: `25565-25665,19132`.
+impl std::fmt::Display for Ports {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut rest = &self.0[..];
+        let mut sep = "";
+        while let Some((&start, _)) = rest.split_first() {
+            let run = rest
+                .windows(2)
+                .take_while(|w| w[0].checked_add(1) == Some(w[1]))
+                .count();
+            match run {
+                0 => write!(f, "{sep}{start}")?,
+                _ => write!(f, "{sep}{start}-{}", rest[run])?,
+            }
+            rest = &rest[run + 1..];
+            sep = ",";
+        }
+        Ok(())
+    }
+}
+
 impl Deref for Ports {
     type Target = [u16];
     fn deref(&self) -> &[u16] {
@@ -95,4 +116,12 @@ mod tests {
         );
         assert!(Ports::from_input("").is_empty());
     }
+
+    #[test]
+    fn ports_display_as_spans() {
+        let ports = Ports::from_input("25565-25570, 19132, 25571, 1");
+        assert_eq!(ports.to_string(), "25565-25570,19132,25571,1");
+        assert_eq!(Ports::from_input(&ports.to_string()), ports);
+        assert_eq!(Ports::from_input("").to_string(), "");
+    }
 }
@@ -1,8 +1,8 @@
-use crate::types::Credentials;
+use crate::transport::Transport;
+use crate::types::{Credentials, Timeouts};
 use std::net::SocketAddr;
 use std::time::Duration;
-use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::TcpStream;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
 use tokio::time::timeout;
 
 const MAX_DISCONNECT_LEN: usize = 32 * 1024;
@@ -14,30 +14,37 @@ pub struct LoginOutcome {
 }
 
 pub async fn probe(
+    net: &dyn Transport,
     addr: SocketAddr,
+    host: &str,
     protocol: i32,
-    timeout_ms: u64,
+    timeouts: Timeouts,
     creds: Option<&Credentials>,
 ) -> LoginOutcome {
-    probe_inner(addr, protocol, timeout_ms, creds)
+    probe_inner(net, addr, host, protocol, timeouts, creds)
         .await
         .unwrap_or_default()
 }
 
 async fn probe_inner(
+    net: &dyn Transport,
     addr: SocketAddr,
+    host: &str,
     protocol: i32,
-    timeout_ms: u64,
+    timeouts: Timeouts,
     _creds: Option<&Credentials>,
 ) -> Option<LoginOutcome> {
-    let dur = Duration::from_millis(timeout_ms);
-    let mut stream = timeout(dur, TcpStream::connect(addr)).await.ok()?.ok()?;
+    let dur = timeouts.read;
+    let mut stream = timeout(timeouts.connect, net.connect(addr))
+        .await
+        .ok()?
+        .ok()?;
 
     let handshake = {
         let mut payload = Vec::new();
         write_varint(&mut payload, 0x00); // packet id: handshake
         write_varint(&mut payload, protocol);
-        write_string(&mut payload, &addr.ip().to_string());
+        write_string(&mut payload, host);
         payload.extend_from_slice(&addr.port().to_be_bytes());
         write_varint(&mut payload, 2); // next state = login
         frame(payload)
@@ -93,7 +100,11 @@ fn disconnect_is_whitelist(payload: &[u8]) -> bool {
     NEEDLES.iter().any(|n| msg.contains(n))
 }
 
-async fn read_packet_payload(stream: &mut TcpStream, len: i32, dur: Duration) -> Option<Vec<u8>> {
+async fn read_packet_payload<S: AsyncRead + Unpin>(
+    stream: &mut S,
+    len: i32,
+    dur: Duration,
+) -> Option<Vec<u8>> {
     let len = usize::try_from(len).ok()?.min(MAX_DISCONNECT_LEN);
     let mut buf = vec![0u8; len];
     timeout(dur, stream.read_exact(&mut buf)).await.ok()?.ok()?;
@@ -117,7 +128,7 @@ fn read_varint_buf(buf: &mut &[u8]) -> Option<i32> {
     }
 }
 
-async fn read_varint_stream(stream: &mut TcpStream) -> Option<i32> {
+async fn read_varint_stream<S: AsyncRead + Unpin>(stream: &mut S) -> Option<i32> {
     let mut result = 0i32;
     let mut shift = 0u32;
     loop {
@@ -156,7 +167,7 @@ fn build_login_start(name: &str, protocol: i32) -> Vec<u8> {
     frame(payload)
 }
 
-fn offline_uuid(name: &str) -> [u8; 16] {
+pub(crate) fn offline_uuid(name: &str) -> [u8; 16] {
     let mut out = [0u8; 16];
     let mut h: u64 = 0xcbf29ce484222325;
     for (i, b) in name.bytes().enumerate() {
@@ -196,6 +207,7 @@ fn write_string(buf: &mut Vec<u8>, s: &str) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tokio::net::TcpStream;
 
     fn disconnect_payload(reason: &str) -> Vec<u8> {
         let mut p = Vec::new();
@@ -278,4 +290,49 @@ mod tests {
         let p47 = build_login_start("Scanner", 47);
         assert_eq!(p47[0], 9);
     }
+
+    #[tokio::test]
+    async fn probes_fake_server_logins() {
+        use crate::transport::TokioNet;
+        use testkit::{JavaServer, Login};
+
+        let timeouts = Timeouts::uniform(Duration::from_secs(2));
+        let cases = [
+            (Login::EncryptionRequest, Some(true), None),
+            (Login::Success, Some(false), Some(false)),
+            (Login::Compression(256), Some(false), Some(false)),
+            (Login::not_whitelisted(), Some(false), Some(true)),
+            (
+                Login::Disconnect(r#"{"text":"Server is full"}"#.into()),
+                None,
+                None,
+            ),
+        ];
+        for (login, online_mode, whitelist) in cases {
+            let server = JavaServer::new("{}")
+                .login(login.clone())
+                .spawn()
+                .await
+                .unwrap();
+            for protocol in [47, 760, 763, 767] {
+                let outcome = probe(
+                    &TokioNet,
+                    server.addr(),
+                    "localhost",
+                    protocol,
+                    timeouts,
+                    None,
+                )
+                .await;
+                assert_eq!(
+                    outcome,
+                    LoginOutcome {
+                        online_mode,
+                        whitelist
+                    },
+                    "{login:?} at protocol {protocol}"
+                );
+            }
+        }
+    }
 }
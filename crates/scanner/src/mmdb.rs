@@ -0,0 +1,447 @@
+//! Enough of the MaxMind DB format (`.mmdb`) to read GeoLite2, DB-IP and
+//! ipinfo databases: the record for one address, for enriching results, or
+//! every network whose record matches, for target lists.
+
+use ipnet::IpNet;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::ops::Range;
+
+const METADATA_MARKER: &[u8] = b"\xAB\xCD\xEFMaxMind.com";
+// Gap between the search tree and the data section, all zeros
+const DATA_SEPARATOR: usize = 16;
+// Records nest a few levels; anything deeper is a corrupt file.
+const MAX_DEPTH: usize = 64;
+
+/// A database over its bytes, borrowed (`&[u8]`) or owned (`Vec<u8>`).
+pub struct Reader<B> {
+    buf: B,
+    tree_len: usize,
+    data: Range<usize>,
+    node_count: usize,
+    record_size: usize,
+    ipv6: bool,
+    // Where IPv4 addresses start in an IPv6 tree
+    ipv4_start: Option<usize>,
+}
+
+impl<B: AsRef<[u8]>> Reader<B> {
+    pub fn new(buf: B) -> Option<Self> {
+        let bytes = buf.as_ref();
+        let marker = bytes
+            .windows(METADATA_MARKER.len())
+            .rposition(|w| w == METADATA_MARKER)?;
+        let meta = &bytes[marker + METADATA_MARKER.len()..];
+        let (meta, _) = decode(meta, 0, 0)?;
+        let node_count = meta.get("node_count")?.as_u64()? as usize;
+        let record_size = meta.get("record_size")?.as_u64()? as usize;
+        if ![24, 28, 32].contains(&record_size) {
+            return None;
+        }
+        let tree_len = node_count.checked_mul(record_size / 4)?;
+        let data = tree_len + DATA_SEPARATOR..marker;
+        bytes.get(data.clone())?;
+        let mut reader = Self {
+            buf,
+            tree_len,
+            data,
+            node_count,
+            record_size,
+            ipv6: meta.get("ip_version")?.as_u64()? == 6,
+            ipv4_start: None,
+        };
+        if reader.ipv6 {
+            reader.ipv4_start = reader.find_ipv4_start();
+        }
+        Some(reader)
+    }
+
+    /// The record for the network holding `ip`, if any.
+    pub fn lookup(&self, ip: IpAddr) -> Option<Value> {
+        let (mut node, bits, width) = match (ip.to_canonical(), self.ipv6) {
+            (IpAddr::V4(v4), false) => (0, u32::from(v4) as u128, 32),
+            (IpAddr::V4(v4), true) => (self.ipv4_start?, u32::from(v4) as u128, 32),
+            (IpAddr::V6(v6), true) => (0, u128::from(v6), 128),
+            (IpAddr::V6(_), false) => return None,
+        };
+        for depth in 0..width {
+            let bit = (bits >> (width - 1 - depth) & 1) as usize;
+            let record = self.node(node)?[bit];
+            if record < self.node_count {
+                node = record;
+                continue;
+            }
+            let offset = (record - self.node_count).checked_sub(DATA_SEPARATOR)?;
+            return decode(self.data(), offset, 0).map(|(value, _)| value);
+        }
+        None
+    }
+
+    /// Every network whose record satisfies `keep`, in tree order. IPv4 in an
+    /// IPv6 database comes out as IPv4, and its aliases are skipped. Each node
+    /// is walked once, so a corrupt file whose nodes share children can't
+    /// multiply the work.
+    pub fn networks(&self, keep: impl Fn(&Value) -> bool) -> Vec<IpNet> {
+        let width = if self.ipv6 { 128 } else { 32 };
+        let mut verdicts: HashMap<usize, bool> = HashMap::new();
+        let mut visited = vec![false; self.node_count];
+        let mut out = Vec::new();
+        let mut stack = vec![(0usize, 0u128, 0u32)];
+        while let Some((node, bits, depth)) = stack.pop() {
+            // ::ffff:0:0/96 and 2002::/16 lead back into the IPv4 subtree.
+            if Some(node) == self.ipv4_start && (depth, bits) != (96, 0) {
+                continue;
+            }
+            let Some(records) = self.node(node) else {
+                continue;
+            };
+            if std::mem::replace(&mut visited[node], true) {
+                continue;
+            }
+            for (bit, record) in records.into_iter().enumerate().rev() {
+                let bits = bits | (bit as u128) << (width - 1 - depth);
+                let depth = depth + 1;
+                if record < self.node_count {
+                    if depth < width {
+                        stack.push((record, bits, depth));
+                    }
+                    continue;
+                }
+                let Some(offset) = (record - self.node_count).checked_sub(DATA_SEPARATOR) else {
+                    continue;
+                };
+                let matched = *verdicts.entry(offset).or_insert_with(|| {
+                    decode(self.data(), offset, 0).is_some_and(|(record, _)| keep(&record))
+                });
+                if matched {
+                    out.extend(network(bits, depth, self.ipv6));
+                }
+            }
+        }
+        out
+    }
+
+    fn data(&self) -> &[u8] {
+        &self.buf.as_ref()[self.data.clone()]
+    }
+
+    fn node(&self, n: usize) -> Option<[usize; 2]> {
+        let size = self.record_size / 4;
+        let b = self.buf.as_ref()[..self.tree_len].get(n * size..(n + 1) * size)?;
+        let be = |bytes: &[u8]| bytes.iter().fold(0usize, |v, &b| v << 8 | b as usize);
+        Some(match self.record_size {
+            24 => [be(&b[..3]), be(&b[3..])],
+            28 => [
+                (b[3] as usize >> 4) << 24 | be(&b[..3]),
+                (b[3] as usize & 0x0F) << 24 | be(&b[4..]),
+            ],
+            _ => [be(&b[..4]), be(&b[4..])],
+        })
+    }
+
+    // The node reached by 96 zero bits, where IPv4 lives in an IPv6 tree.
+    fn find_ipv4_start(&self) -> Option<usize> {
+        let mut node = 0;
+        for _ in 0..96 {
+            node = self.node(node)?[0];
+            if node >= self.node_count {
+                return None;
+            }
+        }
+        Some(node)
+    }
+}
+
+fn network(bits: u128, prefix: u32, ipv6: bool) -> Option<IpNet> {
+    if !ipv6 {
+        return IpNet::new(IpAddr::V4(Ipv4Addr::from(bits as u32)), prefix as u8).ok();
+    }
+    if prefix > 96 && bits >> 32 == 0 {
+        let ip = IpAddr::V4(Ipv4Addr::from(bits as u32));
+        return IpNet::new(ip, (prefix - 96) as u8).ok();
+    }
+    IpNet::new(IpAddr::V6(Ipv6Addr::from(bits)), prefix as u8).ok()
+}
+
+// One field at `pos` of a section whose pointers are relative to its start;
+// returns the value and where the next field begins.
+fn decode(buf: &[u8], pos: usize, depth: usize) -> Option<(Value, usize)> {
+    if depth > MAX_DEPTH {
+        return None;
+    }
+    let ctrl = *buf.get(pos)?;
+    let mut pos = pos + 1;
+    let mut kind = ctrl >> 5;
+    if kind == 1 {
+        let ss = (ctrl >> 3 & 0x03) as usize;
+        let bytes = buf.get(pos..pos + ss + 1)?;
+        let low = bytes.iter().fold(0usize, |v, &b| v << 8 | b as usize);
+        let target = match ss {
+            0 => (ctrl as usize & 0x07) << 8 | low,
+            1 => ((ctrl as usize & 0x07) << 16 | low) + 2048,
+            2 => ((ctrl as usize & 0x07) << 24 | low) + 526_336,
+            _ => low,
+        };
+        let (value, _) = decode(buf, target, depth + 1)?;
+        return Some((value, pos + ss + 1));
+    }
+    if kind == 0 {
+        // Extended types run from 8 (int32) to 15 (float).
+        kind = buf
+            .get(pos)?
+            .checked_add(7)
+            .filter(|k| (8..=15).contains(k))?;
+        pos += 1;
+    }
+    let mut size = (ctrl & 0x1F) as usize;
+    if size >= 29 {
+        let extra = size - 28;
+        let bytes = buf.get(pos..pos + extra)?;
+        let n = bytes.iter().fold(0usize, |v, &b| v << 8 | b as usize);
+        size = [29, 285, 65_821][extra - 1] + n;
+        pos += extra;
+    }
+    let uint = |bytes: &[u8]| bytes.iter().fold(0u64, |v, &b| v << 8 | b as u64);
+    match kind {
+        2 => {
+            let s = String::from_utf8_lossy(buf.get(pos..pos + size)?);
+            Some((Value::String(s.into_owned()), pos + size))
+        }
+        3 if size == 8 => {
+            let v = f64::from_be_bytes(buf.get(pos..pos + 8)?.try_into().ok()?);
+            Some((Value::from(v), pos + 8))
+        }
+        15 if size == 4 => {
+            let v = f32::from_be_bytes(buf.get(pos..pos + 4)?.try_into().ok()?);
+            Some((Value::from(v as f64), pos + 4))
+        }
+        5 | 6 | 9 if size <= 8 => Some((Value::from(uint(buf.get(pos..pos + size)?)), pos + size)),
+        8 if size <= 4 => {
+            let v = uint(buf.get(pos..pos + size)?) as u32 as i32;
+            Some((Value::from(v), pos + size))
+        }
+        7 => {
+            let mut map = Map::new();
+            for _ in 0..size {
+                let (key, next) = decode(buf, pos, depth + 1)?;
+                let (value, next) = decode(buf, next, depth + 1)?;
+                map.insert(key.as_str()?.to_string(), value);
+                pos = next;
+            }
+            Some((Value::Object(map), pos))
+        }
+        11 => {
+            let mut items = Vec::with_capacity(size.min(256));
+            for _ in 0..size {
+                let (value, next) = decode(buf, pos, depth + 1)?;
+                items.push(value);
+                pos = next;
+            }
+            Some((Value::Array(items), pos))
+        }
+        14 => Some((Value::Bool(size != 0), pos)),
+        // Bytes and uint128 aren't needed to match records.
+        4 | 10 => {
+            buf.get(pos..pos + size)?;
+            Some((Value::Null, pos + size))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn encode(v: &Value, out: &mut Vec<u8>) {
+        let head = |kind: u8, size: usize, out: &mut Vec<u8>| match size {
+            0..29 => out.push(kind << 5 | size as u8),
+            29..285 => out.extend([kind << 5 | 29, (size - 29) as u8]),
+            _ => unreachable!("fixtures stay small"),
+        };
+        match v {
+            Value::String(s) => {
+                head(2, s.len(), out);
+                out.extend(s.as_bytes());
+            }
+            Value::Number(n) => {
+                let bytes = (n.as_u64().unwrap() as u32).to_be_bytes();
+                let skip = bytes.iter().take_while(|&&b| b == 0).count();
+                head(6, 4 - skip, out);
+                out.extend(&bytes[skip..]);
+            }
+            Value::Object(map) => {
+                head(7, map.len(), out);
+                for (k, v) in map {
+                    encode(&Value::String(k.clone()), out);
+                    encode(v, out);
+                }
+            }
+            _ => unreachable!("not used by the fixtures"),
+        }
+    }
+
+    /// A database with 24-bit records holding `entries`; IPv6 databases take
+    /// IPv4 networks under `::/96`, and get the `::ffff:0:0/96` alias.
+    pub(crate) fn build(ipv6: bool, entries: &[(&str, Value)]) -> Vec<u8> {
+        const EMPTY: u32 = u32::MAX;
+        const DATA: u32 = 1 << 31;
+        let width = if ipv6 { 128 } else { 32 };
+        let mut nodes: Vec<[u32; 2]> = vec![[EMPTY, EMPTY]];
+        let mut data = Vec::new();
+        let insert = |nodes: &mut Vec<[u32; 2]>, bits: u128, prefix: u32, leaf: u32| {
+            let mut node = 0;
+            for d in 0..prefix {
+                let bit = (bits >> (width - 1 - d) & 1) as usize;
+                if d + 1 == prefix {
+                    nodes[node][bit] = leaf;
+                } else {
+                    if nodes[node][bit] == EMPTY {
+                        nodes.push([EMPTY, EMPTY]);
+                        nodes[node][bit] = (nodes.len() - 1) as u32;
+                    }
+                    node = nodes[node][bit] as usize;
+                }
+            }
+        };
+        for (net, record) in entries {
+            let net: IpNet = net.parse().unwrap();
+            let (bits, prefix) = match (net, ipv6) {
+                (IpNet::V4(n), false) => (u32::from(n.network()) as u128, n.prefix_len() as u32),
+                (IpNet::V4(n), true) => {
+                    (u32::from(n.network()) as u128, n.prefix_len() as u32 + 96)
+                }
+                (IpNet::V6(n), _) => (u128::from(n.network()), n.prefix_len() as u32),
+            };
+            let leaf = DATA | data.len() as u32;
+            encode(record, &mut data);
+            insert(&mut nodes, bits, prefix, leaf);
+        }
+        if ipv6 {
+            // Point ::ffff:0:0/96 at the IPv4 subtree, as real databases do.
+            let mut v4 = 0usize;
+            for _ in 0..96 {
+                v4 = nodes[v4][0] as usize;
+            }
+            let alias = 0xFFFFu128 << 32;
+            insert(&mut nodes, alias, 96, v4 as u32);
+        }
+        let count = nodes.len() as u32;
+        let resolve = |r: u32| match r {
+            EMPTY => count,
+            r if r & DATA != 0 => count + DATA_SEPARATOR as u32 + (r & !DATA),
+            r => r,
+        };
+        let mut out = Vec::new();
+        for [l, r] in &nodes {
+            out.extend(&resolve(*l).to_be_bytes()[1..]);
+            out.extend(&resolve(*r).to_be_bytes()[1..]);
+        }
+        out.extend([0u8; DATA_SEPARATOR]);
+        out.extend(data);
+        out.extend(METADATA_MARKER);
+        let meta = json!({
+            "node_count": count,
+            "record_size": 24,
+            "ip_version": if ipv6 { 6 } else { 4 },
+        });
+        encode(&meta, &mut out);
+        out
+    }
+
+    fn nets(db: &[u8], keep: impl Fn(&Value) -> bool) -> Vec<String> {
+        let mut nets: Vec<String> = Reader::new(db)
+            .unwrap()
+            .networks(keep)
+            .iter()
+            .map(|n| n.to_string())
+            .collect();
+        nets.sort();
+        nets
+    }
+
+    #[test]
+    fn walks_networks_whose_record_matches() {
+        let db = build(
+            false,
+            &[
+                ("1.0.0.0/24", json!({"autonomous_system_number": 13335})),
+                ("8.8.8.0/24", json!({"autonomous_system_number": 15169})),
+                ("104.16.0.0/13", json!({"autonomous_system_number": 13335})),
+            ],
+        );
+        let cloudflare = |v: &Value| v["autonomous_system_number"] == 13335;
+        assert_eq!(nets(&db, cloudflare), ["1.0.0.0/24", "104.16.0.0/13"]);
+    }
+
+    #[test]
+    fn ipv6_databases_report_ipv4_once() {
+        let de = json!({"country": {"iso_code": "DE"}});
+        let db = build(
+            true,
+            &[
+                ("5.1.0.0/16", de.clone()),
+                ("2a01:4f8::/32", de),
+                ("9.9.9.0/24", json!({"country": {"iso_code": "US"}})),
+            ],
+        );
+        let german = |v: &Value| v["country"]["iso_code"] == "DE";
+        assert_eq!(nets(&db, german), ["2a01:4f8::/32", "5.1.0.0/16"]);
+    }
+
+    #[test]
+    fn looks_up_the_record_for_an_address() {
+        let db = build(
+            true,
+            &[
+                ("5.9.0.0/16", json!({"autonomous_system_number": 24940})),
+                ("2a01:4f8::/32", json!({"autonomous_system_number": 24940})),
+            ],
+        );
+        let reader = Reader::new(db).unwrap();
+        let asn =
+            |ip: &str| reader.lookup(ip.parse().unwrap())?["autonomous_system_number"].as_u64();
+        assert_eq!(asn("5.9.10.20"), Some(24940));
+        assert_eq!(asn("::ffff:5.9.10.20"), Some(24940));
+        assert_eq!(asn("2a01:4f8:10::1"), Some(24940));
+        assert_eq!(asn("5.10.0.1"), None);
+        let v4 = build(false, &[("5.9.0.0/16", json!({"asn": 1}))]);
+        assert!(
+            Reader::new(&v4)
+                .unwrap()
+                .lookup("2a01:4f8::1".parse().unwrap())
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn walks_each_node_once() {
+        // 1.0.0.0/24 is a chain of 24 nodes; point both children of each at
+        // the next, so every one of 2^23 paths would reach the same leaf.
+        let mut db = build(false, &[("1.0.0.0/24", json!({"a": 1}))]);
+        for node in 0..23u32 {
+            let next = &(node + 1).to_be_bytes()[1..];
+            let at = node as usize * 6;
+            db[at..at + 3].copy_from_slice(next);
+            db[at + 3..at + 6].copy_from_slice(next);
+        }
+        assert_eq!(nets(&db, |_| true), ["0.0.0.0/24"]);
+    }
+
+    #[test]
+    fn rejects_unknown_extended_types() {
+        assert!(decode(&[0x00, 0xFF], 0, 0).is_none());
+        assert!(decode(&[0x00, 0x00], 0, 0).is_none());
+        assert_eq!(decode(&[0x01, 0x07], 0, 0), Some((Value::Bool(true), 2)));
+    }
+
+    #[test]
+    fn rejects_files_without_metadata() {
+        assert!(Reader::new(b"not a database").is_none());
+        let mut db = build(false, &[("1.0.0.0/24", json!({"a": 1}))]);
+        db.truncate(10);
+        assert!(Reader::new(&db).is_none());
+    }
+}
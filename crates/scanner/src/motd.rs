@@ -0,0 +1,451 @@
+use serde_json::{Map, Value};
+use std::fmt;
+
+// §0-§f, then Bedrock's §g (minecoin gold).
+const PALETTE: [Rgb; 17] = [
+    Rgb(0x00, 0x00, 0x00),
+    Rgb(0x00, 0x00, 0xAA),
+    Rgb(0x00, 0xAA, 0x00),
+    Rgb(0x00, 0xAA, 0xAA),
+    Rgb(0xAA, 0x00, 0x00),
+    Rgb(0xAA, 0x00, 0xAA),
+    Rgb(0xFF, 0xAA, 0x00),
+    Rgb(0xAA, 0xAA, 0xAA),
+    Rgb(0x55, 0x55, 0x55),
+    Rgb(0x55, 0x55, 0xFF),
+    Rgb(0x55, 0xFF, 0x55),
+    Rgb(0x55, 0xFF, 0xFF),
+    Rgb(0xFF, 0x55, 0x55),
+    Rgb(0xFF, 0x55, 0xFF),
+    Rgb(0xFF, 0xFF, 0x55),
+    Rgb(0xFF, 0xFF, 0xFF),
+    Rgb(0xDD, 0xD6, 0x05),
+];
+
+// Chat component color names, in palette order.
+const NAMES: [&str; 16] = [
+    "black",
+    "dark_blue",
+    "dark_green",
+    "dark_aqua",
+    "dark_red",
+    "dark_purple",
+    "gold",
+    "gray",
+    "dark_gray",
+    "blue",
+    "green",
+    "aqua",
+    "red",
+    "light_purple",
+    "yellow",
+    "white",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    /// A legacy `§` color code, `0`-`9` and `a`-`f`.
+    pub fn from_code(code: char) -> Option<Self> {
+        let idx = code.to_digit(16)? as usize;
+        Some(PALETTE[idx])
+    }
+
+    /// As [`Rgb::from_code`], plus the `g` only Bedrock clients color.
+    pub fn from_bedrock_code(code: char) -> Option<Self> {
+        match code {
+            'g' => Some(PALETTE[16]),
+            _ => Self::from_code(code),
+        }
+    }
+
+    /// A chat component color: a name like `dark_red` or `#RRGGBB`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(hex) = name.strip_prefix('#') {
+            return Self::from_hex(hex);
+        }
+        NAMES.iter().position(|&n| n == name).map(|i| PALETTE[i])
+    }
+
+    fn from_hex(hex: &str) -> Option<Self> {
+        if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let v = u32::from_str_radix(hex, 16).ok()?;
+        Some(Self((v >> 16) as u8, (v >> 8) as u8, v as u8))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Style {
+    // None = the client's default text color
+    pub color: Option<Rgb>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underlined: bool,
+    pub strikethrough: bool,
+    pub obfuscated: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Span {
+    pub text: String,
+    pub style: Style,
+}
+
+/// A server's MOTD as styled spans, from a Java chat component or a `§`-coded
+/// string. Adjacent spans never share a style.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Motd {
+    pub spans: Vec<Span>,
+}
+
+impl Motd {
+    /// A `§`-coded string, as older Java servers send. Bungee's
+    /// `§x§r§r§g§g§b§b` hex colors are understood too.
+    pub fn from_legacy(s: &str) -> Self {
+        let mut motd = Self::default();
+        motd.push_legacy(s, &Style::default(), false);
+        motd
+    }
+
+    /// A Bedrock pong's `§`-coded MOTD, where `§g` is minecoin gold.
+    pub fn from_bedrock(s: &str) -> Self {
+        let mut motd = Self::default();
+        motd.push_legacy(s, &Style::default(), true);
+        motd
+    }
+
+    /// A Java `description`: a string, a component object or an array of them.
+    pub fn from_component(v: &Value) -> Self {
+        let mut motd = Self::default();
+        motd.walk(v, &Style::default());
+        motd
+    }
+
+    /// The text without any styling, for search and export.
+    pub fn plain(&self) -> String {
+        self.spans.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// One `Motd` per `\n`-separated line.
+    pub fn lines(&self) -> Vec<Motd> {
+        let mut lines = vec![Motd::default()];
+        for span in &self.spans {
+            for (i, part) in span.text.split('\n').enumerate() {
+                if i > 0 {
+                    lines.push(Motd::default());
+                }
+                if let Some(line) = lines.last_mut() {
+                    line.push(part, &span.style);
+                }
+            }
+        }
+        lines
+    }
+
+    /// Without leading and trailing whitespace; servers pad MOTDs to center them.
+    pub fn trim(&self) -> Motd {
+        let mut spans = self.spans.clone();
+        while let Some(first) = spans.first_mut() {
+            first.text = first.text.trim_start().to_string();
+            if !first.text.is_empty() {
+                break;
+            }
+            spans.remove(0);
+        }
+        while let Some(last) = spans.last_mut() {
+            last.text = last.text.trim_end().to_string();
+            if !last.text.is_empty() {
+                break;
+            }
+            spans.pop();
+        }
+        Motd { spans }
+    }
+
+    pub fn append(&mut self, other: Motd) {
+        for span in other.spans {
+            self.push(&span.text, &span.style);
+        }
+    }
+
+    fn push(&mut self, text: &str, style: &Style) {
+        if text.is_empty() {
+            return;
+        }
+        match self.spans.last_mut() {
+            Some(last) if last.style == *style => last.text.push_str(text),
+            _ => self.spans.push(Span {
+                text: text.to_string(),
+                style: style.clone(),
+            }),
+        }
+    }
+
+    // Returns the node's own style, which array siblings after the first inherit.
+    fn walk(&mut self, v: &Value, inherited: &Style) -> Style {
+        match v {
+            Value::String(s) => self.push_legacy(s, inherited, false),
+            Value::Number(_) | Value::Bool(_) => self.push(&v.to_string(), inherited),
+            Value::Array(arr) => {
+                if let Some((first, rest)) = arr.split_first() {
+                    let style = self.walk(first, inherited);
+                    for child in rest {
+                        self.walk(child, &style);
+                    }
+                    return style;
+                }
+            }
+            Value::Object(map) => {
+                let style = merge(inherited, map);
+                if let Some(text) = map.get("text").and_then(Value::as_str) {
+                    self.push_legacy(text, &style, false);
+                }
+                for child in map
+                    .get("extra")
+                    .and_then(Value::as_array)
+                    .into_iter()
+                    .flatten()
+                {
+                    self.walk(child, &style);
+                }
+                return style;
+            }
+            Value::Null => {}
+        }
+        inherited.clone()
+    }
+
+    // Codes inside component text are common; `§r` falls back to the component's style.
+    fn push_legacy(&mut self, s: &str, base: &Style, bedrock: bool) {
+        let mut style = base.clone();
+        let mut chars = s.chars();
+        let mut run = String::new();
+        while let Some(c) = chars.next() {
+            if c != '\u{00A7}' {
+                run.push(c);
+                continue;
+            }
+            self.push(&run, &style);
+            run.clear();
+            let Some(code) = chars.next().map(|c| c.to_ascii_lowercase()) else {
+                break;
+            };
+            match code {
+                'x' => {
+                    let rest = chars.as_str();
+                    if let Some((rgb, used)) = bungee_hex(rest) {
+                        style = Style {
+                            color: Some(rgb),
+                            ..Style::default()
+                        };
+                        chars = rest[used..].chars();
+                    }
+                }
+                'k' => style.obfuscated = true,
+                'l' => style.bold = true,
+                'm' => style.strikethrough = true,
+                'n' => style.underlined = true,
+                'o' => style.italic = true,
+                'r' => style = base.clone(),
+                // A color code also clears formatting.
+                _ => {
+                    let color = match bedrock {
+                        true => Rgb::from_bedrock_code(code),
+                        false => Rgb::from_code(code),
+                    };
+                    if let Some(rgb) = color {
+                        style = Style {
+                            color: Some(rgb),
+                            ..Style::default()
+                        };
+                    }
+                }
+            }
+        }
+        self.push(&run, &style);
+    }
+}
+
+impl fmt::Display for Motd {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.plain())
+    }
+}
+
+impl From<&str> for Motd {
+    fn from(s: &str) -> Self {
+        Self::from_legacy(s)
+    }
+}
+
+fn merge(inherited: &Style, map: &Map<String, Value>) -> Style {
+    let flag = |key: &str, current: bool| map.get(key).and_then(Value::as_bool).unwrap_or(current);
+    Style {
+        color: map
+            .get("color")
+            .and_then(Value::as_str)
+            .and_then(Rgb::from_name)
+            .or(inherited.color),
+        bold: flag("bold", inherited.bold),
+        italic: flag("italic", inherited.italic),
+        underlined: flag("underlined", inherited.underlined),
+        strikethrough: flag("strikethrough", inherited.strikethrough),
+        obfuscated: flag("obfuscated", inherited.obfuscated),
+    }
+}
+
+// The six `§h` pairs after `§x`, and how many bytes they take.
+fn bungee_hex(rest: &str) -> Option<(Rgb, usize)> {
+    let mut hex = String::with_capacity(6);
+    let mut chars = rest.char_indices();
+    for _ in 0..6 {
+        let (_, '\u{00A7}') = chars.next()? else {
+            return None;
+        };
+        hex.push(chars.next()?.1);
+    }
+    let used = chars.next().map_or(rest.len(), |(i, _)| i);
+    Some((Rgb::from_hex(&hex)?, used))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn span(text: &str, style: Style) -> Span {
+        Span {
+            text: text.into(),
+            style,
+        }
+    }
+
+    fn colored(rgb: Rgb) -> Style {
+        Style {
+            color: Some(rgb),
+            ..Style::default()
+        }
+    }
+
+    #[test]
+    fn legacy_colors_reset_formatting() {
+        let motd = Motd::from_legacy("§l§aHi §othere§cX");
+        assert_eq!(
+            motd.spans,
+            [
+                span("Hi ", colored(Rgb(0x55, 0xFF, 0x55))),
+                span(
+                    "there",
+                    Style {
+                        italic: true,
+                        ..colored(Rgb(0x55, 0xFF, 0x55))
+                    }
+                ),
+                span("X", colored(Rgb(0xFF, 0x55, 0x55))),
+            ]
+        );
+        assert_eq!(motd.plain(), "Hi thereX");
+    }
+
+    #[test]
+    fn reads_bungee_hex_and_bedrock_gold() {
+        let motd = Motd::from_bedrock("§x§1§2§A§b§c§dHex§gCoins");
+        assert_eq!(
+            motd.spans,
+            [
+                span("Hex", colored(Rgb(0x12, 0xAB, 0xCD))),
+                span("Coins", colored(Rgb(0xDD, 0xD6, 0x05))),
+            ]
+        );
+        // Java clients don't color `§g`.
+        let java = Motd::from_legacy("§aHex§gCoins");
+        assert_eq!(
+            java.spans,
+            [span("HexCoins", colored(Rgb(0x55, 0xFF, 0x55)))]
+        );
+    }
+
+    #[test]
+    fn tolerates_dangling_and_unknown_codes() {
+        assert_eq!(Motd::from_legacy("a§zb§").plain(), "ab");
+        assert_eq!(Motd::from_legacy("§x§1§2Short").plain(), "Short");
+        assert_eq!(Rgb::from_name("#+12345"), None);
+        assert_eq!(Rgb::from_name("#12AB5f"), Some(Rgb(0x12, 0xAB, 0x5F)));
+    }
+
+    #[test]
+    fn components_inherit_style_down_the_tree() {
+        let v = json!({
+            "text": "",
+            "bold": true,
+            "extra": [
+                {"text": "Red", "color": "red"},
+                {"text": "Hex", "color": "#00FF80", "bold": false, "underlined": true},
+                "§kplain",
+            ],
+        });
+        let motd = Motd::from_component(&v);
+        let bold = Style {
+            bold: true,
+            ..Style::default()
+        };
+        assert_eq!(
+            motd.spans,
+            [
+                span(
+                    "Red",
+                    Style {
+                        bold: true,
+                        ..colored(Rgb(0xFF, 0x55, 0x55))
+                    }
+                ),
+                span(
+                    "Hex",
+                    Style {
+                        underlined: true,
+                        ..colored(Rgb(0x00, 0xFF, 0x80))
+                    }
+                ),
+                span(
+                    "plain",
+                    Style {
+                        obfuscated: true,
+                        ..bold
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn array_siblings_inherit_the_first_element() {
+        let v = json!([{"text": "A", "italic": true}, "B", {"text": "C", "italic": false}]);
+        let motd = Motd::from_component(&v);
+        let italic = Style {
+            italic: true,
+            ..Style::default()
+        };
+        assert_eq!(
+            motd.spans,
+            [span("AB", italic), span("C", Style::default())]
+        );
+    }
+
+    #[test]
+    fn splits_and_trims_lines() {
+        let motd = Motd::from_legacy("  §aTop  \n  §7bottom ");
+        let lines: Vec<String> = motd.lines().iter().map(|l| l.trim().plain()).collect();
+        assert_eq!(lines, ["Top", "bottom"]);
+        assert_eq!(
+            motd.lines()[1].trim().spans[0].style,
+            colored(Rgb(0xAA, 0xAA, 0xAA))
+        );
+    }
+}
@@ -0,0 +1,373 @@
+//! Minecraft's Named Binary Tag format, uncompressed and big-endian as in
+//! `servers.dat`. Strings are Java's modified UTF-8. Reading checks every
+//! length against the input, so a hostile file can't make it allocate more
+//! than it's worth.
+
+// Vanilla's own limit; deeper files are rejected rather than overflowing the stack.
+const MAX_DEPTH: usize = 512;
+
+const END: u8 = 0;
+const BYTE: u8 = 1;
+const SHORT: u8 = 2;
+const INT: u8 = 3;
+const LONG: u8 = 4;
+const FLOAT: u8 = 5;
+const DOUBLE: u8 = 6;
+const BYTE_ARRAY: u8 = 7;
+const STRING: u8 = 8;
+const LIST: u8 = 9;
+const COMPOUND: u8 = 10;
+const INT_ARRAY: u8 = 11;
+const LONG_ARRAY: u8 = 12;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    // Elements share one type; an empty list is written as a list of End
+    List(Vec<Tag>),
+    // In file order, which vanilla keeps too
+    Compound(Vec<(String, Tag)>),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Tag {
+    /// A compound's entry by name.
+    pub fn get(&self, key: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    fn id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => BYTE,
+            Tag::Short(_) => SHORT,
+            Tag::Int(_) => INT,
+            Tag::Long(_) => LONG,
+            Tag::Float(_) => FLOAT,
+            Tag::Double(_) => DOUBLE,
+            Tag::ByteArray(_) => BYTE_ARRAY,
+            Tag::String(_) => STRING,
+            Tag::List(_) => LIST,
+            Tag::Compound(_) => COMPOUND,
+            Tag::IntArray(_) => INT_ARRAY,
+            Tag::LongArray(_) => LONG_ARRAY,
+        }
+    }
+}
+
+/// A root tag named `name`, usually `""`.
+pub fn write(name: &str, root: &Tag) -> Vec<u8> {
+    let mut out = vec![root.id()];
+    write_str(&mut out, name);
+    write_payload(&mut out, root);
+    out
+}
+
+/// The root tag and its name; None if `bytes` isn't exactly one well-formed tag.
+pub fn read(bytes: &[u8]) -> Option<(String, Tag)> {
+    let mut r = Reader { buf: bytes };
+    let id = r.u8()?;
+    let name = r.string()?;
+    let tag = r.payload(id, 0)?;
+    r.buf.is_empty().then_some((name, tag))
+}
+
+fn write_payload(out: &mut Vec<u8>, tag: &Tag) {
+    match tag {
+        Tag::Byte(v) => out.push(*v as u8),
+        Tag::Short(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Int(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Long(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Float(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::Double(v) => out.extend_from_slice(&v.to_be_bytes()),
+        Tag::ByteArray(v) => {
+            out.extend_from_slice(&(v.len() as i32).to_be_bytes());
+            out.extend(v.iter().map(|&b| b as u8));
+        }
+        Tag::String(s) => write_str(out, s),
+        Tag::List(items) => {
+            out.push(items.first().map_or(END, Tag::id));
+            out.extend_from_slice(&(items.len() as i32).to_be_bytes());
+            for item in items {
+                write_payload(out, item);
+            }
+        }
+        Tag::Compound(entries) => {
+            for (key, value) in entries {
+                out.push(value.id());
+                write_str(out, key);
+                write_payload(out, value);
+            }
+            out.push(END);
+        }
+        Tag::IntArray(v) => {
+            out.extend_from_slice(&(v.len() as i32).to_be_bytes());
+            for n in v {
+                out.extend_from_slice(&n.to_be_bytes());
+            }
+        }
+        Tag::LongArray(v) => {
+            out.extend_from_slice(&(v.len() as i32).to_be_bytes());
+            for n in v {
+                out.extend_from_slice(&n.to_be_bytes());
+            }
+        }
+    }
+}
+
+// Modified UTF-8: NUL as C0 80 and astral chars as two 3-byte surrogates.
+// Longer strings than the u16 prefix allows are cut at a char boundary.
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    let mut bytes = Vec::with_capacity(s.len());
+    for c in s.chars() {
+        let mut units = [0u16; 2];
+        let mut encoded = Vec::with_capacity(6);
+        for &unit in c.encode_utf16(&mut units).iter() {
+            let u = unit as u32;
+            match u {
+                0x01..=0x7F => encoded.push(u as u8),
+                0x00 | 0x80..=0x7FF => {
+                    encoded.push(0xC0 | (u >> 6) as u8);
+                    encoded.push(0x80 | (u & 0x3F) as u8);
+                }
+                _ => {
+                    encoded.push(0xE0 | (u >> 12) as u8);
+                    encoded.push(0x80 | ((u >> 6) & 0x3F) as u8);
+                    encoded.push(0x80 | (u & 0x3F) as u8);
+                }
+            }
+        }
+        if bytes.len() + encoded.len() > u16::MAX as usize {
+            break;
+        }
+        bytes.extend_from_slice(&encoded);
+    }
+    out.extend_from_slice(&(bytes.len() as u16).to_be_bytes());
+    out.extend_from_slice(&bytes);
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        if self.buf.len() < n {
+            return None;
+        }
+        let (head, rest) = self.buf.split_at(n);
+        self.buf = rest;
+        Some(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        self.take(N)?.try_into().ok()
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        Some(self.take(1)?[0])
+    }
+
+    // Array and list lengths; negative ones are malformed.
+    fn len(&mut self, elem_size: usize) -> Option<usize> {
+        let n = usize::try_from(i32::from_be_bytes(self.array()?)).ok()?;
+        // Every element takes at least `elem_size` bytes of what's left.
+        (n.checked_mul(elem_size.max(1))? <= self.buf.len()).then_some(n)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        let len = u16::from_be_bytes(self.array()?) as usize;
+        decode_mutf8(self.take(len)?)
+    }
+
+    fn payload(&mut self, id: u8, depth: usize) -> Option<Tag> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        Some(match id {
+            BYTE => Tag::Byte(self.u8()? as i8),
+            SHORT => Tag::Short(i16::from_be_bytes(self.array()?)),
+            INT => Tag::Int(i32::from_be_bytes(self.array()?)),
+            LONG => Tag::Long(i64::from_be_bytes(self.array()?)),
+            FLOAT => Tag::Float(f32::from_be_bytes(self.array()?)),
+            DOUBLE => Tag::Double(f64::from_be_bytes(self.array()?)),
+            BYTE_ARRAY => {
+                let n = self.len(1)?;
+                Tag::ByteArray(self.take(n)?.iter().map(|&b| b as i8).collect())
+            }
+            STRING => Tag::String(self.string()?),
+            LIST => {
+                let elem = self.u8()?;
+                // Sized by what's left to read, not just by the claimed count.
+                let n = self.len(min_payload(elem))?;
+                if elem == END && n > 0 {
+                    return None;
+                }
+                let mut items = Vec::with_capacity(n);
+                for _ in 0..n {
+                    items.push(self.payload(elem, depth + 1)?);
+                }
+                Tag::List(items)
+            }
+            COMPOUND => {
+                let mut entries = Vec::new();
+                loop {
+                    let id = self.u8()?;
+                    if id == END {
+                        break;
+                    }
+                    let key = self.string()?;
+                    entries.push((key, self.payload(id, depth + 1)?));
+                }
+                Tag::Compound(entries)
+            }
+            INT_ARRAY => {
+                let n = self.len(4)?;
+                let mut v = Vec::with_capacity(n);
+                for _ in 0..n {
+                    v.push(i32::from_be_bytes(self.array()?));
+                }
+                Tag::IntArray(v)
+            }
+            LONG_ARRAY => {
+                let n = self.len(8)?;
+                let mut v = Vec::with_capacity(n);
+                for _ in 0..n {
+                    v.push(i64::from_be_bytes(self.array()?));
+                }
+                Tag::LongArray(v)
+            }
+            _ => return None,
+        })
+    }
+}
+
+// The fewest bytes a payload of type `id` takes, for bounding list lengths.
+fn min_payload(id: u8) -> usize {
+    match id {
+        BYTE | COMPOUND => 1,
+        SHORT | STRING => 2,
+        INT | FLOAT | BYTE_ARRAY | INT_ARRAY | LONG_ARRAY => 4,
+        LIST => 5,
+        LONG | DOUBLE => 8,
+        _ => 1,
+    }
+}
+
+// Collects UTF-16 units and lets the standard decoder pair the surrogates.
+fn decode_mutf8(bytes: &[u8]) -> Option<String> {
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let cont = |j: usize| {
+            bytes
+                .get(j)
+                .filter(|&&c| c & 0xC0 == 0x80)
+                .map(|&c| (c & 0x3F) as u16)
+        };
+        let (unit, len) = match b {
+            0x01..=0x7F => (b as u16, 1),
+            0xC0..=0xDF => (((b & 0x1F) as u16) << 6 | cont(i + 1)?, 2),
+            0xE0..=0xEF => (
+                ((b & 0x0F) as u16) << 12 | cont(i + 1)? << 6 | cont(i + 2)?,
+                3,
+            ),
+            _ => return None,
+        };
+        units.push(unit);
+        i += len;
+    }
+    String::from_utf16(&units).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_every_tag_type() {
+        let root = Tag::Compound(vec![
+            ("b".into(), Tag::Byte(-1)),
+            ("s".into(), Tag::Short(300)),
+            ("i".into(), Tag::Int(-70_000)),
+            ("l".into(), Tag::Long(1 << 40)),
+            ("f".into(), Tag::Float(1.5)),
+            ("d".into(), Tag::Double(-2.25)),
+            ("ba".into(), Tag::ByteArray(vec![1, -2, 3])),
+            ("str".into(), Tag::String("héllo \0 🎮".into())),
+            ("empty".into(), Tag::List(Vec::new())),
+            (
+                "list".into(),
+                Tag::List(vec![
+                    Tag::Compound(vec![("x".into(), Tag::Int(1))]),
+                    Tag::Compound(Vec::new()),
+                ]),
+            ),
+            ("ia".into(), Tag::IntArray(vec![1, -1])),
+            ("la".into(), Tag::LongArray(vec![i64::MIN])),
+        ]);
+        let bytes = write("", &root);
+        assert_eq!(read(&bytes), Some((String::new(), root)));
+    }
+
+    #[test]
+    fn encodes_strings_as_modified_utf8() {
+        let mut out = Vec::new();
+        write_str(&mut out, "a\0🎮");
+        // NUL is C0 80; U+1F3AE is the surrogates D83C DFAE, three bytes each.
+        assert_eq!(
+            out,
+            [0, 9, b'a', 0xC0, 0x80, 0xED, 0xA0, 0xBC, 0xED, 0xBE, 0xAE]
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_and_oversized_input() {
+        let bytes = write("", &Tag::Compound(vec![("k".into(), Tag::Int(7))]));
+        for cut in 0..bytes.len() {
+            assert!(read(&bytes[..cut]).is_none(), "cut at {cut}");
+        }
+        // A byte array claiming 2 GiB in a 12-byte file
+        let huge = [BYTE_ARRAY, 0, 0, 0x7F, 0xFF, 0xFF, 0xFF, 0, 0, 0, 0, 0];
+        assert!(read(&huge).is_none());
+        // A list of 2^31 compounds, though each needs at least one byte
+        let list = [LIST, 0, 0, COMPOUND, 0x7F, 0xFF, 0xFF, 0xFF, END];
+        assert!(read(&list).is_none());
+        // Ten longs in the space of one
+        let longs = [LIST, 0, 0, LONG, 0, 0, 0, 10, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert!(read(&longs).is_none());
+        // Trailing garbage after the root
+        let mut extra = bytes.clone();
+        extra.push(0);
+        assert!(read(&extra).is_none());
+    }
+
+    #[test]
+    fn rejects_runaway_nesting() {
+        // Lists of lists, one level deeper than allowed
+        let mut bytes = vec![LIST, 0, 0];
+        for _ in 0..=MAX_DEPTH {
+            bytes.extend_from_slice(&[LIST, 0, 0, 0, 1]);
+        }
+        bytes.extend_from_slice(&[END, 0, 0, 0, 0]);
+        assert!(read(&bytes).is_none());
+    }
+}
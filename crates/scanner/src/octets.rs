@@ -0,0 +1,181 @@
+//! nmap's octet notation for IPv4: each octet a value, a span, a comma list of
+//! those, or `*`, as in `10.0.1-5.*`, `192.168.0,2,4.1-254` or `10.*.0.1`.
+//! Spans may be open-ended (`-100`, `200-`). As in nmap, `*` covers 0-255.
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr};
+
+/// An IPv4 address set in octet notation. Only the octet values are stored;
+/// hosts are produced on demand, so `*.*.*.1` costs four short lists.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OctetPattern {
+    // Each octet's values, ascending and without repeats
+    octets: [Vec<u8>; 4],
+}
+
+impl OctetPattern {
+    pub fn parse(s: &str) -> Option<Self> {
+        let parts: Vec<&str> = s.trim().split('.').collect();
+        let [a, b, c, d] = parts[..] else {
+            return None;
+        };
+        Some(Self {
+            octets: [octet(a)?, octet(b)?, octet(c)?, octet(d)?],
+        })
+    }
+
+    pub fn host_count(&self) -> u128 {
+        self.octets.iter().map(|o| o.len() as u128).product()
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let IpAddr::V4(ip) = ip.to_canonical() else {
+            return false;
+        };
+        ip.octets()
+            .iter()
+            .zip(&self.octets)
+            .all(|(b, values)| values.binary_search(b).is_ok())
+    }
+
+    /// Every address, lowest first.
+    pub fn hosts(&self) -> impl Iterator<Item = IpAddr> + Send + 'static {
+        let octets = self.octets.clone();
+        (0..self.host_count() as u64).map(move |mut i| {
+            let mut addr = [0u8; 4];
+            for (slot, values) in addr.iter_mut().zip(&octets).rev() {
+                let len = values.len() as u64;
+                *slot = values[(i % len) as usize];
+                i /= len;
+            }
+            IpAddr::V4(Ipv4Addr::from(addr))
+        })
+    }
+}
+
+// `*`, or comma-separated values and spans.
+fn octet(s: &str) -> Option<Vec<u8>> {
+    let mut seen = [false; 256];
+    for item in s.split(',').map(str::trim) {
+        let (low, high): (u8, u8) = match item {
+            "*" => (0, 255),
+            _ => match item.split_once('-') {
+                Some((low, high)) => (
+                    if low.is_empty() { 0 } else { low.parse().ok()? },
+                    if high.is_empty() {
+                        255
+                    } else {
+                        high.parse().ok()?
+                    },
+                ),
+                None => {
+                    let v = item.parse().ok()?;
+                    (v, v)
+                }
+            },
+        };
+        if low > high {
+            return None;
+        }
+        for v in low..=high {
+            seen[v as usize] = true;
+        }
+    }
+    Some((0..=255u8).filter(|&v| seen[v as usize]).collect())
+}
+
+// Back in the notation `parse` reads, spans collapsed: `10.0.1-5.*`.
+impl fmt::Display for OctetPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, values) in self.octets.iter().enumerate() {
+            if i > 0 {
+                f.write_str(".")?;
+            }
+            if values.len() == 256 {
+                f.write_str("*")?;
+                continue;
+            }
+            let mut rest = &values[..];
+            let mut sep = "";
+            while let Some(&start) = rest.first() {
+                let run = rest.windows(2).take_while(|w| w[0] + 1 == w[1]).count();
+                match run {
+                    0 => write!(f, "{sep}{start}")?,
+                    _ => write!(f, "{sep}{start}-{}", rest[run])?,
+                }
+                rest = &rest[run + 1..];
+                sep = ",";
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hosts(s: &str) -> Vec<String> {
+        let pattern = OctetPattern::parse(s).unwrap();
+        pattern.hosts().map(|ip| ip.to_string()).collect()
+    }
+
+    #[test]
+    fn expands_lists_spans_and_wildcards() {
+        assert_eq!(
+            hosts("192.168.0,2.1-2"),
+            ["192.168.0.1", "192.168.0.2", "192.168.2.1", "192.168.2.2"]
+        );
+        let wild = OctetPattern::parse("10.*.0.1").unwrap();
+        assert_eq!(wild.host_count(), 256);
+        assert_eq!(wild.hosts().nth(255).unwrap().to_string(), "10.255.0.1");
+        assert_eq!(
+            hosts("10.0.0.-1,254-"),
+            ["10.0.0.0", "10.0.0.1", "10.0.0.254", "10.0.0.255"]
+        );
+    }
+
+    #[test]
+    fn matches_addresses_octet_by_octet() {
+        let pattern = OctetPattern::parse("10.0,2.1-5.*").unwrap();
+        for (ip, inside) in [("10.2.3.200", true), ("10.1.3.200", false), ("::1", false)] {
+            assert_eq!(pattern.contains(ip.parse().unwrap()), inside, "{ip}");
+        }
+    }
+
+    #[test]
+    fn counts_without_expanding() {
+        let all = OctetPattern::parse("*.*.*.1").unwrap();
+        assert_eq!(all.host_count(), 1 << 24);
+        assert_eq!(all.hosts().next().unwrap().to_string(), "0.0.0.1");
+        assert_eq!(
+            OctetPattern::parse("10.0.1-5.*").unwrap().host_count(),
+            5 * 256
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_octets() {
+        for bad in [
+            "10.0.0",
+            "10.0.0.0.0",
+            "10.0.5-1.*",
+            "10.0.256.1",
+            "10.0.x.1",
+            "10.0..1",
+        ] {
+            assert_eq!(OctetPattern::parse(bad), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn displays_in_the_notation_it_reads() {
+        for s in ["10.0.1-5.*", "192.168.0,2,4.1-254", "10.*.0.1"] {
+            assert_eq!(OctetPattern::parse(s).unwrap().to_string(), s);
+        }
+        assert_eq!(
+            OctetPattern::parse("10.3,1,2.0-0.*").unwrap().to_string(),
+            "10.1-3.0.*"
+        );
+    }
+}
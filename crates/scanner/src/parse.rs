@@ -1,22 +1,226 @@
+use crate::limits::Ports;
+use crate::octets::OctetPattern;
+use crate::prefixes::Query;
+use crate::types::{Edition, Endpoint, PortOverride};
 use ipnet::IpNet;
-use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
 
+/// What the address list holds: networks swept on every configured port (or
+/// their own, given as `java=`/`bedrock=` after the range), octet patterns
+/// swept like networks, endpoints probed only where they point, and
+/// `asn:`/`country:`/`provider:` queries still to be [resolved] into ranges.
+///
+/// [resolved]: crate::prefixes::resolve
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Targets {
+    pub ranges: Vec<IpNet>,
+    pub endpoints: Vec<Endpoint>,
+    pub overrides: Vec<PortOverride>,
+    pub patterns: Vec<OctetPattern>,
+    pub queries: Vec<Query>,
+}
+
+impl Targets {
+    fn len(&self) -> usize {
+        self.ranges.len() + self.endpoints.len() + self.patterns.len() + self.queries.len()
+    }
+}
+
+/// A set of addresses per family, kept as sorted, disjoint spans so overlapping
+/// or adjacent networks collapse; [`RangeSet::cidrs`] gives the fewest CIDRs
+/// covering exactly the set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RangeSet {
+    // Inclusive, sorted, neither overlapping nor touching
+    v4: Vec<(u128, u128)>,
+    v6: Vec<(u128, u128)>,
+}
+
+impl RangeSet {
+    pub fn new(nets: impl IntoIterator<Item = IpNet>) -> Self {
+        let (mut v4, mut v6) = (Vec::new(), Vec::new());
+        for net in nets {
+            match net {
+                IpNet::V4(n) => v4.push((
+                    u32::from(n.network()) as u128,
+                    u32::from(n.broadcast()) as u128,
+                )),
+                IpNet::V6(n) => v6.push((u128::from(n.network()), u128::from(n.broadcast()))),
+            }
+        }
+        Self {
+            v4: normalize(v4),
+            v6: normalize(v6),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.v4.is_empty() && self.v6.is_empty()
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let (spans, v) = match ip {
+            IpAddr::V4(a) => (&self.v4, u32::from(a) as u128),
+            IpAddr::V6(a) => (&self.v6, u128::from(a)),
+        };
+        let at = spans.partition_point(|&(start, _)| start <= v);
+        at > 0 && v <= spans[at - 1].1
+    }
+
+    /// How many addresses the set holds, saturating at `u128::MAX`.
+    pub fn len(&self) -> u128 {
+        self.v4
+            .iter()
+            .chain(&self.v6)
+            .map(|&(start, end)| (end - start).saturating_add(1))
+            .fold(0, u128::saturating_add)
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let merged = |a: &[(u128, u128)], b: &[(u128, u128)]| normalize([a, b].concat());
+        Self {
+            v4: merged(&self.v4, &other.v4),
+            v6: merged(&self.v6, &other.v6),
+        }
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self {
+            v4: intersect(&self.v4, &other.v4),
+            v6: intersect(&self.v6, &other.v6),
+        }
+    }
+
+    /// The addresses in `self` that aren't in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        Self {
+            v4: subtract(&self.v4, &other.v4),
+            v6: subtract(&self.v6, &other.v6),
+        }
+    }
+
+    /// IPv4 first, each family in address order.
+    pub fn cidrs(&self) -> Vec<IpNet> {
+        let v4 = self.v4.iter().flat_map(|&(start, end)| {
+            range_to_cidrs(start, end, 32, |v| IpAddr::V4(Ipv4Addr::from(v as u32)))
+        });
+        let v6 = self.v6.iter().flat_map(|&(start, end)| {
+            range_to_cidrs(start, end, 128, |v| IpAddr::V6(Ipv6Addr::from(v)))
+        });
+        v4.chain(v6).collect()
+    }
+}
+
+impl FromIterator<IpNet> for RangeSet {
+    fn from_iter<I: IntoIterator<Item = IpNet>>(iter: I) -> Self {
+        Self::new(iter)
+    }
+}
+
+fn normalize(mut spans: Vec<(u128, u128)>) -> Vec<(u128, u128)> {
+    spans.sort_unstable();
+    let mut out: Vec<(u128, u128)> = Vec::with_capacity(spans.len());
+    for (start, end) in spans {
+        match out.last_mut() {
+            Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+            _ => out.push((start, end)),
+        }
+    }
+    out
+}
+
+fn intersect(a: &[(u128, u128)], b: &[(u128, u128)]) -> Vec<(u128, u128)> {
+    let (mut i, mut j) = (0, 0);
+    let mut out = Vec::new();
+    while let (Some(&(a0, a1)), Some(&(b0, b1))) = (a.get(i), b.get(j)) {
+        let (start, end) = (a0.max(b0), a1.min(b1));
+        if start <= end {
+            out.push((start, end));
+        }
+        if a1 < b1 {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    out
+}
+
+fn subtract(a: &[(u128, u128)], b: &[(u128, u128)]) -> Vec<(u128, u128)> {
+    let mut out = Vec::new();
+    for &(start, end) in a {
+        let mut from = Some(start);
+        for &(cut0, cut1) in b.iter().filter(|&&(c0, c1)| c1 >= start && c0 <= end) {
+            if let Some(f) = from
+                && f < cut0
+            {
+                out.push((f, cut0 - 1));
+            }
+            from = cut1.checked_add(1);
+        }
+        if let Some(f) = from
+            && f <= end
+        {
+            out.push((f, end));
+        }
+    }
+    out
+}
+
+// Single ports and `low-high` spans, each port once in the order given.
 pub fn parse_ports(input: &str) -> Vec<u16> {
-    input
-        .split(',')
-        .filter_map(|s| s.trim().parse::<u16>().ok())
-        .collect()
+    let mut seen = HashSet::new();
+    let mut ports = Vec::new();
+    for item in input.split(',').map(str::trim) {
+        let span = match item.split_once('-') {
+            Some((low, high)) => match (low.trim().parse(), high.trim().parse()) {
+                (Ok(low), Ok(high)) => low..=high,
+                _ => continue,
+            },
+            None => match item.parse() {
+                Ok(port) => port..=port,
+                Err(_) => continue,
+            },
+        };
+        ports.extend(span.filter(|&p: &u16| seen.insert(p)));
+    }
+    ports
 }
 
 pub fn parse_ip_ranges(input: &str) -> Vec<IpNet> {
     parse_ip_ranges_reporting(input).0
 }
 
-// Also returns non-empty lines that produced no networks, so the UI can report
-// which input was dropped instead of silently ignoring it.
+// Networks only; lines naming other targets (an exact endpoint, an octet
+// pattern, an `asn:` query) are valid and skipped, not reported.
 pub fn parse_ip_ranges_reporting(input: &str) -> (Vec<IpNet>, Vec<String>) {
     let mut result = Vec::new();
     let mut rejected = Vec::new();
+    for line in input.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut targets = Targets::default();
+        parse_line(line, &mut targets);
+        if targets.len() == 0 {
+            rejected.push(line.to_string());
+        }
+        result.extend(targets.ranges);
+    }
+    (result, rejected)
+}
+
+pub fn parse_targets(input: &str) -> Targets {
+    parse_targets_reporting(input).0
+}
+
+// Also returns non-empty lines that produced no targets, so the UI can report
+// which input was dropped instead of silently ignoring it.
+pub fn parse_targets_reporting(input: &str) -> (Targets, Vec<String>) {
+    let mut result = Targets::default();
+    let mut rejected = Vec::new();
     for line in input.lines() {
         let line = line.trim();
         if line.is_empty() {
@@ -31,7 +235,74 @@ pub fn parse_ip_ranges_reporting(input: &str) -> (Vec<IpNet>, Vec<String>) {
     (result, rejected)
 }
 
-fn parse_line(line: &str, result: &mut Vec<IpNet>) {
+fn parse_line(line: &str, targets: &mut Targets) {
+    let Some((spec, java, bedrock)) = split_port_options(line) else {
+        return;
+    };
+    if java.is_none()
+        && bedrock.is_none()
+        && let Some(endpoint) = parse_endpoint(spec)
+    {
+        targets.endpoints.push(endpoint);
+        return;
+    }
+    if let Some(query) = Query::parse(spec) {
+        // Resolved ranges take the scan's ports, as patterns do.
+        if java.is_none() && bedrock.is_none() {
+            targets.queries.push(query);
+        }
+        return;
+    }
+    let before = targets.ranges.len();
+    parse_range(spec, &mut targets.ranges);
+    let options = java.is_some() || bedrock.is_some();
+    // Overrides are keyed by network, so patterns take the scan's ports.
+    if targets.ranges.len() == before
+        && !options
+        && let Some(pattern) = OctetPattern::parse(spec)
+    {
+        targets.patterns.push(pattern);
+        return;
+    }
+    if options {
+        for &net in &targets.ranges[before..] {
+            targets.overrides.push(PortOverride {
+                net,
+                java: java.clone(),
+                bedrock: bedrock.clone(),
+            });
+        }
+    }
+}
+
+// Words like `java=25565-25665` or `bedrock=` (none) after the range; `None`
+// when one doesn't parse.
+fn split_port_options(line: &str) -> Option<(&str, Option<Ports>, Option<Ports>)> {
+    let is_option = |w: &str| w.starts_with("java=") || w.starts_with("bedrock=");
+    let at = line
+        .match_indices(char::is_whitespace)
+        .map(|(i, c)| (i, i + c.len()))
+        .find(|&(_, next)| is_option(&line[next..]))
+        .map(|(i, _)| i);
+    let Some(at) = at else {
+        return Some((line, None, None));
+    };
+    let ports = |v: &str| {
+        let ports = Ports::from_input(v);
+        (v.is_empty() || !ports.is_empty()).then_some(ports)
+    };
+    let (mut java, mut bedrock) = (None, None);
+    for word in line[at..].split_whitespace() {
+        match word.split_once('=')? {
+            ("java", v) => java = Some(ports(v)?),
+            ("bedrock", v) => bedrock = Some(ports(v)?),
+            _ => return None,
+        }
+    }
+    Some((line[..at].trim(), java, bedrock))
+}
+
+fn parse_range(line: &str, result: &mut Vec<IpNet>) {
     if let Ok(net) = line.parse::<IpNet>() {
         result.push(net);
         return;
@@ -66,6 +337,24 @@ fn parse_line(line: &str, result: &mut Vec<IpNet>) {
     }
 }
 
+// `1.2.3.4:25570` or `[::1]:19133`, optionally suffixed `/java` or `/bedrock`.
+// A CIDR's prefix comes after a bare address, so the two never collide.
+fn parse_endpoint(line: &str) -> Option<Endpoint> {
+    let (addr, edition) = match line.rsplit_once('/') {
+        Some((addr, suffix)) => {
+            let edition = match suffix.trim().to_ascii_lowercase().as_str() {
+                "java" => Edition::Java,
+                "bedrock" => Edition::Bedrock,
+                _ => return None,
+            };
+            (addr, Some(edition))
+        }
+        None => (line, None),
+    };
+    let addr = addr.trim().parse::<SocketAddr>().ok()?;
+    Some(Endpoint { addr, edition })
+}
+
 fn range_to_cidrs(
     start: u128,
     end: u128,
@@ -119,6 +408,15 @@ mod tests {
         parse_ip_ranges(input)
     }
 
+    #[test]
+    fn range_reporting_skips_other_valid_targets() {
+        let (nets, rejected) = parse_ip_ranges_reporting(
+            "10.0.0.0/24\n1.2.3.4:25565\n10.0.1-5.*\nasn:24940\ngarbage\n",
+        );
+        assert_eq!(nets, vec!["10.0.0.0/24".parse::<IpNet>().unwrap()]);
+        assert_eq!(rejected, ["garbage"]);
+    }
+
     #[test]
     fn reporting_collects_rejected_lines() {
         let (nets, rejected) = parse_ip_ranges_reporting(
@@ -131,6 +429,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn target_reporting_collects_rejected_lines() {
+        let (targets, rejected) = parse_targets_reporting(
+            "10.0.0.0/24\n\ngarbage\n2001:db8::1-10.0.0.5\n10.0.0.9-10.0.0.1\n1.2.3.4:25565/pocket\n",
+        );
+        assert_eq!(targets.ranges, vec!["10.0.0.0/24".parse().unwrap()]);
+        assert!(targets.endpoints.is_empty());
+        assert_eq!(
+            rejected,
+            vec![
+                "garbage",
+                "2001:db8::1-10.0.0.5",
+                "10.0.0.9-10.0.0.1",
+                "1.2.3.4:25565/pocket"
+            ]
+        );
+    }
+
+    #[test]
+    fn endpoints_take_an_optional_edition() {
+        let targets = parse_targets(
+            "1.2.3.4:25570\n[::1]:19133/bedrock\n10.0.0.1:25565 / Java\n10.0.0.0/30\n",
+        );
+        let endpoint = |addr: &str, edition| Endpoint {
+            addr: addr.parse().unwrap(),
+            edition,
+        };
+        assert_eq!(
+            targets.endpoints,
+            vec![
+                endpoint("1.2.3.4:25570", None),
+                endpoint("[::1]:19133", Some(Edition::Bedrock)),
+                endpoint("10.0.0.1:25565", Some(Edition::Java)),
+            ]
+        );
+        assert_eq!(targets.ranges, vec!["10.0.0.0/30".parse().unwrap()]);
+        for e in &targets.endpoints {
+            assert_eq!(parse_targets(&e.to_string()).endpoints, vec![*e]);
+        }
+    }
+
     #[test]
     fn ipv4_range_splits_into_cidrs() {
         assert_eq!(
@@ -202,4 +541,126 @@ mod tests {
         assert_eq!(parse_ports("25565, 19132 , 70000, x"), vec![25565, 19132]);
         assert!(parse_ports("").is_empty());
     }
+
+    #[test]
+    fn parse_ports_expands_spans_once() {
+        assert_eq!(
+            parse_ports("25565-25567, 25566, 19132 - 19133, 10-5, 1-x"),
+            vec![25565, 25566, 25567, 19132, 19133]
+        );
+        assert_eq!(parse_ports("0-65535").len(), 65536);
+    }
+
+    #[test]
+    fn octet_patterns_are_reported_like_other_forms() {
+        let (targets, rejected) = parse_targets_reporting(
+            "10.0.1-5.*\n192.168.0,2,4.1-254\n10.*.0.1\n10.0.5-1.*\n10.*.0.1 java=25565\n2001:db8::*\n",
+        );
+        let patterns: Vec<String> = targets.patterns.iter().map(|p| p.to_string()).collect();
+        assert_eq!(patterns, ["10.0.1-5.*", "192.168.0,2,4.1-254", "10.*.0.1"]);
+        assert!(targets.ranges.is_empty());
+        assert_eq!(
+            rejected,
+            ["10.0.5-1.*", "10.*.0.1 java=25565", "2001:db8::*"]
+        );
+        // Plain addresses and dashed ranges keep their own forms.
+        let plain = parse_targets("10.0.0.1\n10.0.0.1-10.0.0.2\n");
+        assert!(plain.patterns.is_empty());
+        assert_eq!(plain.ranges.len(), 3);
+    }
+
+    #[test]
+    fn prefix_queries_are_kept_for_resolving() {
+        let (targets, rejected) = parse_targets_reporting(
+            "asn:24940\ncountry:de\nprovider:aws\nasn:AS13335\nprovider:aws java=25565\ncountry:XYZ\n",
+        );
+        let queries: Vec<String> = targets.queries.iter().map(|q| q.to_string()).collect();
+        assert_eq!(
+            queries,
+            ["asn:24940", "country:DE", "provider:aws", "asn:13335"]
+        );
+        assert!(targets.ranges.is_empty());
+        assert_eq!(rejected, ["provider:aws java=25565", "country:XYZ"]);
+    }
+
+    fn set(input: &str) -> RangeSet {
+        RangeSet::new(nets(input))
+    }
+
+    fn cidrs(set: &RangeSet) -> Vec<String> {
+        set.cidrs().iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn range_sets_merge_overlaps_and_neighbours() {
+        let merged =
+            set("10.0.0.0/16\n10.0.5.0/24\n10.1.0.0/16\n2001:db8::/33\n2001:db8:8000::/33\n");
+        assert_eq!(cidrs(&merged), ["10.0.0.0/15", "2001:db8::/32"]);
+        assert!(merged.contains("10.1.255.255".parse().unwrap()));
+        assert!(!merged.contains("10.2.0.0".parse().unwrap()));
+        assert_eq!(cidrs(&set("0.0.0.0/0\n10.0.0.0/8\n")), ["0.0.0.0/0"]);
+    }
+
+    #[test]
+    fn range_sets_intersect_and_subtract() {
+        let a = set("10.0.0.0/24\n10.0.2.0/24\n");
+        let b = set("10.0.0.128/25\n10.0.1.0/24\n10.0.2.0/23\n");
+        assert_eq!(cidrs(&a.intersection(&b)), ["10.0.0.128/25", "10.0.2.0/24"]);
+        assert_eq!(cidrs(&a.difference(&b)), ["10.0.0.0/25"]);
+        assert_eq!(cidrs(&a.union(&b)), ["10.0.0.0/22"]);
+        let hole = set("10.0.0.0/24").difference(&set("10.0.0.7/32"));
+        assert_eq!(
+            cidrs(&hole),
+            [
+                "10.0.0.0/30",
+                "10.0.0.4/31",
+                "10.0.0.6/32",
+                "10.0.0.8/29",
+                "10.0.0.16/28",
+                "10.0.0.32/27",
+                "10.0.0.64/26",
+                "10.0.0.128/25"
+            ]
+        );
+        assert!(set("::/0").difference(&set("::/0")).is_empty());
+    }
+
+    #[test]
+    fn ranges_carry_their_own_ports() {
+        let (targets, rejected) = parse_targets_reporting(
+            "10.0.0.0/22 java=25565-25665 bedrock=\n10.1.0.1 - 10.1.0.2 bedrock=19132,19133\n10.2.0.0/24\n1.2.3.4:25565 java=1\n10.3.0.0/24 java=x\n10.4.0.0/24 pe=1\n",
+        );
+        assert_eq!(
+            rejected,
+            [
+                "1.2.3.4:25565 java=1",
+                "10.3.0.0/24 java=x",
+                "10.4.0.0/24 pe=1"
+            ]
+        );
+        assert_eq!(targets.ranges.len(), 4);
+        let lines: Vec<String> = targets.overrides.iter().map(|o| o.to_string()).collect();
+        assert_eq!(
+            lines,
+            [
+                "10.0.0.0/22 java=25565-25665 bedrock=",
+                "10.1.0.1/32 bedrock=19132-19133",
+                "10.1.0.2/32 bedrock=19132-19133"
+            ]
+        );
+        assert_eq!(parse_targets(&lines[0]).overrides, targets.overrides[..1]);
+    }
+
+    #[test]
+    fn port_options_may_follow_any_whitespace() {
+        for line in [
+            "10.0.0.0/24\u{a0}java=1",
+            "10.0.0.0/24\u{3000}java=1",
+            "10.0.0.0/24\tjava=1",
+        ] {
+            let (targets, rejected) = parse_targets_reporting(line);
+            assert!(rejected.is_empty(), "{line:?}");
+            assert_eq!(targets.overrides[0].to_string(), "10.0.0.0/24 java=1");
+        }
+    }
 }
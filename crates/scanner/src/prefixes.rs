@@ -0,0 +1,418 @@
+//! Target lines naming whole networks by owner, resolved offline against data
+//! files kept in one directory:
+//!
+//! | Line             | Files read                                                |
+//! |------------------|-----------------------------------------------------------|
+//! | `asn:24940`      | every `*.mmdb` and `*.csv` prefix database                |
+//! | `country:DE`     | the same databases                                        |
+//! | `provider:aws`   | `aws.json`, `aws.csv` or `aws.txt`, named after the query |
+//!
+//! Databases are MaxMind-format files (GeoLite2 ASN/Country/City, DB-IP lite,
+//! ipinfo) or CSVs with a `network` column, or `start_ip` and `end_ip`, next to
+//! an ASN (`autonomous_system_number`, `asn`) or country (`country_iso_code`,
+//! `country_code`, `country`) column. Provider files are AWS, GCP or Azure
+//! `ip-ranges.json` downloads, or anything with one prefix per line, such as
+//! a geofeed.
+
+use crate::mmdb;
+use crate::parse::{RangeSet, parse_ip_ranges};
+use ipnet::IpNet;
+use serde_json::Value;
+use std::fmt;
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Query {
+    Asn(u32),
+    // ISO 3166 alpha-2, uppercase
+    Country(String),
+    // Lowercase file stem
+    Provider(String),
+}
+
+impl Query {
+    /// `asn:24940` (or `asn:AS24940`), `country:DE`, `provider:aws`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (kind, value) = s.trim().split_once(':')?;
+        let value = value.trim();
+        match kind.trim().to_ascii_lowercase().as_str() {
+            "asn" => parse_asn(value).map(Self::Asn),
+            "country" if value.len() == 2 && value.chars().all(|c| c.is_ascii_alphabetic()) => {
+                Some(Self::Country(value.to_ascii_uppercase()))
+            }
+            "provider"
+                if !value.is_empty()
+                    && value
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+            {
+                Some(Self::Provider(value.to_ascii_lowercase()))
+            }
+            _ => None,
+        }
+    }
+
+    fn matches_record(&self, record: &Value) -> bool {
+        match self {
+            Self::Asn(asn) => record_asn(record) == Some(*asn),
+            Self::Country(code) => record_country(record).as_ref() == Some(code),
+            Self::Provider(_) => false,
+        }
+    }
+
+    fn matches_text(&self, s: &str) -> bool {
+        match self {
+            Self::Asn(asn) => parse_asn(s) == Some(*asn),
+            Self::Country(code) => s.trim().eq_ignore_ascii_case(code),
+            Self::Provider(_) => false,
+        }
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Asn(asn) => write!(f, "asn:{asn}"),
+            Self::Country(code) => write!(f, "country:{code}"),
+            Self::Provider(name) => write!(f, "provider:{name}"),
+        }
+    }
+}
+
+/// The networks each query stands for per the files in `dir`, merged into
+/// the fewest CIDRs, in the order given. Each file is read once for the whole
+/// batch. IPv6 prefixes are left out, as no sweep gets through one. Unreadable
+/// or unrecognised files are skipped; only a missing directory is an error.
+pub fn resolve(queries: &[Query], dir: &Path) -> io::Result<Vec<Vec<IpNet>>> {
+    let mut found = vec![Vec::new(); queries.len()];
+    let by_database = queries.iter().any(|q| !matches!(q, Query::Provider(_)));
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        let provides = |query: &Query| match query {
+            Query::Provider(name) => {
+                stem.eq_ignore_ascii_case(name) && ["json", "csv", "txt"].contains(&ext.as_str())
+            }
+            _ => false,
+        };
+        let database = by_database && ["mmdb", "csv"].contains(&ext.as_str());
+        if !database && !queries.iter().any(provides) {
+            continue;
+        }
+        let bytes = match std::fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(error) => {
+                tracing::warn!(path = %path.display(), %error, "prefix file skipped");
+                continue;
+            }
+        };
+        let reader = (ext == "mmdb")
+            .then(|| mmdb::Reader::new(bytes.as_slice()))
+            .flatten();
+        let text = (ext != "mmdb").then(|| String::from_utf8_lossy(&bytes));
+        for (query, nets) in queries.iter().zip(&mut found) {
+            match (query, &reader, &text) {
+                (Query::Provider(_), _, Some(text)) if provides(query) => {
+                    nets.extend(provider_prefixes(text))
+                }
+                (Query::Provider(_), _, _) => {}
+                (_, Some(reader), _) => nets.extend(reader.networks(|r| query.matches_record(r))),
+                (_, None, Some(text)) if database => nets.extend(csv_networks(query, text)),
+                _ => {}
+            }
+        }
+    }
+    Ok(found
+        .into_iter()
+        .map(|nets| RangeSet::new(nets.into_iter().filter(|n| matches!(n, IpNet::V4(_)))).cidrs())
+        .collect())
+}
+
+/// Networks in a MaxMind-format database whose record matches `query`.
+pub fn mmdb_networks(query: &Query, db: &[u8]) -> Vec<IpNet> {
+    match mmdb::Reader::new(db) {
+        Some(reader) => reader.networks(|record| query.matches_record(record)),
+        None => Vec::new(),
+    }
+}
+
+/// Networks in a CSV prefix database whose row matches `query`; empty when the
+/// header lacks a network or a matching column.
+pub fn csv_networks(query: &Query, text: &str) -> Vec<IpNet> {
+    let mut lines = text.lines();
+    let Some(header) = lines.next() else {
+        return Vec::new();
+    };
+    let header: Vec<String> = csv_fields(header)
+        .into_iter()
+        .map(|h| h.to_ascii_lowercase())
+        .collect();
+    let column = |names: &[&str]| {
+        names
+            .iter()
+            .find_map(|name| header.iter().position(|h| h == name))
+    };
+    let key = match query {
+        Query::Asn(_) => column(ASN_COLUMNS),
+        Query::Country(_) => column(COUNTRY_COLUMNS),
+        Query::Provider(_) => None,
+    };
+    let network = column(&["network"]);
+    let span = column(&["start_ip"]).zip(column(&["end_ip"]));
+    let Some(key) = key.filter(|_| network.is_some() || span.is_some()) else {
+        return Vec::new();
+    };
+    let mut nets = Vec::new();
+    for line in lines {
+        let fields = csv_fields(line);
+        if !fields.get(key).is_some_and(|v| query.matches_text(v)) {
+            continue;
+        }
+        let range = match (network, span) {
+            (Some(n), _) => fields.get(n).cloned(),
+            (None, Some((start, end))) => fields
+                .get(start)
+                .zip(fields.get(end))
+                .map(|(start, end)| format!("{start}-{end}")),
+            (None, None) => None,
+        };
+        if let Some(range) = range {
+            nets.extend(parse_ip_ranges(&range));
+        }
+    }
+    nets
+}
+
+/// Prefixes in a cloud provider's `ip-ranges.json` (AWS, GCP or Azure), or
+/// the first field of each line of a plain list or geofeed.
+pub fn provider_prefixes(text: &str) -> Vec<IpNet> {
+    let Ok(json) = serde_json::from_str::<Value>(text) else {
+        return text
+            .lines()
+            .map(|line| line.split(['#', ',']).next().unwrap_or_default().trim())
+            .filter_map(|prefix| prefix.parse().ok())
+            .collect();
+    };
+    let mut prefixes = Vec::new();
+    // AWS: prefixes[].ip_prefix, ipv6_prefixes[].ipv6_prefix
+    // GCP: prefixes[].ipv4Prefix / ipv6Prefix
+    for entry in ["prefixes", "ipv6_prefixes"]
+        .iter()
+        .filter_map(|key| json[key].as_array())
+        .flatten()
+    {
+        for key in ["ip_prefix", "ipv6_prefix", "ipv4Prefix", "ipv6Prefix"] {
+            prefixes.extend(entry[key].as_str());
+        }
+    }
+    // Azure: values[].properties.addressPrefixes[]
+    for value in json["values"].as_array().into_iter().flatten() {
+        let list = value["properties"]["addressPrefixes"].as_array();
+        prefixes.extend(list.into_iter().flatten().filter_map(Value::as_str));
+    }
+    prefixes.iter().filter_map(|p| p.parse().ok()).collect()
+}
+
+// CSV columns holding an ASN or a country code, in order of preference.
+pub(crate) const ASN_COLUMNS: &[&str] = &["autonomous_system_number", "asn"];
+pub(crate) const COUNTRY_COLUMNS: &[&str] = &["country_iso_code", "country_code", "country"];
+
+// `24940` or `AS24940`
+pub(crate) fn parse_asn(s: &str) -> Option<u32> {
+    let s = s.trim();
+    let digits = s
+        .strip_prefix("AS")
+        .or_else(|| s.strip_prefix("as"))
+        .unwrap_or(s);
+    digits.parse().ok()
+}
+
+// A database record's ASN: a number, or a string as in ipinfo's `AS13335`.
+pub(crate) fn record_asn(record: &Value) -> Option<u32> {
+    let field = |v: &Value| match v {
+        Value::Number(n) => n.as_u64().and_then(|n| u32::try_from(n).ok()),
+        Value::String(s) => parse_asn(s),
+        _ => None,
+    };
+    field(&record["autonomous_system_number"]).or_else(|| field(&record["asn"]))
+}
+
+// A database record's country code, uppercase.
+pub(crate) fn record_country(record: &Value) -> Option<String> {
+    [
+        &record["country"]["iso_code"],
+        &record["country_code"],
+        &record["country"],
+    ]
+    .into_iter()
+    .find_map(|v| v.as_str().map(str::trim).filter(|s| !s.is_empty()))
+    .map(str::to_ascii_uppercase)
+}
+
+// Splits a CSV line, honouring quotes and doubled quotes inside them.
+pub(crate) fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.trim_end_matches('\r').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn strings(nets: Vec<IpNet>) -> Vec<String> {
+        nets.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_query_lines() {
+        assert_eq!(Query::parse("asn:24940"), Some(Query::Asn(24940)));
+        assert_eq!(Query::parse("ASN:AS24940"), Some(Query::Asn(24940)));
+        assert_eq!(
+            Query::parse("country:de"),
+            Some(Query::Country("DE".into()))
+        );
+        assert_eq!(
+            Query::parse("provider:DigitalOcean"),
+            Some(Query::Provider("digitalocean".into()))
+        );
+        for bad in [
+            "asn:",
+            "asn:x1",
+            "country:DEU",
+            "provider:../aws",
+            "city:Berlin",
+        ] {
+            assert_eq!(Query::parse(bad), None, "{bad}");
+        }
+        assert_eq!(Query::parse("asn:AS3").unwrap().to_string(), "asn:3");
+    }
+
+    #[test]
+    fn reads_mmdb_asn_and_country_records() {
+        let db = mmdb::tests::build(
+            false,
+            &[
+                ("5.9.0.0/16", json!({"autonomous_system_number": 24940})),
+                ("1.1.1.0/24", json!({"autonomous_system_number": 13335})),
+                (
+                    "88.198.0.0/16",
+                    json!({"asn": "AS24940", "country_code": "DE"}),
+                ),
+                ("81.2.69.0/24", json!({"country": {"iso_code": "GB"}})),
+            ],
+        );
+        assert_eq!(
+            strings(mmdb_networks(&Query::Asn(24940), &db)),
+            ["5.9.0.0/16", "88.198.0.0/16"]
+        );
+        let gb = mmdb_networks(&Query::Country("GB".into()), &db);
+        assert_eq!(strings(gb), ["81.2.69.0/24"]);
+        assert!(mmdb_networks(&Query::Asn(1), b"garbage").is_empty());
+    }
+
+    #[test]
+    fn reads_csv_databases() {
+        let geolite = "network,autonomous_system_number,autonomous_system_organization\n\
+                       5.9.0.0/16,24940,\"Hetzner Online GmbH\"\n\
+                       1.1.1.0/24,13335,\"Cloudflare, Inc.\"\n";
+        assert_eq!(
+            strings(csv_networks(&Query::Asn(24940), geolite)),
+            ["5.9.0.0/16"]
+        );
+        let ipinfo = "start_ip,end_ip,country,country_name,asn\n\
+                      10.0.0.0,10.0.1.255,DE,Germany,AS24940\n\
+                      10.0.2.0,10.0.2.255,FR,France,AS16276\n";
+        assert_eq!(
+            strings(csv_networks(&Query::Country("DE".into()), ipinfo)),
+            ["10.0.0.0/23"]
+        );
+        assert_eq!(
+            strings(csv_networks(&Query::Asn(16276), ipinfo)),
+            ["10.0.2.0/24"]
+        );
+        // No ASN column to match against
+        assert!(csv_networks(&Query::Asn(24940), "network,geoname_id\n").is_empty());
+    }
+
+    #[test]
+    fn reads_provider_range_files() {
+        let aws = r#"{"prefixes": [{"ip_prefix": "3.5.140.0/22", "service": "S3"}],
+                      "ipv6_prefixes": [{"ipv6_prefix": "2600:1f14::/35"}]}"#;
+        assert_eq!(
+            strings(provider_prefixes(aws)),
+            ["3.5.140.0/22", "2600:1f14::/35"]
+        );
+        let gcp =
+            r#"{"prefixes": [{"ipv4Prefix": "34.1.208.0/20"}, {"ipv6Prefix": "2600:1900::/35"}]}"#;
+        assert_eq!(
+            strings(provider_prefixes(gcp)),
+            ["34.1.208.0/20", "2600:1900::/35"]
+        );
+        let azure = r#"{"values": [{"name": "AzureCloud",
+                        "properties": {"addressPrefixes": ["13.64.0.0/16", "2603:1000::/40"]}}]}"#;
+        assert_eq!(
+            strings(provider_prefixes(azure)),
+            ["13.64.0.0/16", "2603:1000::/40"]
+        );
+        let geofeed = "# DigitalOcean geofeed\n5.101.96.0/21,NL,NL-NH,Amsterdam,\n\n";
+        assert_eq!(strings(provider_prefixes(geofeed)), ["5.101.96.0/21"]);
+    }
+
+    #[test]
+    fn resolves_against_a_directory() {
+        let dir = std::env::temp_dir().join(format!("mc-scan-prefixes-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let asn = mmdb::tests::build(
+            false,
+            &[
+                ("5.9.0.0/17", json!({"autonomous_system_number": 24940})),
+                ("5.9.128.0/17", json!({"autonomous_system_number": 24940})),
+            ],
+        );
+        std::fs::write(dir.join("GeoLite2-ASN.mmdb"), asn).unwrap();
+        std::fs::write(dir.join("extra.csv"), "network,asn\n78.46.0.0/15,24940\n").unwrap();
+        std::fs::write(
+            dir.join("aws.json"),
+            r#"{"prefixes": [{"ip_prefix": "3.5.140.0/22"}],
+                "ipv6_prefixes": [{"ipv6_prefix": "2600:1f14::/35"}]}"#,
+        )
+        .unwrap();
+        let queries: Vec<Query> = ["asn:24940", "provider:aws", "provider:gcp"]
+            .iter()
+            .map(|q| Query::parse(q).unwrap())
+            .collect();
+        let found: Vec<Vec<String>> = resolve(&queries, &dir)
+            .unwrap()
+            .into_iter()
+            .map(strings)
+            .collect();
+        assert_eq!(found[0], ["5.9.0.0/16", "78.46.0.0/15"]);
+        assert_eq!(found[1], ["3.5.140.0/22"]);
+        assert!(found[2].is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(resolve(&queries, &dir).is_err());
+    }
+}
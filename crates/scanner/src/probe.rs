@@ -0,0 +1,586 @@
+//! Pluggable probes. Each [`Prober`] speaks one protocol; a [`Registry`] decides
+//! which of them run against a target and in what order.
+
+use crate::transport::Transport;
+use crate::types::{Edition, ProbeOptions, ServerInfo};
+use crate::{Miss, ProbeError, bedrock, bedrock_login, java, login, query, rcon};
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tracing::{debug, trace};
+
+pub type ProbeFuture<'a> = BoxFuture<'a, Result<(), Miss>>;
+
+#[derive(Clone, Copy)]
+pub struct Target<'a> {
+    pub addr: SocketAddr,
+    pub edition: Edition,
+    pub options: &'a ProbeOptions,
+    pub net: &'a dyn Transport,
+}
+
+impl Target<'_> {
+    /// What to put in a Java handshake's server address field.
+    pub fn handshake_host(&self) -> String {
+        match &self.options.handshake_host {
+            Some(host) => host.clone(),
+            None => self.addr.ip().to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    // Results this prober applies to
+    pub edition: Edition,
+    // Finds a server on its own; otherwise it adds to what a discoverer found
+    pub discovers: bool,
+    // Speaks UDP rather than TCP
+    pub udp: bool,
+    // Also connects to ports other than the target's (RCON, advertised ports)
+    pub side_ports: bool,
+}
+
+pub trait Prober: Send + Sync {
+    /// Short stable name, used in logs.
+    fn name(&self) -> &'static str;
+
+    fn capabilities(&self) -> Capabilities;
+
+    /// Discoverers get `ServerInfo::base` for the target and fill it in;
+    /// enrichers get what a discoverer found. On a miss `info` is discarded
+    /// for discoverers and kept as is for enrichers.
+    fn probe<'a>(&'a self, target: Target<'a>, info: &'a mut ServerInfo) -> ProbeFuture<'a>;
+}
+
+/// Ordered set of probers. For a target, discoverers of its edition are tried
+/// in registration order until one answers, then every matching enricher runs.
+#[derive(Clone, Default)]
+pub struct Registry {
+    probers: Vec<Arc<dyn Prober>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Status pings for both editions plus the enrichers `options` turns on.
+    pub fn standard(options: &ProbeOptions) -> Self {
+        Self::standard_with(options, BedrockStatus::default())
+    }
+
+    pub(crate) fn standard_with(options: &ProbeOptions, bedrock: BedrockStatus) -> Self {
+        let mut r = Self::new();
+        r.register(JavaStatus).register(bedrock);
+        if options.query_enabled {
+            r.register(Query);
+        }
+        if options.online_mode_check {
+            r.register(JavaLogin);
+        }
+        if options.rcon_check {
+            r.register(Rcon);
+        }
+        if options.raknet_check {
+            r.register(RakNet);
+        }
+        if options.online_mode_check {
+            r.register(BedrockLogin);
+        }
+        r
+    }
+
+    pub fn register(&mut self, prober: impl Prober + 'static) -> &mut Self {
+        self.probers.push(Arc::new(prober));
+        self
+    }
+
+    pub fn probers(&self) -> impl Iterator<Item = &dyn Prober> {
+        self.probers.iter().map(|p| p.as_ref())
+    }
+
+    /// Fails with the most telling miss of the discoverers: a reply that didn't
+    /// parse says more than a timeout.
+    pub async fn probe(&self, target: Target<'_>) -> Result<ServerInfo, ProbeError> {
+        let addr = target.addr;
+        let mut found = None;
+        let mut error = ProbeError::NoProber(target.edition);
+        for p in self.matching(target.edition, true) {
+            let mut info = ServerInfo::base(addr, target.edition);
+            match p.probe(target, &mut info).await {
+                Ok(()) => {
+                    debug!(%addr, prober = p.name(), version = %info.version, online = info.online, "found");
+                    found = Some(info);
+                    break;
+                }
+                Err(miss @ Miss::Unparsed(stage)) => {
+                    debug!(%addr, prober = p.name(), stage, "response did not parse");
+                    error = miss.into();
+                }
+                // The common case on a wide scan, kept at trace.
+                Err(miss @ Miss::Unreachable(stage)) => {
+                    trace!(%addr, prober = p.name(), stage, "unreachable");
+                    if !matches!(error, ProbeError::Unparsed(_)) {
+                        error = miss.into();
+                    }
+                }
+            }
+        }
+        let mut info = found.ok_or(error)?;
+        // The parsers always capture it; it's only worth its memory on request.
+        if !target.options.keep_raw {
+            info.raw_status = None;
+        }
+        for p in self.matching(target.edition, false) {
+            if let Err(miss) = p.probe(target, &mut info).await {
+                trace!(%addr, prober = p.name(), ?miss, "enrichment returned nothing");
+            }
+        }
+        Ok(info)
+    }
+
+    fn matching(&self, edition: Edition, discovers: bool) -> impl Iterator<Item = &dyn Prober> {
+        self.probers().filter(move |p| {
+            let caps = p.capabilities();
+            caps.edition == edition && caps.discovers == discovers
+        })
+    }
+}
+
+impl std::fmt::Debug for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.probers().map(|p| p.name()))
+            .finish()
+    }
+}
+
+/// Java server list ping (status request).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JavaStatus;
+
+impl Prober for JavaStatus {
+    fn name(&self) -> &'static str {
+        "java_status"
+    }
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            edition: Edition::Java,
+            discovers: true,
+            udp: false,
+            side_ports: false,
+        }
+    }
+    fn probe<'a>(&'a self, target: Target<'a>, info: &'a mut ServerInfo) -> ProbeFuture<'a> {
+        async move {
+            *info = java::probe_inner(
+                target.net,
+                target.addr,
+                &target.handshake_host(),
+                target.options.timeouts,
+            )
+            .await?;
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+/// Bedrock unconnected ping. With `follow` set it also tries the port the pong
+/// advertises, unless that port is in `skip`, and records it as an endpoint.
+#[derive(Debug, Clone, Default)]
+pub struct BedrockStatus {
+    pub follow: bool,
+    pub skip: Vec<u16>,
+}
+
+impl Prober for BedrockStatus {
+    fn name(&self) -> &'static str {
+        "bedrock_status"
+    }
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            edition: Edition::Bedrock,
+            discovers: true,
+            udp: true,
+            side_ports: self.follow,
+        }
+    }
+    fn probe<'a>(&'a self, target: Target<'a>, info: &'a mut ServerInfo) -> ProbeFuture<'a> {
+        async move {
+            *info =
+                bedrock::probe_inner(target.net, target.addr, target.options.timeouts.read).await?;
+            if self.follow {
+                bedrock::follow_advertised_port(
+                    target.net,
+                    info,
+                    &self.skip,
+                    target.options.timeouts.read,
+                )
+                .await;
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+/// GameSpy4 full stat (`enable-query=true`): world, plugins, full player list.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Query;
+
+impl Prober for Query {
+    fn name(&self) -> &'static str {
+        "query"
+    }
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            edition: Edition::Java,
+            discovers: false,
+            udp: true,
+            side_ports: false,
+        }
+    }
+    fn probe<'a>(&'a self, target: Target<'a>, info: &'a mut ServerInfo) -> ProbeFuture<'a> {
+        async move {
+            let q = query::probe(target.net, target.addr, target.options.timeouts.read)
+                .await
+                .ok_or(Miss::Unreachable("query"))?;
+            info.world = q.world;
+            info.plugins = q.plugins;
+            // The ids belong to the SLP sample we're replacing, so drop them too.
+            if !q.players.is_empty() {
+                info.samples = q.players;
+                info.sample_ids.clear();
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+/// Java login attempt that tells online-mode and whitelist apart.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JavaLogin;
+
+impl Prober for JavaLogin {
+    fn name(&self) -> &'static str {
+        "java_login"
+    }
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            edition: Edition::Java,
+            discovers: false,
+            udp: false,
+            side_ports: false,
+        }
+    }
+    fn probe<'a>(&'a self, target: Target<'a>, info: &'a mut ServerInfo) -> ProbeFuture<'a> {
+        async move {
+            let outcome = login::probe(
+                target.net,
+                target.addr,
+                &target.handshake_host(),
+                info.protocol,
+                target.options.timeouts,
+                target.options.identity.as_ref(),
+            )
+            .await;
+            info.online_mode = outcome.online_mode;
+            info.whitelist = outcome.whitelist;
+            trace!(addr = %target.addr, online_mode = ?info.online_mode, whitelist = ?info.whitelist, "login probe");
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+/// RCON auth probe on the default RCON port of the target's host.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Rcon;
+
+impl Prober for Rcon {
+    fn name(&self) -> &'static str {
+        "rcon"
+    }
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            edition: Edition::Java,
+            discovers: false,
+            udp: false,
+            side_ports: true,
+        }
+    }
+    fn probe<'a>(&'a self, target: Target<'a>, info: &'a mut ServerInfo) -> ProbeFuture<'a> {
+        async move {
+            info.rcon_exposed =
+                rcon::probe(target.net, target.addr.ip(), target.options.timeouts).await;
+            info.rcon_exposed
+                .map(|_| ())
+                .ok_or(Miss::Unreachable("rcon"))
+        }
+        .boxed()
+    }
+}
+
+/// RakNet OpenConnectionRequest1, answered only by a real RakNet stack.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RakNet;
+
+impl Prober for RakNet {
+    fn name(&self) -> &'static str {
+        "raknet"
+    }
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            edition: Edition::Bedrock,
+            discovers: false,
+            udp: true,
+            side_ports: false,
+        }
+    }
+    fn probe<'a>(&'a self, target: Target<'a>, info: &'a mut ServerInfo) -> ProbeFuture<'a> {
+        async move {
+            let reply =
+                bedrock::open_connection(target.net, target.addr, target.options.timeouts.read)
+                    .await
+                    .ok_or(Miss::Unreachable("raknet"))?;
+            // Another GUID than the pong's means another server answered on this port.
+            if let Some(pong) = info.server_guid.as_deref().and_then(parse_guid)
+                && pong != reply.guid
+            {
+                return Err(Miss::Unparsed("raknet_guid"));
+            }
+            info.raknet_protocol = Some(reply.protocol);
+            info.raknet_mtu = reply.mtu;
+            info.raknet_security = reply.security;
+            if info.server_guid.is_none() {
+                info.server_guid = Some(reply.guid.to_string());
+            }
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+/// Pong GUIDs are printed signed by some servers and unsigned by others.
+fn parse_guid(s: &str) -> Option<u64> {
+    s.parse::<u64>()
+        .ok()
+        .or_else(|| s.parse::<i64>().ok().map(|g| g as u64))
+}
+
+/// Bedrock offline login that tells online-mode and allowlist apart.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BedrockLogin;
+
+impl Prober for BedrockLogin {
+    fn name(&self) -> &'static str {
+        "bedrock_login"
+    }
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            edition: Edition::Bedrock,
+            discovers: false,
+            udp: true,
+            side_ports: false,
+        }
+    }
+    fn probe<'a>(&'a self, target: Target<'a>, info: &'a mut ServerInfo) -> ProbeFuture<'a> {
+        async move {
+            let outcome = bedrock_login::probe(
+                target.net,
+                target.addr,
+                info.protocol,
+                &info.version,
+                target.options.timeouts.read,
+            )
+            .await;
+            info.online_mode = outcome.online_mode;
+            info.whitelist = outcome.whitelist;
+            trace!(addr = %target.addr, online_mode = ?info.online_mode, whitelist = ?info.whitelist, "bedrock login probe");
+            Ok(())
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::TokioNet;
+
+    // Answers with a fixed MOTD, or misses when `motd` is None.
+    struct Fake {
+        name: &'static str,
+        edition: Edition,
+        discovers: bool,
+        motd: Option<&'static str>,
+    }
+
+    impl Prober for Fake {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+        fn capabilities(&self) -> Capabilities {
+            Capabilities {
+                edition: self.edition,
+                discovers: self.discovers,
+                udp: false,
+                side_ports: false,
+            }
+        }
+        fn probe<'a>(&'a self, _: Target<'a>, info: &'a mut ServerInfo) -> ProbeFuture<'a> {
+            async move {
+                let motd = self.motd.ok_or(Miss::Unreachable("fake"))?;
+                info.motd.append(motd.into());
+                Ok(())
+            }
+            .boxed()
+        }
+    }
+
+    fn fake(name: &'static str, discovers: bool, motd: Option<&'static str>) -> Fake {
+        Fake {
+            name,
+            edition: Edition::Java,
+            discovers,
+            motd,
+        }
+    }
+
+    static OPTIONS: std::sync::LazyLock<ProbeOptions> =
+        std::sync::LazyLock::new(ProbeOptions::default);
+
+    fn target(edition: Edition) -> Target<'static> {
+        Target {
+            addr: "127.0.0.1:25565".parse().unwrap(),
+            edition,
+            options: &OPTIONS,
+            net: &TokioNet,
+        }
+    }
+
+    #[tokio::test]
+    async fn first_answering_discoverer_wins_then_enrichers_run() {
+        let mut r = Registry::new();
+        r.register(fake("silent", true, None))
+            .register(fake("legacy", true, Some("legacy")))
+            .register(fake("modern", true, Some("modern")))
+            .register(fake("miss", false, None))
+            .register(fake("extra", false, Some("+extra")));
+        let info = r.probe(target(Edition::Java)).await.unwrap();
+        assert_eq!(info.motd.plain(), "legacy+extra");
+    }
+
+    #[tokio::test]
+    async fn nothing_found_skips_enrichers() {
+        let mut r = Registry::new();
+        r.register(fake("silent", true, None))
+            .register(fake("extra", false, Some("+extra")));
+        assert_eq!(
+            r.probe(target(Edition::Java)).await.unwrap_err(),
+            ProbeError::Unreachable("fake")
+        );
+    }
+
+    #[tokio::test]
+    async fn probers_only_run_for_their_edition() {
+        let mut r = Registry::new();
+        r.register(fake("java", true, Some("java")));
+        assert_eq!(
+            r.probe(target(Edition::Bedrock)).await.unwrap_err(),
+            ProbeError::NoProber(Edition::Bedrock)
+        );
+    }
+
+    #[test]
+    fn handshake_host_defaults_to_the_target_ip() {
+        let mut t = target(Edition::Java);
+        assert_eq!(t.handshake_host(), "127.0.0.1");
+        let options = ProbeOptions::builder()
+            .handshake_host("play.example.com")
+            .build();
+        t.options = &options;
+        assert_eq!(t.handshake_host(), "play.example.com");
+    }
+
+    #[test]
+    fn standard_registry_follows_options() {
+        let names = |r: &Registry| r.probers().map(|p| p.name()).collect::<Vec<_>>();
+        assert_eq!(
+            names(&Registry::standard(&ProbeOptions::default())),
+            ["java_status", "bedrock_status"]
+        );
+        let all = Registry::standard(
+            &ProbeOptions::builder()
+                .query(true)
+                .online_mode_check(true)
+                .raknet_check(true)
+                .rcon_check(true)
+                .build(),
+        );
+        assert_eq!(
+            names(&all),
+            [
+                "java_status",
+                "bedrock_status",
+                "query",
+                "java_login",
+                "rcon",
+                "raknet",
+                "bedrock_login"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn raw_status_is_kept_only_on_request() {
+        let json = r#"{"version":{"name":"Velocity","protocol":767},"preventsChatReports":true}"#;
+        let server = testkit::JavaServer::new(json).spawn().await.unwrap();
+        for keep_raw in [false, true] {
+            let options = ProbeOptions::builder().keep_raw(keep_raw).build();
+            let target = Target {
+                addr: server.addr(),
+                edition: Edition::Java,
+                options: &options,
+                net: &TokioNet,
+            };
+            let info = Registry::standard(&options).probe(target).await.unwrap();
+            assert_eq!(info.raw_status.as_deref(), keep_raw.then_some(json));
+        }
+    }
+
+    #[tokio::test]
+    async fn raknet_reply_from_another_guid_is_not_recorded() {
+        let motd =
+            "MCPE;Fake;766;1.21.50;4;40;81985529216486895;Fake level;Creative;1;19132;19133;";
+        let options = ProbeOptions::builder().raknet_check(true).build();
+        for (guid, confirmed) in [(0x0123_4567_89AB_CDEF, true), (5, false)] {
+            let server = testkit::BedrockServer::new(motd)
+                .guid(guid)
+                .raknet(true)
+                .spawn()
+                .await
+                .unwrap();
+            let target = Target {
+                addr: server.addr(),
+                edition: Edition::Bedrock,
+                options: &options,
+                net: &TokioNet,
+            };
+            let info = Registry::standard(&options).probe(target).await.unwrap();
+            assert_eq!(info.raknet_protocol.is_some(), confirmed);
+            assert_eq!(info.server_guid.as_deref(), Some("81985529216486895"));
+        }
+    }
+
+    #[test]
+    fn pong_guids_parse_signed_or_unsigned() {
+        assert_eq!(parse_guid("81985529216486895"), Some(0x0123_4567_89AB_CDEF));
+        assert_eq!(parse_guid("-1"), Some(u64::MAX));
+        assert_eq!(parse_guid("level"), None);
+    }
+}
@@ -1,6 +1,6 @@
+use crate::transport::Transport;
 use std::net::SocketAddr;
 use std::time::Duration;
-use tokio::net::UdpSocket;
 use tokio::time::timeout;
 
 #[derive(Debug, Default)]
@@ -13,10 +13,8 @@ pub struct QueryData {
 const KV_PREFIX: &[u8] = b"splitnum\x00\x80\x00";
 const PLAYER_PREFIX: &[u8] = b"\x01player_\x00\x00";
 
-pub async fn probe(addr: SocketAddr, timeout_ms: u64) -> Option<QueryData> {
-    let dur = Duration::from_millis(timeout_ms);
-    let socket = UdpSocket::bind(super::local_bind_addr(&addr)).await.ok()?;
-    socket.connect(addr).await.ok()?;
+pub async fn probe(net: &dyn Transport, addr: SocketAddr, dur: Duration) -> Option<QueryData> {
+    let socket = net.datagram(addr).await.ok()?;
 
     let session_id: i32 = 1;
 
@@ -200,4 +198,24 @@ mod tests {
         data.extend_from_slice(b"9513307\0");
         assert_eq!(parse_challenge(&data), Some(9513307));
     }
+
+    #[tokio::test]
+    async fn probes_fake_server() {
+        use crate::transport::TokioNet;
+
+        let server = testkit::QueryServer::new()
+            .token(-123_456)
+            .map("survival")
+            .plugins("Paper on 1.21: LuckPerms 5.4; Vault 1.7")
+            .players(["Notch", "jeb_"])
+            .spawn()
+            .await
+            .unwrap();
+        let q = probe(&TokioNet, server.addr(), Duration::from_secs(2))
+            .await
+            .expect("should answer");
+        assert_eq!(q.world.as_deref(), Some("survival"));
+        assert_eq!(q.plugins, ["LuckPerms 5.4", "Vault 1.7"]);
+        assert_eq!(q.players, ["Notch", "jeb_"]);
+    }
 }
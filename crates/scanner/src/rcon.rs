@@ -0,0 +1,185 @@
+use super::Miss;
+use super::transport::Transport;
+use super::types::Timeouts;
+use std::net::{IpAddr, SocketAddr};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::time::timeout;
+use tracing::{debug, trace};
+
+const DEFAULT_PORT: u16 = 25575;
+
+const SERVERDATA_AUTH: i32 = 3;
+const SERVERDATA_AUTH_RESPONSE: i32 = 2;
+// Some implementations send an empty RESPONSE_VALUE ahead of the auth response.
+const SERVERDATA_RESPONSE_VALUE: i32 = 0;
+
+// id(4) + type(4) + two NUL terminators; vanilla caps payloads at 4096.
+const MIN_PACKET: i32 = 10;
+const MAX_PACKET: i32 = 4096 + MIN_PACKET;
+
+/// Sends one auth packet with a throwaway password to RCON on `host`.
+/// Some(true) when the reply is RCON framing, Some(false) when the port is
+/// closed or speaks something else, None when the attempt timed out.
+pub async fn probe(net: &dyn Transport, host: IpAddr, timeouts: Timeouts) -> Option<bool> {
+    let addr = SocketAddr::new(host, DEFAULT_PORT);
+    match probe_inner(net, addr, timeouts).await {
+        Ok(()) => {
+            debug!(%addr, "rcon exposed");
+            Some(true)
+        }
+        Err(Miss::Unparsed(stage)) => {
+            debug!(%addr, stage, "not rcon");
+            Some(false)
+        }
+        Err(Miss::Unreachable("connect")) => {
+            trace!(%addr, "rcon port closed");
+            Some(false)
+        }
+        Err(Miss::Unreachable(stage)) => {
+            trace!(%addr, stage, "rcon undetermined");
+            None
+        }
+    }
+}
+
+async fn probe_inner(
+    net: &dyn Transport,
+    addr: SocketAddr,
+    timeouts: Timeouts,
+) -> Result<(), Miss> {
+    let dur = timeouts.read;
+    let mut stream = match timeout(timeouts.connect, net.connect(addr)).await {
+        Ok(Ok(s)) => s,
+        Ok(Err(_)) => return Err(Miss::Unreachable("connect")),
+        Err(_) => return Err(Miss::Unreachable("connect_timeout")),
+    };
+
+    let request_id = (super::entropy_u64(0) & 0x7FFF_FFFF) as i32;
+    let password = format!("{:016x}", super::entropy_u64(1));
+    timeout(
+        dur,
+        stream.write_all(&build_packet(request_id, SERVERDATA_AUTH, &password)),
+    )
+    .await
+    .map_err(|_| Miss::Unreachable("send_timeout"))?
+    .map_err(|_| Miss::Unreachable("send"))?;
+
+    let mut len_buf = [0u8; 4];
+    match timeout(dur, stream.read_exact(&mut len_buf)).await {
+        Ok(Ok(_)) => {}
+        // A peer that hangs up on a foreign handshake is not RCON.
+        Ok(Err(_)) => return Err(Miss::Unparsed("closed")),
+        Err(_) => return Err(Miss::Unreachable("recv_timeout")),
+    }
+    let len = i32::from_le_bytes(len_buf);
+    if !(MIN_PACKET..=MAX_PACKET).contains(&len) {
+        return Err(Miss::Unparsed("length"));
+    }
+    let mut body = vec![0u8; len as usize];
+    timeout(dur, stream.read_exact(&mut body))
+        .await
+        .map_err(|_| Miss::Unreachable("recv_timeout"))?
+        .map_err(|_| Miss::Unparsed("body"))?;
+
+    is_auth_reply(&body, request_id)
+        .then_some(())
+        .ok_or(Miss::Unparsed("packet"))
+}
+
+// length(4) + id(4) + type(4) + payload + NUL + NUL, all little-endian.
+fn build_packet(id: i32, kind: i32, payload: &str) -> Vec<u8> {
+    let len = (payload.len() + MIN_PACKET as usize) as i32;
+    let mut buf = Vec::with_capacity(len as usize + 4);
+    buf.extend_from_slice(&len.to_le_bytes());
+    buf.extend_from_slice(&id.to_le_bytes());
+    buf.extend_from_slice(&kind.to_le_bytes());
+    buf.extend_from_slice(payload.as_bytes());
+    buf.extend_from_slice(&[0, 0]);
+    buf
+}
+
+// `body` excludes the length prefix. A wrong password answers with id -1.
+fn is_auth_reply(body: &[u8], request_id: i32) -> bool {
+    let Some(id) = body.get(0..4).and_then(|b| b.try_into().ok()) else {
+        return false;
+    };
+    let Some(kind) = body.get(4..8).and_then(|b| b.try_into().ok()) else {
+        return false;
+    };
+    let id = i32::from_le_bytes(id);
+    let kind = i32::from_le_bytes(kind);
+    (id == request_id || id == -1)
+        && matches!(kind, SERVERDATA_AUTH_RESPONSE | SERVERDATA_RESPONSE_VALUE)
+        && body.ends_with(&[0, 0])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::TokioNet;
+    use std::time::Duration;
+
+    fn body(id: i32, kind: i32) -> Vec<u8> {
+        build_packet(id, kind, "")[4..].to_vec()
+    }
+
+    #[test]
+    fn packet_layout_matches_the_protocol() {
+        let p = build_packet(7, SERVERDATA_AUTH, "pw");
+        assert_eq!(&p[0..4], &12i32.to_le_bytes());
+        assert_eq!(&p[4..8], &7i32.to_le_bytes());
+        assert_eq!(&p[8..12], &3i32.to_le_bytes());
+        assert_eq!(&p[12..], b"pw\0\0");
+    }
+
+    #[test]
+    fn rejected_and_accepted_auth_both_count_as_rcon() {
+        assert!(is_auth_reply(&body(-1, SERVERDATA_AUTH_RESPONSE), 42));
+        assert!(is_auth_reply(&body(42, SERVERDATA_AUTH_RESPONSE), 42));
+        assert!(is_auth_reply(&body(42, SERVERDATA_RESPONSE_VALUE), 42));
+    }
+
+    #[test]
+    fn foreign_replies_are_not_rcon() {
+        assert!(!is_auth_reply(&body(5, SERVERDATA_AUTH_RESPONSE), 42));
+        assert!(!is_auth_reply(&body(42, 9), 42));
+        assert!(!is_auth_reply(b"HTTP/1.1 400", 42));
+    }
+
+    #[tokio::test]
+    async fn fake_rcon_server_is_detected() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut sock, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 64];
+            let _ = sock.read(&mut buf).await.unwrap();
+            sock.write_all(&build_packet(-1, SERVERDATA_AUTH_RESPONSE, ""))
+                .await
+                .unwrap();
+        });
+        assert!(
+            probe_inner(&TokioNet, addr, Timeouts::uniform(Duration::from_secs(1)))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn http_like_reply_is_not_rcon() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut sock, _) = listener.accept().await.unwrap();
+            let mut buf = [0u8; 64];
+            let _ = sock.read(&mut buf).await.unwrap();
+            sock.write_all(b"HTTP/1.1 400 Bad Request\r\n\r\n")
+                .await
+                .unwrap();
+        });
+        assert!(matches!(
+            probe_inner(&TokioNet, addr, Timeouts::uniform(Duration::from_secs(1))).await,
+            Err(Miss::Unparsed(_))
+        ));
+    }
+}
@@ -0,0 +1,245 @@
+//! The client's multiplayer list, `.minecraft/servers.dat`: a root compound
+//! holding a `servers` list of `{name, ip, icon}` compounds. Results become
+//! entries here, and an existing list can be read back as scan targets.
+
+use crate::nbt::{self, Tag};
+use crate::types::{Edition, ServerInfo};
+use std::collections::HashSet;
+use std::io::Read;
+use std::net::{IpAddr, SocketAddr};
+
+const JAVA_PORT: u16 = 25565;
+const PNG_DATA_URL: &str = "data:image/png;base64,";
+// Far beyond any real list, icons and all; a bigger one is cut short and rejected.
+const MAX_INFLATED: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub name: String,
+    // `host`, `host:port` or `[v6]:port`, as typed into the client
+    pub ip: String,
+    // Base64 PNG without the data URL prefix
+    pub icon: Option<String>,
+    // Tags the client keeps alongside (acceptTextures, hidden, ...), kept as read
+    pub other: Vec<(String, Tag)>,
+}
+
+impl Entry {
+    /// Named after the first MOTD line, or the address when that's blank.
+    pub fn from_server(s: &ServerInfo) -> Self {
+        // The client reads a bare IPv6 address's last group as the port.
+        let ip = if s.addr.port() == JAVA_PORT && s.addr.is_ipv4() {
+            s.addr.ip().to_string()
+        } else {
+            s.addr.to_string()
+        };
+        let name = s
+            .motd
+            .lines()
+            .first()
+            .map(|line| line.plain().trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| ip.clone());
+        Self {
+            name,
+            icon: s
+                .favicon
+                .as_deref()
+                .and_then(|f| f.strip_prefix(PNG_DATA_URL))
+                .map(str::to_string),
+            ip,
+            other: Vec::new(),
+        }
+    }
+
+    /// The entry's address if `ip` is a literal; hostnames would need DNS.
+    pub fn addr(&self) -> Option<SocketAddr> {
+        let ip = self.ip.trim();
+        if let Ok(addr) = ip.parse() {
+            return Some(addr);
+        }
+        let bare = ip.trim_start_matches('[').trim_end_matches(']');
+        if let Ok(ip) = bare.parse::<IpAddr>() {
+            return Some(SocketAddr::new(ip, JAVA_PORT));
+        }
+        let (host, port) = ip.rsplit_once(':')?;
+        Some(SocketAddr::new(host.parse().ok()?, port.parse().ok()?))
+    }
+
+    // Entries naming one server, `1.2.3.4` and `1.2.3.4:25565` included, share a key.
+    fn key(&self) -> String {
+        match self.addr() {
+            Some(addr) => addr.to_string(),
+            None => self.ip.trim().to_ascii_lowercase(),
+        }
+    }
+
+    fn from_tag(tag: &Tag) -> Option<Self> {
+        let Tag::Compound(fields) = tag else {
+            return None;
+        };
+        let mut entry = Self {
+            name: String::new(),
+            ip: tag.get("ip")?.as_str()?.to_string(),
+            icon: None,
+            other: Vec::new(),
+        };
+        for (key, value) in fields {
+            match (key.as_str(), value) {
+                ("name", Tag::String(s)) => entry.name = s.clone(),
+                ("icon", Tag::String(s)) => entry.icon = Some(s.clone()),
+                ("ip", _) => {}
+                _ => entry.other.push((key.clone(), value.clone())),
+            }
+        }
+        Some(entry)
+    }
+
+    fn to_tag(&self) -> Tag {
+        let mut fields = vec![
+            ("name".to_string(), Tag::String(self.name.clone())),
+            ("ip".to_string(), Tag::String(self.ip.clone())),
+        ];
+        if let Some(icon) = &self.icon {
+            fields.push(("icon".to_string(), Tag::String(icon.clone())));
+        }
+        fields.extend(self.other.iter().cloned());
+        Tag::Compound(fields)
+    }
+}
+
+/// Java results as entries; the client can't join Bedrock servers.
+pub fn entries<'a>(items: impl IntoIterator<Item = &'a ServerInfo>) -> Vec<Entry> {
+    items
+        .into_iter()
+        .filter(|s| s.edition == Edition::Java)
+        .map(Entry::from_server)
+        .collect()
+}
+
+/// The entries of a `servers.dat`; entries without an `ip` are skipped.
+/// Gzipped copies, as some launchers keep, are read too.
+pub fn read(bytes: &[u8]) -> Option<Vec<Entry>> {
+    let mut inflated = Vec::new();
+    let bytes = if bytes.starts_with(&[0x1F, 0x8B]) {
+        flate2::read::GzDecoder::new(bytes)
+            .take(MAX_INFLATED)
+            .read_to_end(&mut inflated)
+            .ok()?;
+        &inflated[..]
+    } else {
+        bytes
+    };
+    let (_, root) = nbt::read(bytes)?;
+    match root.get("servers") {
+        Some(Tag::List(list)) => Some(list.iter().filter_map(Entry::from_tag).collect()),
+        Some(_) => None,
+        None => Some(Vec::new()),
+    }
+}
+
+pub fn write(entries: &[Entry]) -> Vec<u8> {
+    let servers = Tag::List(entries.iter().map(Entry::to_tag).collect());
+    nbt::write("", &Tag::Compound(vec![("servers".into(), servers)]))
+}
+
+/// `existing` untouched and in order, then each of `new` naming a server
+/// that isn't listed yet.
+pub fn merge(existing: Vec<Entry>, new: Vec<Entry>) -> Vec<Entry> {
+    let mut seen: HashSet<String> = existing.iter().map(Entry::key).collect();
+    let mut out = existing;
+    out.extend(new.into_iter().filter(|e| seen.insert(e.key())));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn java(addr: &str, motd: &str) -> ServerInfo {
+        let mut s = ServerInfo::base(addr.parse().unwrap(), Edition::Java);
+        s.motd = motd.into();
+        s
+    }
+
+    #[test]
+    fn entries_use_the_first_motd_line_and_favicon() {
+        let mut s = java("1.2.3.4:25565", "  §aLobby  \nsecond line");
+        s.favicon = Some(format!("{PNG_DATA_URL}iVBORw0KGgo="));
+        let other = java("1.2.3.4:25570", "");
+        let bedrock = ServerInfo::base("1.2.3.4:19132".parse().unwrap(), Edition::Bedrock);
+
+        let list = entries(&[s, other, bedrock]);
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0].name, "Lobby");
+        assert_eq!(list[0].ip, "1.2.3.4");
+        assert_eq!(list[0].icon.as_deref(), Some("iVBORw0KGgo="));
+        assert_eq!(list[1].name, "1.2.3.4:25570");
+        assert_eq!(list[1].icon, None);
+    }
+
+    #[test]
+    fn round_trips_and_keeps_unknown_tags() {
+        let mut entry = Entry::from_server(&java("[2001:db8::1]:25566", "V6"));
+        entry.other.push(("acceptTextures".into(), Tag::Byte(1)));
+        let bytes = write(std::slice::from_ref(&entry));
+        assert_eq!(read(&bytes), Some(vec![entry]));
+    }
+
+    #[test]
+    fn merge_skips_servers_already_listed() {
+        let existing = vec![Entry {
+            name: "Mine".into(),
+            ip: "1.2.3.4:25565".into(),
+            icon: None,
+            other: vec![("hidden".into(), Tag::Byte(0))],
+        }];
+        let new = entries(&[
+            java("1.2.3.4:25565", "Dup"),
+            java("5.6.7.8:25565", "New"),
+            java("5.6.7.8:25565", "New again"),
+        ]);
+        let merged = merge(existing.clone(), new);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0], existing[0]);
+        assert_eq!(merged[1].name, "New");
+    }
+
+    #[test]
+    fn addresses_parse_with_and_without_ports() {
+        let addr = |ip: &str| {
+            Entry {
+                name: String::new(),
+                ip: ip.into(),
+                icon: None,
+                other: Vec::new(),
+            }
+            .addr()
+        };
+        assert_eq!(addr("1.2.3.4"), Some("1.2.3.4:25565".parse().unwrap()));
+        assert_eq!(
+            addr("1.2.3.4:25570"),
+            Some("1.2.3.4:25570".parse().unwrap())
+        );
+        assert_eq!(
+            addr("2001:db8::1"),
+            Some("[2001:db8::1]:25565".parse().unwrap())
+        );
+        assert_eq!(
+            addr("[2001:db8::1]"),
+            Some("[2001:db8::1]:25565".parse().unwrap())
+        );
+        assert_eq!(addr("mc.example.com"), None);
+    }
+
+    #[test]
+    fn reads_gzipped_lists() {
+        use flate2::{Compression, write::GzEncoder};
+        use std::io::Write;
+        let plain = write(&entries(&[java("1.2.3.4:25565", "Gz")]));
+        let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+        gz.write_all(&plain).unwrap();
+        let list = read(&gz.finish().unwrap()).unwrap();
+        assert_eq!(list[0].name, "Gz");
+    }
+}
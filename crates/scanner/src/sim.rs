@@ -0,0 +1,442 @@
+//! In-memory network for tests and simulations: scripted hosts, latency,
+//! seeded packet loss and closed ports. Addresses nobody registered stay
+//! silent, like filtered ports on the internet, so probes run into their
+//! timeouts; with a paused tokio clock a whole /16 scan takes moments.
+
+use crate::transport::{Conn, Datagram, Transport};
+use crate::{bedrock, java};
+use futures::FutureExt;
+use futures::channel::mpsc;
+use futures::future::{BoxFuture, pending};
+use futures::lock::Mutex;
+use futures::{SinkExt, StreamExt};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt, duplex};
+use tokio::time::sleep;
+
+/// Gets everything the client sent so far; `Some(reply)` answers and hangs up,
+/// `None` waits for more.
+pub type TcpHandler = Arc<dyn Fn(&[u8]) -> Option<Vec<u8>> + Send + Sync>;
+
+/// Gets one datagram; `Some(reply)` answers it.
+pub type UdpHandler = Arc<dyn Fn(&[u8]) -> Option<Vec<u8>> + Send + Sync>;
+
+// Room for the largest status a test would script, so writes never block.
+const PIPE_BYTES: usize = 1 << 20;
+
+#[derive(Default)]
+pub struct SimNet {
+    tcp: HashMap<SocketAddr, TcpHandler>,
+    udp: HashMap<SocketAddr, UdpHandler>,
+    closed: HashSet<SocketAddr>,
+    // Round trip added to every connect, TCP reply and UDP answer
+    latency: Duration,
+    loss: Arc<Loss>,
+}
+
+impl SimNet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn latency(&mut self, rtt: Duration) -> &mut Self {
+        self.latency = rtt;
+        self
+    }
+
+    /// Drops each datagram, either way, with probability `rate`. The same seed
+    /// drops the same datagrams as long as they're sent in the same order.
+    /// TCP is reliable and unaffected.
+    pub fn loss(&mut self, rate: f64, seed: u64) -> &mut Self {
+        self.loss = Arc::new(Loss::new(rate, seed));
+        self
+    }
+
+    pub fn tcp(
+        &mut self,
+        addr: SocketAddr,
+        handler: impl Fn(&[u8]) -> Option<Vec<u8>> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.tcp.insert(addr, Arc::new(handler));
+        self
+    }
+
+    pub fn udp(
+        &mut self,
+        addr: SocketAddr,
+        handler: impl Fn(&[u8]) -> Option<Vec<u8>> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.udp.insert(addr, Arc::new(handler));
+        self
+    }
+
+    /// Refuses TCP connects and answers datagrams with "port unreachable".
+    pub fn closed(&mut self, addr: SocketAddr) -> &mut Self {
+        self.closed.insert(addr);
+        self
+    }
+
+    /// A Java server answering the status ping with `status_json`.
+    pub fn java(&mut self, addr: SocketAddr, status_json: &str) -> &mut Self {
+        let reply = java::build_status_response(status_json.as_bytes());
+        // Handshake, then the empty status request.
+        self.tcp(addr, move |seen| {
+            seen.ends_with(&[0x01, 0x00]).then(|| reply.clone())
+        })
+    }
+
+    /// A Bedrock server answering unconnected pings with `motd`
+    /// (`MCPE;name;protocol;version;online;max;...`).
+    pub fn bedrock(&mut self, addr: SocketAddr, motd: &str) -> &mut Self {
+        let reply = bedrock::build_pong(motd);
+        self.udp(addr, move |ping| {
+            (ping.first() == Some(&0x01)).then(|| reply.clone())
+        })
+    }
+}
+
+impl Transport for SimNet {
+    fn connect(&self, addr: SocketAddr) -> BoxFuture<'_, io::Result<Box<dyn Conn>>> {
+        async move {
+            let Some(handler) = self.tcp.get(&addr).cloned() else {
+                if self.closed.contains(&addr) {
+                    sleep(self.latency).await;
+                    return Err(io::ErrorKind::ConnectionRefused.into());
+                }
+                return pending().await;
+            };
+            sleep(self.latency).await;
+            let (client, mut server) = duplex(PIPE_BYTES);
+            let latency = self.latency;
+            tokio::spawn(async move {
+                let mut seen = Vec::new();
+                let mut buf = [0u8; 4096];
+                loop {
+                    match server.read(&mut buf).await {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => seen.extend_from_slice(&buf[..n]),
+                    }
+                    if let Some(reply) = handler(&seen) {
+                        sleep(latency).await;
+                        let _ = server.write_all(&reply).await;
+                        return;
+                    }
+                }
+            });
+            Ok(Box::new(client) as Box<dyn Conn>)
+        }
+        .boxed()
+    }
+
+    fn datagram(&self, peer: SocketAddr) -> BoxFuture<'_, io::Result<Box<dyn Datagram>>> {
+        let (tx, rx) = mpsc::unbounded();
+        let socket = SimDatagram {
+            handler: self.udp.get(&peer).cloned(),
+            closed: self.closed.contains(&peer),
+            latency: self.latency,
+            loss: self.loss.clone(),
+            tx,
+            rx: Mutex::new(rx),
+        };
+        futures::future::ready(Ok(Box::new(socket) as Box<dyn Datagram>)).boxed()
+    }
+}
+
+struct SimDatagram {
+    handler: Option<UdpHandler>,
+    closed: bool,
+    latency: Duration,
+    loss: Arc<Loss>,
+    tx: mpsc::UnboundedSender<io::Result<Vec<u8>>>,
+    rx: Mutex<mpsc::UnboundedReceiver<io::Result<Vec<u8>>>>,
+}
+
+impl Datagram for SimDatagram {
+    fn send<'a>(&'a self, buf: &'a [u8]) -> BoxFuture<'a, io::Result<usize>> {
+        let answer = if self.loss.drops() {
+            None
+        } else if let Some(handler) = &self.handler {
+            handler(buf).filter(|_| !self.loss.drops()).map(Ok)
+        } else if self.closed {
+            Some(Err(io::ErrorKind::ConnectionRefused.into()))
+        } else {
+            None
+        };
+        if let Some(answer) = answer {
+            let mut tx = self.tx.clone();
+            let latency = self.latency;
+            tokio::spawn(async move {
+                sleep(latency).await;
+                let _ = tx.send(answer).await;
+            });
+        }
+        futures::future::ready(Ok(buf.len())).boxed()
+    }
+
+    fn recv<'a>(&'a self, buf: &'a mut [u8]) -> BoxFuture<'a, io::Result<usize>> {
+        async move {
+            let mut rx = self.rx.lock().await;
+            // The sender lives in `self`, so the channel never ends.
+            let datagram = rx.next().await.unwrap_or_else(|| Ok(Vec::new()))?;
+            let n = datagram.len().min(buf.len());
+            buf[..n].copy_from_slice(&datagram[..n]);
+            Ok(n)
+        }
+        .boxed()
+    }
+}
+
+// xorshift64: deterministic for a seed, no RNG dependency.
+#[derive(Debug, Default)]
+struct Loss {
+    // Drop threshold out of u64::MAX; 0 = lossless
+    threshold: u64,
+    state: AtomicU64,
+}
+
+impl Loss {
+    fn new(rate: f64, seed: u64) -> Self {
+        Self {
+            threshold: (rate.clamp(0.0, 1.0) * u64::MAX as f64) as u64,
+            // xorshift never leaves zero
+            state: AtomicU64::new(seed | 1),
+        }
+    }
+
+    fn drops(&self) -> bool {
+        if self.threshold == 0 {
+            return false;
+        }
+        let next = |mut x: u64| {
+            x ^= x << 13;
+            x ^= x >> 7;
+            x ^= x << 17;
+            x
+        };
+        let prev = self
+            .state
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| Some(next(x)))
+            .unwrap_or_default();
+        next(prev) < self.threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(s: &str) -> SocketAddr {
+        s.parse().unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn java_host_answers_the_status_ping() {
+        let mut net = SimNet::new();
+        net.latency(Duration::from_millis(40)).java(
+            addr("10.0.0.1:25565"),
+            r#"{"version":{"name":"1.21","protocol":767}}"#,
+        );
+        let timeouts = crate::types::Timeouts::uniform(Duration::from_secs(1));
+        let info = java::probe_inner(&net, addr("10.0.0.1:25565"), "10.0.0.1", timeouts)
+            .await
+            .unwrap();
+        assert_eq!(info.version, "1.21");
+        assert_eq!(info.latency_ms, 80);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn closed_ports_refuse_and_silent_ones_time_out() {
+        let mut net = SimNet::new();
+        net.closed(addr("10.0.0.1:25565"));
+        let dur = Duration::from_secs(1);
+        let refused = tokio::time::timeout(dur, net.connect(addr("10.0.0.1:25565"))).await;
+        assert_eq!(
+            refused.unwrap().err().map(|e| e.kind()),
+            Some(io::ErrorKind::ConnectionRefused)
+        );
+        let silent = tokio::time::timeout(dur, net.connect(addr("10.0.0.2:25565"))).await;
+        assert!(silent.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn scans_a_simulated_slash_20() {
+        use crate::limits::{Concurrency, Ports};
+        use crate::types::{Edition, ScanConfig};
+
+        let java =
+            r#"{"version":{"name":"Paper 1.21","protocol":767},"players":{"online":3,"max":20}}"#;
+        let mut net = SimNet::new();
+        net.latency(Duration::from_millis(30)).loss(0.2, 42);
+        for i in 0..16u8 {
+            net.java(addr(&format!("10.1.{i}.7:25565")), java);
+            net.closed(addr(&format!("10.1.{i}.8:25565")));
+        }
+        // Over 50 datagrams, about a fifth of these are lost both ways.
+        for i in 0..50u8 {
+            net.bedrock(
+                addr(&format!("10.1.{}.{}:19132", i / 10, 100 + i % 10)),
+                "MCPE;Sim;766;1.21.50;1;10;1234;w;Survival;1;19132;19133;",
+            );
+        }
+
+        let config = ScanConfig::builder()
+            .ranges(["10.1.0.0/20".parse().unwrap()])
+            .java_ports(Ports::from_input("25565"))
+            .bedrock_ports(Ports::from_input("19132"))
+            .concurrency(Concurrency::from_input("4096"))
+            .options(
+                crate::types::ProbeOptions::builder()
+                    .timeout(Duration::from_millis(500))
+                    .build(),
+            )
+            .build();
+        let found: Vec<_> = crate::scan_over(Arc::new(config), Arc::new(net))
+            .filter_map(|hit| async move { hit })
+            .collect()
+            .await;
+
+        let java: Vec<_> = found
+            .iter()
+            .filter(|s| s.edition == Edition::Java)
+            .collect();
+        assert_eq!(java.len(), 16);
+        assert!(
+            java.iter()
+                .all(|s| s.version == "Paper 1.21" && s.latency_ms == 60)
+        );
+        let bedrock = found.len() - java.len();
+        assert!((20..50).contains(&bedrock), "{bedrock} Bedrock hosts");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn imported_targets_probe_only_those_endpoints() {
+        use crate::types::{Edition, ScanConfig};
+
+        let java = r#"{"version":{"name":"1.21","protocol":767}}"#;
+        let mut net = SimNet::new();
+        net.java(addr("10.2.0.1:25565"), java)
+            .java(addr("10.2.0.1:25570"), java)
+            .java(addr("10.2.0.2:25565"), java)
+            .bedrock(
+                addr("10.2.0.3:19133"),
+                "MCPE;Sim;766;1.21.50;1;10;1234;w;Survival;1;19133;19134;",
+            );
+        let masscan = "open tcp 25570 10.2.0.1 1700000000\nopen udp 19133 10.2.0.3 1700000000\n";
+        let config = ScanConfig::builder()
+            .targets(crate::import::parse(masscan))
+            .options(
+                crate::types::ProbeOptions::builder()
+                    .timeout(Duration::from_millis(500))
+                    .build(),
+            )
+            .build();
+        assert_eq!(config.target_count(), 2);
+        let mut found: Vec<_> = crate::scan_over(Arc::new(config), Arc::new(net))
+            .filter_map(|hit| async move { hit })
+            .map(|s| (s.addr, s.edition))
+            .collect()
+            .await;
+        found.sort_by_key(|(addr, _)| *addr);
+        assert_eq!(
+            found,
+            [
+                (addr("10.2.0.1:25570"), Edition::Java),
+                (addr("10.2.0.3:19133"), Edition::Bedrock)
+            ]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn overridden_ranges_scan_their_own_ports() {
+        use crate::types::ScanConfig;
+
+        let java = r#"{"version":{"name":"1.21","protocol":767}}"#;
+        let mut net = SimNet::new();
+        for host in ["10.3.0.1", "10.4.0.1"] {
+            net.java(addr(&format!("{host}:25565")), java)
+                .java(addr(&format!("{host}:25571")), java);
+        }
+        let targets = crate::parse::parse_targets("10.3.0.0/30 java=25570-25572\n10.4.0.0/30\n");
+        let config = ScanConfig::builder()
+            .ranges(targets.ranges)
+            .overrides(targets.overrides)
+            .options(
+                crate::types::ProbeOptions::builder()
+                    .timeout(Duration::from_millis(500))
+                    .build(),
+            )
+            .build();
+        assert_eq!(config.target_count(), 2 * 4 + 2 * 2);
+        let mut found: Vec<_> = crate::scan_over(Arc::new(config), Arc::new(net))
+            .filter_map(|hit| async move { hit.map(|s| s.addr) })
+            .collect()
+            .await;
+        found.sort();
+        assert_eq!(found, [addr("10.3.0.1:25571"), addr("10.4.0.1:25565")]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn each_host_is_probed_by_its_most_specific_range() {
+        use crate::types::ScanConfig;
+
+        let java = r#"{"version":{"name":"1.21","protocol":767}}"#;
+        let mut net = SimNet::new();
+        for target in [
+            "10.5.0.3:25565",
+            "10.5.0.4:25565",
+            "10.5.0.5:25565",
+            "10.5.0.5:25571",
+            "10.6.0.16:25570",
+            "10.6.0.17:25570",
+            "10.6.0.17:25571",
+        ] {
+            net.java(addr(target), java);
+        }
+        let targets = crate::parse::parse_targets(
+            "10.5.0.0/29\n10.5.0.4/30 java=25571\n10.6.0.0/24 java=25570\n10.6.0.16/28 java=25571\n",
+        );
+        let config = ScanConfig::builder()
+            .ranges(targets.ranges)
+            .overrides(targets.overrides)
+            .options(
+                crate::types::ProbeOptions::builder()
+                    .timeout(Duration::from_millis(500))
+                    .build(),
+            )
+            .build();
+        // Java plus the default Bedrock port. The nested ranges probe only their
+        // hosts, so .4 and .16 stay with the ranges around them: 4 + 2 + 240 + 14.
+        assert_eq!(config.target_count(), 2 * (4 + 2 + 240 + 14));
+        let mut found: Vec<_> = crate::scan_over(Arc::new(config), Arc::new(net))
+            .filter_map(|hit| async move { hit.map(|s| s.addr) })
+            .collect()
+            .await;
+        found.sort();
+        assert_eq!(
+            found,
+            [
+                addr("10.5.0.3:25565"),
+                addr("10.5.0.4:25565"),
+                addr("10.5.0.5:25571"),
+                addr("10.6.0.16:25570"),
+                addr("10.6.0.17:25571")
+            ]
+        );
+    }
+
+    #[test]
+    fn loss_is_seeded_and_roughly_at_rate() {
+        let a = Loss::new(0.3, 7);
+        let b = Loss::new(0.3, 7);
+        let drops: Vec<bool> = (0..1000).map(|_| a.drops()).collect();
+        assert!(drops.iter().all(|&d| d == b.drops()));
+        let n = drops.iter().filter(|&&d| d).count();
+        assert!((250..350).contains(&n), "{n} drops");
+        assert!(!Loss::default().drops());
+    }
+}
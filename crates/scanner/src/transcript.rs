@@ -0,0 +1,855 @@
+//! Probe transcripts: every byte a probe sent and got back, with timing.
+//! [`Recorder`] captures them from a live [`Transport`]; [`Replay`] serves them
+//! back so the same probes and parsers can be rerun offline, e.g. from a file
+//! attached to a bug report.
+//!
+//! Only hosts that answered something are kept: a connect that went through,
+//! a refusal, or any datagram back. Silent addresses would only bloat the file.
+
+use crate::ProbeError;
+use crate::probe::{Registry, Target};
+use crate::transport::{Conn, Datagram, Transport};
+use crate::types::{Edition, ProbeOptions, ScanConfig, ServerInfo, Timeouts};
+use flate2::Compression;
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use futures::channel::mpsc;
+use futures::future::{BoxFuture, pending};
+use futures::lock::Mutex as AsyncMutex;
+use futures::{FutureExt, SinkExt, Stream, StreamExt};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf, duplex};
+use tokio::time::{Instant, sleep_until};
+
+const MAGIC: &[u8; 4] = b"MCTR";
+const FORMAT_VERSION: u8 = 1;
+
+// Guards decoding against lengths from a corrupt or hostile file.
+const MAX_CHUNK: usize = 16 * 1024 * 1024;
+// A whole transcript, decompressed; past it the file is cut short and rejected.
+const MAX_INFLATED: usize = 256 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Tcp,
+    Udp,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    // TCP handshake completed
+    Connected,
+    Sent(Vec<u8>),
+    // One read or one datagram, as it arrived
+    Received(Vec<u8>),
+    // Peer closed the stream
+    Closed,
+    // Connect refused, or an ICMP error on a datagram socket
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Event {
+    // Since the exchange opened
+    pub at: Duration,
+    pub step: Step,
+}
+
+/// One connection or datagram socket to one peer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Exchange {
+    pub kind: Kind,
+    pub addr: SocketAddr,
+    pub events: Vec<Event>,
+}
+
+impl Exchange {
+    fn answered(&self) -> bool {
+        self.events.iter().any(|e| !matches!(e.step, Step::Sent(_)))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Transcript {
+    // As recorded, minus the login identity
+    pub options: ProbeOptions,
+    // Probes in the order they started
+    pub targets: Vec<(SocketAddr, Edition)>,
+    pub exchanges: Vec<Exchange>,
+}
+
+impl Transcript {
+    /// Deflated binary: header, options, targets, then each exchange's events.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut raw = Vec::new();
+        raw.extend_from_slice(MAGIC);
+        raw.push(FORMAT_VERSION);
+        let o = &self.options;
+        write_uvarint(&mut raw, o.timeouts.connect.as_millis() as u64);
+        write_uvarint(&mut raw, o.timeouts.read.as_millis() as u64);
+        raw.push(
+            o.query_enabled as u8
+                | (o.online_mode_check as u8) << 1
+                | (o.raknet_check as u8) << 2
+                | (o.rcon_check as u8) << 3
+                | (o.keep_raw as u8) << 4,
+        );
+        write_bytes(
+            &mut raw,
+            o.handshake_host.as_deref().unwrap_or("").as_bytes(),
+        );
+
+        write_uvarint(&mut raw, self.targets.len() as u64);
+        for (addr, edition) in &self.targets {
+            write_addr(&mut raw, addr);
+            raw.push(match edition {
+                Edition::Java => 0,
+                Edition::Bedrock => 1,
+            });
+        }
+
+        write_uvarint(&mut raw, self.exchanges.len() as u64);
+        for ex in &self.exchanges {
+            raw.push(match ex.kind {
+                Kind::Tcp => 0,
+                Kind::Udp => 1,
+            });
+            write_addr(&mut raw, &ex.addr);
+            write_uvarint(&mut raw, ex.events.len() as u64);
+            for e in &ex.events {
+                let (tag, bytes) = match &e.step {
+                    Step::Connected => (0, None),
+                    Step::Sent(b) => (1, Some(b)),
+                    Step::Received(b) => (2, Some(b)),
+                    Step::Closed => (3, None),
+                    Step::Failed => (4, None),
+                };
+                raw.push(tag);
+                write_uvarint(&mut raw, e.at.as_micros() as u64);
+                if let Some(b) = bytes {
+                    write_bytes(&mut raw, b);
+                }
+            }
+        }
+
+        let mut enc = DeflateEncoder::new(Vec::new(), Compression::best());
+        // Writing into a Vec cannot fail.
+        let _ = enc.write_all(&raw);
+        enc.finish().unwrap_or_default()
+    }
+
+    pub fn from_bytes(data: &[u8]) -> io::Result<Self> {
+        let mut raw = Vec::new();
+        DeflateDecoder::new(data)
+            .take(MAX_INFLATED as u64)
+            .read_to_end(&mut raw)?;
+        let mut cur = &raw[..];
+        Self::decode(&mut cur).ok_or_else(|| io::ErrorKind::InvalidData.into())
+    }
+
+    fn decode(cur: &mut &[u8]) -> Option<Self> {
+        if take(cur, 4)? != MAGIC || take(cur, 1)?[0] != FORMAT_VERSION {
+            return None;
+        }
+        let timeouts = Timeouts {
+            connect: Duration::from_millis(read_uvarint(cur)?),
+            read: Duration::from_millis(read_uvarint(cur)?),
+        };
+        let flags = take(cur, 1)?[0];
+        let host = String::from_utf8(read_bytes(cur)?.to_vec()).ok()?;
+        let options = ProbeOptions {
+            timeouts,
+            query_enabled: flags & 1 != 0,
+            online_mode_check: flags & 2 != 0,
+            raknet_check: flags & 4 != 0,
+            rcon_check: flags & 8 != 0,
+            keep_raw: flags & 16 != 0,
+            identity: None,
+            handshake_host: Some(host).filter(|h| !h.is_empty()),
+        };
+
+        let mut targets = Vec::new();
+        for _ in 0..read_uvarint(cur)? {
+            let addr = read_addr(cur)?;
+            let edition = match take(cur, 1)?[0] {
+                0 => Edition::Java,
+                1 => Edition::Bedrock,
+                _ => return None,
+            };
+            targets.push((addr, edition));
+        }
+
+        let mut exchanges = Vec::new();
+        for _ in 0..read_uvarint(cur)? {
+            let kind = match take(cur, 1)?[0] {
+                0 => Kind::Tcp,
+                1 => Kind::Udp,
+                _ => return None,
+            };
+            let addr = read_addr(cur)?;
+            let mut events = Vec::new();
+            for _ in 0..read_uvarint(cur)? {
+                let tag = take(cur, 1)?[0];
+                let at = Duration::from_micros(read_uvarint(cur)?);
+                let step = match tag {
+                    0 => Step::Connected,
+                    1 => Step::Sent(read_bytes(cur)?.to_vec()),
+                    2 => Step::Received(read_bytes(cur)?.to_vec()),
+                    3 => Step::Closed,
+                    4 => Step::Failed,
+                    _ => return None,
+                };
+                events.push(Event { at, step });
+            }
+            exchanges.push(Exchange { kind, addr, events });
+        }
+        Some(Self {
+            options,
+            targets,
+            exchanges,
+        })
+    }
+}
+
+/// Wraps a transport and writes down everything that passes through it.
+/// Silent exchanges are let go as they close, and so are the targets they
+/// were the last hope of, so memory follows the answers rather than the range.
+pub struct Recorder<T> {
+    inner: T,
+    options: ProbeOptions,
+    started: Arc<Mutex<Started>>,
+    opened: AtomicU64,
+    // Closed exchanges that got an answer, by when they opened
+    exchanges: Arc<Mutex<Vec<(u64, Exchange)>>>,
+}
+
+// Probed targets in start order, minus those whose address went silent.
+#[derive(Default)]
+struct Started {
+    targets: Vec<(SocketAddr, Edition)>,
+    // Exchanges still open per address
+    open: HashMap<SocketAddr, usize>,
+    answered: HashSet<SocketAddr>,
+}
+
+impl Started {
+    fn opened(&mut self, addr: SocketAddr) {
+        *self.open.entry(addr).or_default() += 1;
+    }
+
+    fn closed(&mut self, addr: SocketAddr, answered: bool) {
+        if let Some(n) = self.open.get_mut(&addr) {
+            *n -= 1;
+            if *n == 0 {
+                self.open.remove(&addr);
+            }
+        }
+        if answered {
+            self.answered.insert(addr);
+        } else if !self.open.contains_key(&addr) && !self.answered.contains(&addr) {
+            self.targets.retain(|(a, _)| *a != addr);
+        }
+    }
+}
+
+impl<T: Transport> Recorder<T> {
+    /// `options` are the ones the probes run with; they are saved alongside
+    /// so a replay probes the same way.
+    pub fn new(inner: T, options: &ProbeOptions) -> Self {
+        Self {
+            inner,
+            options: ProbeOptions {
+                identity: None,
+                ..options.clone()
+            },
+            started: Arc::default(),
+            opened: AtomicU64::new(0),
+            exchanges: Arc::default(),
+        }
+    }
+
+    /// What was recorded so far, for the hosts that answered. Connections and
+    /// sockets still open are left out.
+    pub fn transcript(&self) -> Transcript {
+        let mut exchanges = lock(&self.exchanges).clone();
+        // Replay hands out an address's exchanges in this order.
+        exchanges.sort_unstable_by_key(|(seq, _)| *seq);
+        let exchanges: Vec<Exchange> = exchanges.into_iter().map(|(_, ex)| ex).collect();
+        let hosts: HashSet<IpAddr> = exchanges.iter().map(|ex| ex.addr.ip()).collect();
+        let targets = lock(&self.started)
+            .targets
+            .iter()
+            .filter(|(addr, _)| hosts.contains(&addr.ip()))
+            .copied()
+            .collect();
+        Transcript {
+            options: self.options.clone(),
+            targets,
+            exchanges,
+        }
+    }
+
+    /// Probes a target through this recorder with `registry`, keeping it for
+    /// the transcript.
+    pub async fn probe(
+        &self,
+        registry: &Registry,
+        addr: SocketAddr,
+        edition: Edition,
+        options: &ProbeOptions,
+    ) -> Result<ServerInfo, ProbeError> {
+        lock(&self.started).targets.push((addr, edition));
+        let target = Target {
+            addr,
+            edition,
+            options,
+            net: self,
+        };
+        registry.probe(target).await
+    }
+
+    fn open(&self, kind: Kind, addr: SocketAddr) -> Log {
+        lock(&self.started).opened(addr);
+        Log {
+            seq: self.opened.fetch_add(1, Ordering::Relaxed),
+            kind,
+            addr,
+            start: Instant::now(),
+            events: Mutex::default(),
+            done: self.exchanges.clone(),
+            started: self.started.clone(),
+        }
+    }
+}
+
+impl<T: Transport> Transport for Recorder<T> {
+    fn connect(&self, addr: SocketAddr) -> BoxFuture<'_, io::Result<Box<dyn Conn>>> {
+        async move {
+            let log = self.open(Kind::Tcp, addr);
+            match self.inner.connect(addr).await {
+                Ok(inner) => {
+                    log.push(Step::Connected);
+                    Ok(Box::new(RecordingConn { inner, log }) as Box<dyn Conn>)
+                }
+                Err(e) => {
+                    log.push(Step::Failed);
+                    Err(e)
+                }
+            }
+        }
+        .boxed()
+    }
+
+    fn datagram(&self, peer: SocketAddr) -> BoxFuture<'_, io::Result<Box<dyn Datagram>>> {
+        async move {
+            let inner = self.inner.datagram(peer).await?;
+            let log = self.open(Kind::Udp, peer);
+            Ok(Box::new(RecordingDatagram { inner, log }) as Box<dyn Datagram>)
+        }
+        .boxed()
+    }
+}
+
+struct Log {
+    seq: u64,
+    kind: Kind,
+    addr: SocketAddr,
+    start: Instant,
+    events: Mutex<Vec<Event>>,
+    done: Arc<Mutex<Vec<(u64, Exchange)>>>,
+    started: Arc<Mutex<Started>>,
+}
+
+impl Log {
+    fn push(&self, step: Step) {
+        // Whole microseconds, as stored.
+        let at = Duration::from_micros(self.start.elapsed().as_micros() as u64);
+        lock(&self.events).push(Event { at, step });
+    }
+}
+
+impl Drop for Log {
+    fn drop(&mut self) {
+        let exchange = Exchange {
+            kind: self.kind,
+            addr: self.addr,
+            events: std::mem::take(self.events.get_mut().unwrap_or_else(|e| e.into_inner())),
+        };
+        let answered = exchange.answered();
+        lock(&self.started).closed(self.addr, answered);
+        if answered {
+            lock(&self.done).push((self.seq, exchange));
+        }
+    }
+}
+
+struct RecordingConn {
+    inner: Box<dyn Conn>,
+    log: Log,
+}
+
+impl AsyncRead for RecordingConn {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let before = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        match &poll {
+            Poll::Ready(Ok(())) if buf.filled().len() > before => {
+                self.log
+                    .push(Step::Received(buf.filled()[before..].to_vec()));
+            }
+            Poll::Ready(Ok(())) if buf.remaining() > 0 => self.log.push(Step::Closed),
+            Poll::Ready(Err(_)) => self.log.push(Step::Failed),
+            _ => {}
+        }
+        poll
+    }
+}
+
+impl AsyncWrite for RecordingConn {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.inner).poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = &poll {
+            self.log.push(Step::Sent(buf[..*n].to_vec()));
+        }
+        poll
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+struct RecordingDatagram {
+    inner: Box<dyn Datagram>,
+    log: Log,
+}
+
+impl Datagram for RecordingDatagram {
+    fn send<'a>(&'a self, buf: &'a [u8]) -> BoxFuture<'a, io::Result<usize>> {
+        async move {
+            let n = self.inner.send(buf).await?;
+            self.log.push(Step::Sent(buf[..n].to_vec()));
+            Ok(n)
+        }
+        .boxed()
+    }
+
+    fn recv<'a>(&'a self, buf: &'a mut [u8]) -> BoxFuture<'a, io::Result<usize>> {
+        async move {
+            match self.inner.recv(buf).await {
+                Ok(n) => {
+                    self.log.push(Step::Received(buf[..n].to_vec()));
+                    Ok(n)
+                }
+                Err(e) => {
+                    self.log.push(Step::Failed);
+                    Err(e)
+                }
+            }
+        }
+        .boxed()
+    }
+}
+
+/// Serves a transcript back as a network. Each connect or socket to an address
+/// takes that address's next recorded exchange and answers with its recorded
+/// bytes at their recorded times; what the probe sends is not checked.
+/// Addresses with nothing left stay silent.
+pub struct Replay {
+    queues: HashMap<(Kind, SocketAddr), Mutex<VecDeque<Exchange>>>,
+}
+
+impl Replay {
+    pub fn new(transcript: &Transcript) -> Self {
+        let mut queues: HashMap<_, Mutex<VecDeque<_>>> = HashMap::new();
+        for ex in &transcript.exchanges {
+            lock(queues.entry((ex.kind, ex.addr)).or_default()).push_back(ex.clone());
+        }
+        Self { queues }
+    }
+
+    fn next(&self, kind: Kind, addr: SocketAddr) -> Option<Exchange> {
+        lock(self.queues.get(&(kind, addr))?).pop_front()
+    }
+}
+
+impl Transport for Replay {
+    fn connect(&self, addr: SocketAddr) -> BoxFuture<'_, io::Result<Box<dyn Conn>>> {
+        async move {
+            let start = Instant::now();
+            let Some(ex) = self.next(Kind::Tcp, addr) else {
+                return pending().await;
+            };
+            let mut events = ex.events.into_iter();
+            match events.next() {
+                Some(Event {
+                    at,
+                    step: Step::Connected,
+                }) => sleep_until(start + at).await,
+                Some(Event {
+                    at,
+                    step: Step::Failed,
+                }) => {
+                    sleep_until(start + at).await;
+                    return Err(io::ErrorKind::ConnectionRefused.into());
+                }
+                _ => return pending().await,
+            }
+            let events: Vec<Event> = events.collect();
+            let (client, mut server) = duplex(1 << 20);
+            tokio::spawn(async move {
+                for e in events {
+                    match e.step {
+                        Step::Received(bytes) => {
+                            sleep_until(start + e.at).await;
+                            if server.write_all(&bytes).await.is_err() {
+                                return;
+                            }
+                        }
+                        Step::Closed | Step::Failed => {
+                            sleep_until(start + e.at).await;
+                            return;
+                        }
+                        Step::Connected | Step::Sent(_) => {}
+                    }
+                }
+                // The recording ended with the peer still connected.
+                let mut sink = [0u8; 4096];
+                while matches!(server.read(&mut sink).await, Ok(n) if n > 0) {}
+            });
+            Ok(Box::new(client) as Box<dyn Conn>)
+        }
+        .boxed()
+    }
+
+    fn datagram(&self, peer: SocketAddr) -> BoxFuture<'_, io::Result<Box<dyn Datagram>>> {
+        let start = Instant::now();
+        let (mut tx, rx) = mpsc::unbounded();
+        if let Some(ex) = self.next(Kind::Udp, peer) {
+            tokio::spawn(async move {
+                for e in ex.events {
+                    let item = match e.step {
+                        Step::Received(bytes) => Ok(bytes),
+                        Step::Failed => Err(io::ErrorKind::ConnectionRefused.into()),
+                        _ => continue,
+                    };
+                    sleep_until(start + e.at).await;
+                    if tx.send(item).await.is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+        let socket = ReplayDatagram {
+            rx: AsyncMutex::new(rx),
+        };
+        futures::future::ready(Ok(Box::new(socket) as Box<dyn Datagram>)).boxed()
+    }
+}
+
+struct ReplayDatagram {
+    rx: AsyncMutex<mpsc::UnboundedReceiver<io::Result<Vec<u8>>>>,
+}
+
+impl Datagram for ReplayDatagram {
+    fn send<'a>(&'a self, buf: &'a [u8]) -> BoxFuture<'a, io::Result<usize>> {
+        futures::future::ready(Ok(buf.len())).boxed()
+    }
+
+    fn recv<'a>(&'a self, buf: &'a mut [u8]) -> BoxFuture<'a, io::Result<usize>> {
+        async move {
+            let mut rx = self.rx.lock().await;
+            // Past the recording the peer went quiet.
+            let Some(datagram) = rx.next().await else {
+                return pending().await;
+            };
+            let datagram = datagram?;
+            let n = datagram.len().min(buf.len());
+            buf[..n].copy_from_slice(&datagram[..n]);
+            Ok(n)
+        }
+        .boxed()
+    }
+}
+
+/// [`scan_over`](crate::scan_over) through `recorder`, which keeps each target
+/// as its probe begins.
+pub fn scan_recorded<T: Transport + 'static>(
+    config: Arc<ScanConfig>,
+    recorder: Arc<Recorder<T>>,
+) -> impl Stream<Item = Option<ServerInfo>> + Send + 'static {
+    let registry = Arc::new(crate::scan_registry(&config));
+    crate::sweep(config.clone(), move |addr, edition| {
+        let registry = registry.clone();
+        let config = config.clone();
+        let recorder = recorder.clone();
+        async move {
+            recorder
+                .probe(&registry, addr, edition, &config.options)
+                .await
+                .ok()
+        }
+    })
+}
+
+/// Reruns every recorded probe against the transcript, with the options it was
+/// recorded with, and returns each target's outcome in the recorded order.
+pub async fn replay(
+    transcript: &Transcript,
+) -> Vec<(SocketAddr, Edition, Result<ServerInfo, ProbeError>)> {
+    let net = Replay::new(transcript);
+    let registry = Registry::standard(&transcript.options);
+    let probes = transcript.targets.iter().map(|&(addr, edition)| {
+        let target = Target {
+            addr,
+            edition,
+            options: &transcript.options,
+            net: &net,
+        };
+        let registry = &registry;
+        async move { (addr, edition, registry.probe(target).await) }
+    });
+    futures::future::join_all(probes).await
+}
+
+// A poisoned lock only means another probe panicked; the log is still usable.
+fn lock<T>(m: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    m.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn write_uvarint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push(v as u8 | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
+
+fn read_uvarint(cur: &mut &[u8]) -> Option<u64> {
+    let mut result = 0u64;
+    for shift in (0..64).step_by(7) {
+        let b = take(cur, 1)?[0];
+        result |= ((b & 0x7F) as u64) << shift;
+        if b & 0x80 == 0 {
+            return Some(result);
+        }
+    }
+    None
+}
+
+fn write_bytes(buf: &mut Vec<u8>, bytes: &[u8]) {
+    write_uvarint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+fn read_bytes<'a>(cur: &mut &'a [u8]) -> Option<&'a [u8]> {
+    let len = read_uvarint(cur)? as usize;
+    if len > MAX_CHUNK {
+        return None;
+    }
+    take(cur, len)
+}
+
+fn write_addr(buf: &mut Vec<u8>, addr: &SocketAddr) {
+    match addr.ip() {
+        IpAddr::V4(ip) => {
+            buf.push(4);
+            buf.extend_from_slice(&ip.octets());
+        }
+        IpAddr::V6(ip) => {
+            buf.push(6);
+            buf.extend_from_slice(&ip.octets());
+        }
+    }
+    buf.extend_from_slice(&addr.port().to_be_bytes());
+}
+
+fn read_addr(cur: &mut &[u8]) -> Option<SocketAddr> {
+    let ip = match take(cur, 1)?[0] {
+        4 => IpAddr::from(<[u8; 4]>::try_from(take(cur, 4)?).ok()?),
+        6 => IpAddr::from(<[u8; 16]>::try_from(take(cur, 16)?).ok()?),
+        _ => return None,
+    };
+    let port = u16::from_be_bytes(take(cur, 2)?.try_into().ok()?);
+    Some(SocketAddr::new(ip, port))
+}
+
+fn take<'a>(cur: &mut &'a [u8], n: usize) -> Option<&'a [u8]> {
+    if cur.len() < n {
+        return None;
+    }
+    let (head, rest) = cur.split_at(n);
+    *cur = rest;
+    Some(head)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::TokioNet;
+    use testkit::{BedrockServer, JavaServer, Login, QueryServer};
+
+    async fn record(
+        options: &ProbeOptions,
+        targets: &[(SocketAddr, Edition)],
+    ) -> (Transcript, Vec<Result<ServerInfo, ProbeError>>) {
+        let recorder = Recorder::new(TokioNet, options);
+        let registry = Registry::standard(options);
+        let mut live = Vec::new();
+        for &(addr, edition) in targets {
+            live.push(recorder.probe(&registry, addr, edition, options).await);
+        }
+        (recorder.transcript(), live)
+    }
+
+    #[tokio::test]
+    async fn replays_status_query_login_and_pong_offline() {
+        let java =
+            r#"{"version":{"name":"Paper 1.21.1","protocol":767},"players":{"online":2,"max":20}}"#;
+        let status = JavaServer::new(java)
+            .login(Login::not_whitelisted())
+            .spawn()
+            .await
+            .unwrap();
+        // Query on the game port, like `query.port` left at its default.
+        let query = QueryServer::new()
+            .players(["Notch", "jeb_"])
+            .map("world_nether")
+            .spawn_on(status.addr().port())
+            .await
+            .unwrap();
+        let bedrock = BedrockServer::new("MCPE;Pong;766;1.21.50;1;10;42;w;Survival;1;;;")
+            .spawn()
+            .await
+            .unwrap();
+        let options = ProbeOptions::builder()
+            .timeout(Duration::from_millis(200))
+            .query(true)
+            .online_mode_check(true)
+            .build();
+        let targets = [
+            (status.addr(), Edition::Java),
+            (bedrock.addr(), Edition::Bedrock),
+        ];
+        let (transcript, live) = record(&options, &targets).await;
+        drop((status, query, bedrock));
+
+        let decoded = Transcript::from_bytes(&transcript.to_bytes()).unwrap();
+        assert_eq!(decoded, transcript);
+        assert_eq!(decoded.targets, targets);
+
+        let replayed = replay(&decoded).await;
+        let java = replayed[0].2.as_ref().unwrap();
+        let java_live = live[0].as_ref().unwrap();
+        assert_eq!(java.version, "Paper 1.21.1");
+        assert_eq!(java.whitelist, java_live.whitelist);
+        assert_eq!(java.status_hash, java_live.status_hash);
+        assert_eq!(java.whitelist, Some(true));
+        assert_eq!(java.samples, ["Notch", "jeb_"]);
+        assert_eq!(java.world.as_deref(), Some("world_nether"));
+        let pong = replayed[1].2.as_ref().unwrap();
+        assert_eq!(pong.motd.plain(), "Pong");
+    }
+
+    #[tokio::test]
+    async fn replay_reproduces_parse_failure() {
+        let odd = JavaServer::raw_status(*b"\x05\x00\x03{}}")
+            .spawn()
+            .await
+            .unwrap();
+        let options = ProbeOptions::builder()
+            .timeout(Duration::from_millis(500))
+            .build();
+        let (transcript, live) = record(&options, &[(odd.addr(), Edition::Java)]).await;
+        assert!(matches!(live[0], Err(ProbeError::Unparsed(_))));
+        drop(odd);
+
+        let bytes = transcript.to_bytes();
+        let replayed = replay(&Transcript::from_bytes(&bytes).unwrap()).await;
+        assert_eq!(replayed[0].2.as_ref().err(), live[0].as_ref().err());
+    }
+
+    #[tokio::test]
+    async fn drops_hosts_that_never_answered() {
+        // Bound, so no ICMP error comes back either; the ping just goes unanswered.
+        let silent = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = silent.local_addr().unwrap();
+        let options = ProbeOptions::builder()
+            .timeout(Duration::from_millis(100))
+            .build();
+        let recorder = Recorder::new(TokioNet, &options);
+        let registry = Registry::standard(&options);
+        let probed = recorder.probe(&registry, addr, Edition::Bedrock, &options);
+        assert!(probed.await.is_err());
+        // Not even held on to until the transcript is taken.
+        assert!(lock(&recorder.started).targets.is_empty());
+        let transcript = recorder.transcript();
+        assert!(transcript.exchanges.is_empty());
+        assert!(transcript.targets.is_empty());
+    }
+
+    #[tokio::test]
+    async fn scans_record_the_targets_that_answered() {
+        use crate::types::Endpoint;
+
+        let java = r#"{"version":{"name":"1.21","protocol":767}}"#;
+        let server = JavaServer::new(java).spawn().await.unwrap();
+        let silent = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let options = ProbeOptions::builder()
+            .timeout(Duration::from_millis(200))
+            .build();
+        let config = ScanConfig::builder()
+            .targets([
+                Endpoint {
+                    addr: server.addr(),
+                    edition: Some(Edition::Java),
+                },
+                Endpoint {
+                    addr: silent.local_addr().unwrap(),
+                    edition: Some(Edition::Bedrock),
+                },
+            ])
+            .options(options.clone())
+            .build();
+        let recorder = Arc::new(Recorder::new(TokioNet, &options));
+        let found: Vec<_> = scan_recorded(Arc::new(config), recorder.clone())
+            .filter_map(|hit| async move { hit })
+            .collect()
+            .await;
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            recorder.transcript().targets,
+            [(server.addr(), Edition::Java)]
+        );
+    }
+
+    #[test]
+    fn rejects_foreign_and_truncated_files() {
+        assert!(Transcript::from_bytes(b"not deflate at all").is_err());
+        let bytes = Transcript::default().to_bytes();
+        assert_eq!(
+            Transcript::from_bytes(&bytes).unwrap(),
+            Transcript::default()
+        );
+        assert!(Transcript::from_bytes(&bytes[..bytes.len() / 2]).is_err());
+    }
+}
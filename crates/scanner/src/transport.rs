@@ -0,0 +1,58 @@
+//! Where probes get their sockets. [`TokioNet`] is the real network; the
+//! in-memory [`crate::sim::SimNet`] stands in for it in tests and simulations.
+
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use std::io;
+use std::net::SocketAddr;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpStream, UdpSocket};
+
+/// A byte stream to one peer, like a connected `TcpStream`.
+pub trait Conn: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Conn for T {}
+
+/// A datagram socket connected to one peer, like a connected `UdpSocket`.
+pub trait Datagram: Send + Sync {
+    fn send<'a>(&'a self, buf: &'a [u8]) -> BoxFuture<'a, io::Result<usize>>;
+
+    fn recv<'a>(&'a self, buf: &'a mut [u8]) -> BoxFuture<'a, io::Result<usize>>;
+}
+
+pub trait Transport: Send + Sync {
+    /// Opens a stream to `addr`. Timeouts are the caller's job.
+    fn connect(&self, addr: SocketAddr) -> BoxFuture<'_, io::Result<Box<dyn Conn>>>;
+
+    /// Opens a datagram socket that sends to and receives from `peer` only.
+    fn datagram(&self, peer: SocketAddr) -> BoxFuture<'_, io::Result<Box<dyn Datagram>>>;
+}
+
+/// The operating system's network through tokio.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokioNet;
+
+impl Transport for TokioNet {
+    fn connect(&self, addr: SocketAddr) -> BoxFuture<'_, io::Result<Box<dyn Conn>>> {
+        async move { Ok(Box::new(TcpStream::connect(addr).await?) as Box<dyn Conn>) }.boxed()
+    }
+
+    fn datagram(&self, peer: SocketAddr) -> BoxFuture<'_, io::Result<Box<dyn Datagram>>> {
+        async move {
+            let socket = UdpSocket::bind(super::local_bind_addr(&peer)).await?;
+            socket.connect(peer).await?;
+            Ok(Box::new(socket) as Box<dyn Datagram>)
+        }
+        .boxed()
+    }
+}
+
+impl Datagram for UdpSocket {
+    fn send<'a>(&'a self, buf: &'a [u8]) -> BoxFuture<'a, io::Result<usize>> {
+        UdpSocket::send(self, buf).boxed()
+    }
+
+    fn recv<'a>(&'a self, buf: &'a mut [u8]) -> BoxFuture<'a, io::Result<usize>> {
+        UdpSocket::recv(self, buf).boxed()
+    }
+}
@@ -1,8 +1,13 @@
+use super::honeypot::Signal;
 use super::limits::{Concurrency, Ports, TimeoutMs};
+use super::motd::Motd;
+use super::octets::OctetPattern;
+use super::parse::RangeSet;
 use ipnet::IpNet;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Edition {
     Java,
     Bedrock,
@@ -23,7 +28,7 @@ pub struct ModInfo {
     pub version: String,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Credentials {
     pub username: String,
     pub uuid: [u8; 16],
@@ -34,7 +39,11 @@ pub struct Credentials {
 pub struct ServerInfo {
     pub addr: SocketAddr,
     pub edition: Edition,
-    pub motd: String,
+    // Other addresses the same server answered on (advertised ports, shared GUID)
+    pub endpoints: Vec<SocketAddr>,
+    // The other edition on this host when it looks like a Geyser hybrid
+    pub hybrid_with: Option<SocketAddr>,
+    pub motd: Motd,
     pub version: String,
     pub protocol: i32,
     pub online: u32,
@@ -42,12 +51,17 @@ pub struct ServerInfo {
     pub latency_ms: u64,
     pub samples: Vec<String>,
     pub ping_history: Vec<u64>,
+    // Status JSON (Java) or pong string (Bedrock) as received; only kept with
+    // ProbeOptions::keep_raw
+    pub raw_status: Option<String>,
 
     // Java extras (SLP status)
     pub favicon: Option<String>,
     pub sample_ids: Vec<String>,
     pub secure_chat: Option<bool>,
     pub mods: Vec<ModInfo>,
+    // Fingerprint of the raw status JSON, for spotting replayed responses
+    pub status_hash: Option<u64>,
 
     // Query full stat (enable-query=true)
     pub world: Option<String>,
@@ -57,6 +71,19 @@ pub struct ServerInfo {
     pub online_mode: Option<bool>,
     pub whitelist: Option<bool>,
 
+    // RCON answering next to a Java server; None = not checked or undetermined
+    pub rcon_exposed: Option<bool>,
+
+    // Honeypot heuristics that fired; empty = looks genuine
+    pub honeypot: Vec<Signal>,
+
+    // Offline GeoIP/ASN lookup (geoip::GeoDb); None without a database or a match
+    pub asn: Option<u32>,
+    pub as_org: Option<String>,
+    // ISO 3166 alpha-2
+    pub country: Option<String>,
+    pub city: Option<String>,
+
     // Bedrock extras (unconnected pong)
     pub bedrock_edition: Option<String>,
     pub server_guid: Option<String>,
@@ -64,6 +91,11 @@ pub struct ServerInfo {
     pub gamemode: Option<String>,
     pub port_v4: Option<u16>,
     pub port_v6: Option<u16>,
+
+    // RakNet OpenConnectionReply1; None = not checked or no real RakNet endpoint
+    pub raknet_protocol: Option<u8>,
+    pub raknet_mtu: Option<u16>,
+    pub raknet_security: Option<bool>,
 }
 
 impl ServerInfo {
@@ -71,7 +103,9 @@ impl ServerInfo {
         Self {
             addr,
             edition,
-            motd: String::new(),
+            endpoints: Vec::new(),
+            hybrid_with: None,
+            motd: Motd::default(),
             version: String::new(),
             protocol: 0,
             online: 0,
@@ -79,41 +113,375 @@ impl ServerInfo {
             latency_ms: 0,
             samples: Vec::new(),
             ping_history: Vec::new(),
+            raw_status: None,
             favicon: None,
             sample_ids: Vec::new(),
             secure_chat: None,
             mods: Vec::new(),
+            status_hash: None,
             world: None,
             plugins: Vec::new(),
             online_mode: None,
             whitelist: None,
+            rcon_exposed: None,
+            honeypot: Vec::new(),
+            asn: None,
+            as_org: None,
+            country: None,
+            city: None,
             bedrock_edition: None,
             server_guid: None,
             sub_motd: None,
             gamemode: None,
             port_v4: None,
             port_v6: None,
+            raknet_protocol: None,
+            raknet_mtu: None,
+            raknet_security: None,
         }
     }
+
+    // RakNet GUIDs are random per server start, so a shared one means one
+    // process. On another address it could as well be a copied pong, so there
+    // the MOTD and the ports each side advertises have to agree too.
+    pub fn same_server(&self, other: &ServerInfo) -> bool {
+        let same_guid = self.edition == Edition::Bedrock
+            && other.edition == Edition::Bedrock
+            && matches!(
+                (&self.server_guid, &other.server_guid),
+                (Some(a), Some(b)) if a == b && a != "0"
+            );
+        same_guid
+            && (self.addr.ip() == other.addr.ip()
+                || self.motd == other.motd
+                    && self.serves_port(other.addr)
+                    && other.serves_port(self.addr))
+    }
+
+    // `addr`'s port is this server's own, or the one it advertises for that family.
+    fn serves_port(&self, addr: SocketAddr) -> bool {
+        let advertised = if addr.is_ipv4() {
+            self.port_v4
+        } else {
+            self.port_v6
+        };
+        addr.port() == self.addr.port() || advertised == Some(addr.port())
+    }
+
+    pub fn add_endpoint(&mut self, addr: SocketAddr) {
+        if addr != self.addr && !self.endpoints.contains(&addr) {
+            self.endpoints.push(addr);
+        }
+    }
+
+    pub fn merge_endpoints(&mut self, other: &ServerInfo) {
+        self.add_endpoint(other.addr);
+        for &e in &other.endpoints {
+            self.add_endpoint(e);
+        }
+    }
+
+    pub fn is_dual_stack(&self) -> bool {
+        let v6 = self.addr.is_ipv6();
+        self.endpoints.iter().any(|e| e.is_ipv6() != v6)
+    }
 }
 
 #[derive(Debug, Clone, Hash)]
 pub struct ScanConfig {
     pub ranges: Vec<IpNet>,
+    // Exact endpoints probed as-is, besides every port of every range
+    pub targets: Vec<Endpoint>,
     pub java_ports: Ports,
     pub bedrock_ports: Ports,
+    // Ranges probed on their own ports instead of the two sets above
+    pub overrides: Vec<PortOverride>,
+    // nmap-style octet patterns, probed on the two sets like ranges
+    pub patterns: Vec<OctetPattern>,
     pub concurrency: Concurrency,
-    pub timeout_ms: TimeoutMs,
+    pub options: ProbeOptions,
+}
+
+/// A range's own port sets; an edition left `None` keeps the scan's ports,
+/// and an empty set skips that edition for the range.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PortOverride {
+    pub net: IpNet,
+    pub java: Option<Ports>,
+    pub bedrock: Option<Ports>,
+}
+
+// The form `parse` reads back: `10.0.0.0/22 java=25565-25665 bedrock=`.
+impl std::fmt::Display for PortOverride {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.net)?;
+        if let Some(java) = &self.java {
+            write!(f, " java={java}")?;
+        }
+        if let Some(bedrock) = &self.bedrock {
+            write!(f, " bedrock={bedrock}")?;
+        }
+        Ok(())
+    }
+}
+
+/// One `ip:port` to probe on its own rather than as part of a range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Endpoint {
+    pub addr: SocketAddr,
+    // None = try both editions
+    pub edition: Option<Edition>,
+}
+
+impl Endpoint {
+    pub fn editions(self) -> impl Iterator<Item = Edition> {
+        match self.edition {
+            Some(edition) => [Some(edition), None],
+            None => [Some(Edition::Java), Some(Edition::Bedrock)],
+        }
+        .into_iter()
+        .flatten()
+    }
+}
+
+// The form `parse` reads back: `1.2.3.4:25570`, `[::1]:19133/bedrock`.
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.edition {
+            None => write!(f, "{}", self.addr),
+            Some(Edition::Java) => write!(f, "{}/java", self.addr),
+            Some(Edition::Bedrock) => write!(f, "{}/bedrock", self.addr),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Timeouts {
+    // TCP connect; UDP probes have no connect step and only use `read`
+    pub connect: Duration,
+    // Each read, or each UDP round trip
+    pub read: Duration,
+}
+
+impl Timeouts {
+    pub fn uniform(dur: Duration) -> Self {
+        Self {
+            connect: dur,
+            read: dur,
+        }
+    }
+}
+
+impl From<TimeoutMs> for Timeouts {
+    fn from(t: TimeoutMs) -> Self {
+        Self::uniform(Duration::from_millis(t.get()))
+    }
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        TimeoutMs::default().into()
+    }
+}
+
+// How each server is probed; the enrichment steps run on top of the status ping.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct ProbeOptions {
+    pub timeouts: Timeouts,
+    pub query_enabled: bool,
+    pub online_mode_check: bool,
+    pub raknet_check: bool,
+    pub rcon_check: bool,
+    // Account for the login probes; None = offline identity
+    pub identity: Option<Credentials>,
+    // Host sent in Java handshakes; None = the target IP. Virtual-host proxies route on it.
+    pub handshake_host: Option<String>,
+    // Keep ServerInfo::raw_status, for fields the parsers don't know
+    pub keep_raw: bool,
+}
+
+impl ProbeOptions {
+    pub fn builder() -> ProbeOptionsBuilder {
+        ProbeOptionsBuilder::default()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ProbeOptionsBuilder(ProbeOptions);
+
+impl ProbeOptionsBuilder {
+    /// Sets both the connect and the read timeout.
+    pub fn timeout(mut self, dur: Duration) -> Self {
+        self.0.timeouts = Timeouts::uniform(dur);
+        self
+    }
+
+    pub fn connect_timeout(mut self, dur: Duration) -> Self {
+        self.0.timeouts.connect = dur;
+        self
+    }
+
+    pub fn read_timeout(mut self, dur: Duration) -> Self {
+        self.0.timeouts.read = dur;
+        self
+    }
+
+    pub fn query(mut self, on: bool) -> Self {
+        self.0.query_enabled = on;
+        self
+    }
+
+    pub fn online_mode_check(mut self, on: bool) -> Self {
+        self.0.online_mode_check = on;
+        self
+    }
+
+    pub fn raknet_check(mut self, on: bool) -> Self {
+        self.0.raknet_check = on;
+        self
+    }
+
+    pub fn rcon_check(mut self, on: bool) -> Self {
+        self.0.rcon_check = on;
+        self
+    }
+
+    pub fn identity(mut self, creds: Credentials) -> Self {
+        self.0.identity = Some(creds);
+        self
+    }
+
+    pub fn handshake_host(mut self, host: impl Into<String>) -> Self {
+        self.0.handshake_host = Some(host.into());
+        self
+    }
+
+    pub fn keep_raw(mut self, on: bool) -> Self {
+        self.0.keep_raw = on;
+        self
+    }
+
+    pub fn build(self) -> ProbeOptions {
+        self.0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LanConfig {
+    pub bedrock_ports: Ports,
+    // How long to listen for Java announcements and Bedrock pongs
+    pub listen: Duration,
+    pub options: ProbeOptions,
 }
 
 impl ScanConfig {
+    pub fn builder() -> ScanConfigBuilder {
+        ScanConfigBuilder::default()
+    }
+
+    /// The Java and Bedrock ports `net` is probed on.
+    pub fn ports_for(&self, net: &IpNet) -> (&Ports, &Ports) {
+        let found = self.overrides.iter().find(|o| o.net == *net);
+        let java = found.and_then(|o| o.java.as_ref());
+        let bedrock = found.and_then(|o| o.bedrock.as_ref());
+        (
+            java.unwrap_or(&self.java_ports),
+            bedrock.unwrap_or(&self.bedrock_ports),
+        )
+    }
+
+    /// The Java ports `ip` was swept on: those of the most specific range with
+    /// its own ports that holds it, else the scan's; none when it was only
+    /// listed as an exact endpoint.
+    pub fn java_ports_at(&self, ip: IpAddr) -> &[u16] {
+        let own = self
+            .overrides
+            .iter()
+            .filter(|o| self.ranges.contains(&o.net) && probed(&o.net).contains(ip))
+            .max_by_key(|o| o.net.prefix_len());
+        if let Some(o) = own {
+            return o.java.as_deref().unwrap_or(&self.java_ports);
+        }
+        let swept = self.ranges.iter().any(|net| net.contains(&ip))
+            || self.patterns.iter().any(|p| p.contains(ip));
+        if swept { &self.java_ports } else { &[] }
+    }
+
+    /// The addresses of `net` another range probes instead; see [`taken_over`].
+    pub fn taken_over(&self, net: &IpNet) -> RangeSet {
+        taken_over(net, &self.overrides)
+    }
+
     pub fn target_count(&self) -> usize {
+        let probes = |net: &IpNet| {
+            let (java, bedrock) = self.ports_for(net);
+            range_host_count(net, &self.taken_over(net))
+                .saturating_mul((java.len() + bedrock.len()) as u128)
+        };
         let ports = (self.java_ports.len() + self.bedrock_ports.len()) as u128;
-        let hosts: u128 = self.ranges.iter().map(host_count).sum();
-        hosts.saturating_mul(ports).min(usize::MAX as u128) as usize
+        let patterns = self.patterns.iter().map(|p| p.host_count() * ports);
+        self.ranges
+            .iter()
+            .map(probes)
+            .chain(patterns)
+            .fold(0u128, u128::saturating_add)
+            .saturating_add(
+                self.targets
+                    .iter()
+                    .map(|t| t.editions().count() as u128)
+                    .sum(),
+            )
+            .min(usize::MAX as u128) as usize
+    }
+}
+
+/// The addresses of `net` that a range with its own ports probes instead, so
+/// each host is probed once: for a plain range, every overridden network
+/// overlapping it; for an overridden one, the overridden networks nested in
+/// it, the most specific range winning. Only the hosts those ranges probe are
+/// taken, so their network and broadcast addresses stay with `net`.
+pub fn taken_over(net: &IpNet, overrides: &[PortOverride]) -> RangeSet {
+    let own = overrides.iter().any(|o| o.net == *net);
+    overrides
+        .iter()
+        .map(|o| o.net)
+        .filter(|o| match own {
+            true => o != net && net.contains(o),
+            false => net.contains(o) || o.contains(net),
+        })
+        .map(|o| probed(&o))
+        .fold(RangeSet::default(), |taken, hosts| taken.union(&hosts))
+        .intersection(&RangeSet::new([*net]))
+}
+
+// The addresses IpNet::hosts() yields for `net`.
+fn probed(net: &IpNet) -> RangeSet {
+    let all = RangeSet::new([*net]);
+    match net {
+        IpNet::V4(n) if n.prefix_len() < 31 => {
+            let edges = [n.network(), n.broadcast()].map(|a| IpNet::from(IpAddr::V4(a)));
+            all.difference(&RangeSet::new(edges))
+        }
+        _ => all,
     }
 }
 
+/// [`host_count`] less the hosts in `taken`, which lies inside `net`.
+pub fn range_host_count(net: &IpNet, taken: &RangeSet) -> u128 {
+    let mut taken_hosts = taken.len();
+    // IpNet::hosts() already skips an IPv4 block's network and broadcast address.
+    if let IpNet::V4(n) = net
+        && n.prefix_len() < 31
+    {
+        let edges = [n.network(), n.broadcast()];
+        taken_hosts -= edges
+            .iter()
+            .filter(|&&a| taken.contains(IpAddr::V4(a)))
+            .count() as u128;
+    }
+    host_count(net).saturating_sub(taken_hosts)
+}
+
 // Matches IpNet::hosts(): IPv4 excludes network+broadcast (except /31, /32).
 pub fn host_count(net: &IpNet) -> u128 {
     match net {
@@ -137,14 +505,66 @@ impl Default for ScanConfig {
     fn default() -> Self {
         Self {
             ranges: vec![],
+            targets: vec![],
             java_ports: Ports::from_input("25565"),
             bedrock_ports: Ports::from_input("19132"),
+            overrides: vec![],
+            patterns: vec![],
             concurrency: Concurrency::default(),
-            timeout_ms: TimeoutMs::default(),
+            options: ProbeOptions::default(),
         }
     }
 }
 
+#[derive(Debug, Clone, Default)]
+pub struct ScanConfigBuilder(ScanConfig);
+
+impl ScanConfigBuilder {
+    pub fn ranges(mut self, ranges: impl IntoIterator<Item = IpNet>) -> Self {
+        self.0.ranges.extend(ranges);
+        self
+    }
+
+    pub fn targets(mut self, targets: impl IntoIterator<Item = Endpoint>) -> Self {
+        self.0.targets.extend(targets);
+        self
+    }
+
+    pub fn java_ports(mut self, ports: Ports) -> Self {
+        self.0.java_ports = ports;
+        self
+    }
+
+    pub fn bedrock_ports(mut self, ports: Ports) -> Self {
+        self.0.bedrock_ports = ports;
+        self
+    }
+
+    pub fn overrides(mut self, overrides: impl IntoIterator<Item = PortOverride>) -> Self {
+        self.0.overrides.extend(overrides);
+        self
+    }
+
+    pub fn patterns(mut self, patterns: impl IntoIterator<Item = OctetPattern>) -> Self {
+        self.0.patterns.extend(patterns);
+        self
+    }
+
+    pub fn concurrency(mut self, concurrency: Concurrency) -> Self {
+        self.0.concurrency = concurrency;
+        self
+    }
+
+    pub fn options(mut self, options: ProbeOptions) -> Self {
+        self.0.options = options;
+        self
+    }
+
+    pub fn build(self) -> ScanConfig {
+        self.0
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -152,13 +572,82 @@ mod tests {
     fn cfg(ranges: &[&str]) -> ScanConfig {
         ScanConfig {
             ranges: ranges.iter().map(|s| s.parse().unwrap()).collect(),
+            targets: vec![],
             java_ports: Ports::from_input("25565"),
             bedrock_ports: Ports::from_input("19132"),
+            overrides: vec![],
+            patterns: vec![],
             concurrency: Concurrency::default(),
-            timeout_ms: TimeoutMs::default(),
+            options: ProbeOptions::default(),
         }
     }
 
+    fn bedrock(addr: &str, guid: Option<&str>) -> ServerInfo {
+        let mut s = ServerInfo::base(addr.parse().unwrap(), Edition::Bedrock);
+        s.server_guid = guid.map(str::to_string);
+        s
+    }
+
+    #[test]
+    fn same_server_needs_a_shared_real_guid() {
+        let a = bedrock("1.2.3.4:19132", Some("42"));
+        assert!(a.same_server(&bedrock("1.2.3.4:19133", Some("42"))));
+        assert!(!a.same_server(&bedrock("1.2.3.4:19133", Some("7"))));
+        assert!(!a.same_server(&bedrock("1.2.3.4:19133", None)));
+        assert!(!bedrock("1.2.3.4:1", Some("0")).same_server(&bedrock("1.2.3.4:2", Some("0"))));
+        let mut java = ServerInfo::base("1.2.3.4:25565".parse().unwrap(), Edition::Java);
+        java.server_guid = Some("42".into());
+        assert!(!a.same_server(&java));
+    }
+
+    #[test]
+    fn same_guid_elsewhere_needs_matching_ports_and_motd() {
+        let mut v4 = bedrock("1.2.3.4:19132", Some("42"));
+        v4.port_v6 = Some(19133);
+        let mut v6 = bedrock("[2001:db8::1]:19133", Some("42"));
+        v6.port_v4 = Some(19132);
+        assert!(v4.same_server(&v6));
+        v6.motd = "Copied".into();
+        assert!(!v4.same_server(&v6));
+        // Another host echoing the GUID on some other port
+        assert!(!v4.same_server(&bedrock("5.6.7.8:25000", Some("42"))));
+        assert!(v4.same_server(&bedrock("5.6.7.8:19132", Some("42"))));
+    }
+
+    #[test]
+    fn merge_endpoints_dedups_and_detects_dual_stack() {
+        let mut a = bedrock("1.2.3.4:19132", Some("42"));
+        let mut b = bedrock("[2001:db8::1]:19133", Some("42"));
+        b.add_endpoint("1.2.3.4:19132".parse().unwrap());
+        assert!(!a.is_dual_stack());
+        a.merge_endpoints(&b);
+        a.merge_endpoints(&b);
+        assert_eq!(a.endpoints, vec!["[2001:db8::1]:19133".parse().unwrap()]);
+        assert!(a.is_dual_stack());
+    }
+
+    #[test]
+    fn builders_fill_in_defaults() {
+        let options = ProbeOptions::builder()
+            .timeout(Duration::from_secs(2))
+            .read_timeout(Duration::from_secs(5))
+            .query(true)
+            .handshake_host("play.example.com")
+            .build();
+        assert_eq!(options.timeouts.connect, Duration::from_secs(2));
+        assert_eq!(options.timeouts.read, Duration::from_secs(5));
+        assert!(options.query_enabled && !options.rcon_check);
+        assert_eq!(options.handshake_host.as_deref(), Some("play.example.com"));
+
+        let config = ScanConfig::builder()
+            .ranges(["10.0.0.0/24".parse().unwrap()])
+            .options(options.clone())
+            .build();
+        assert_eq!(config.java_ports.to_vec(), [25565]);
+        assert_eq!(config.options, options);
+        assert_eq!(config.target_count(), 254 * 2);
+    }
+
     #[test]
     fn host_count_ipv4_matches_hosts_iter() {
         assert_eq!(host_count(&"10.0.0.0/24".parse().unwrap()), 254);
@@ -176,5 +665,59 @@ mod tests {
     fn target_count_multiplies_ports_and_saturates() {
         assert_eq!(cfg(&["10.0.0.0/24"]).target_count(), 254 * 2);
         assert_eq!(cfg(&["2001:db8::/32"]).target_count(), usize::MAX);
+        let mut exact = cfg(&["10.0.0.0/24"]);
+        let addr = "10.1.0.1:25570".parse().unwrap();
+        exact.targets = vec![
+            Endpoint {
+                addr,
+                edition: Some(Edition::Java),
+            },
+            Endpoint {
+                addr,
+                edition: None,
+            },
+        ];
+        assert_eq!(exact.target_count(), 254 * 2 + 1 + 2);
+    }
+
+    #[test]
+    fn patterns_count_on_the_scan_ports() {
+        let mut config = cfg(&[]);
+        config.patterns = vec![OctetPattern::parse("10.0.1-5.*").unwrap()];
+        assert_eq!(config.target_count(), 5 * 256 * 2);
+    }
+
+    #[test]
+    fn java_ports_follow_the_range_that_swept_the_host() {
+        let mut config = cfg(&["10.0.0.0/16", "10.0.1.0/24"]);
+        config.overrides = vec![PortOverride {
+            net: "10.0.1.0/24".parse().unwrap(),
+            java: Some(Ports::from_input("25565-25567")),
+            bedrock: None,
+        }];
+        let ports = |ip: &str| config.java_ports_at(ip.parse().unwrap()).to_vec();
+        assert_eq!(ports("10.0.1.9"), [25565, 25566, 25567]);
+        assert_eq!(ports("10.0.2.9"), [25565]);
+        assert!(ports("10.9.0.1").is_empty());
+        // The /24 skips its network and broadcast addresses; the /16 probes them.
+        assert_eq!(ports("10.0.1.0"), [25565]);
+        assert_eq!(ports("10.0.1.255"), [25565]);
+    }
+
+    #[test]
+    fn overrides_replace_ports_per_range() {
+        let mut config = cfg(&["10.0.0.0/22", "10.1.0.0/24"]);
+        config.overrides = vec![PortOverride {
+            net: "10.0.0.0/22".parse().unwrap(),
+            java: Some(Ports::from_input("25565-25665")),
+            bedrock: Some(Ports::default()),
+        }];
+        let (java, bedrock) = config.ports_for(&"10.0.0.0/22".parse().unwrap());
+        assert_eq!((java.len(), bedrock.len()), (101, 0));
+        assert_eq!(config.target_count(), 1022 * 101 + 254 * 2);
+        assert_eq!(
+            config.overrides[0].to_string(),
+            "10.0.0.0/22 java=25565-25665 bedrock="
+        );
     }
 }
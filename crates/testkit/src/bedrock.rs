@@ -0,0 +1,110 @@
+use crate::Running;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time::sleep;
+
+const MAGIC: [u8; 16] = [
+    0x00, 0xFF, 0xFF, 0x00, 0xFE, 0xFE, 0xFE, 0xFE, 0xFD, 0xFD, 0xFD, 0xFD, 0x12, 0x34, 0x56, 0x78,
+];
+
+/// A Bedrock server's RakNet listener: unconnected pings and, optionally,
+/// Open Connection Request 1.
+#[derive(Debug, Clone)]
+pub struct BedrockServer {
+    motd: String,
+    guid: u64,
+    raw_pong: Option<Vec<u8>>,
+    raknet: bool,
+    delay: Duration,
+}
+
+impl BedrockServer {
+    /// Answers pings with `motd` (`MCPE;name;protocol;version;online;max;guid;...`).
+    pub fn new(motd: &str) -> Self {
+        Self {
+            motd: motd.into(),
+            guid: 0x0123_4567_89AB_CDEF,
+            raw_pong: None,
+            raknet: false,
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Answers pings with `bytes` as they are.
+    pub fn raw_pong(bytes: impl Into<Vec<u8>>) -> Self {
+        Self {
+            raw_pong: Some(bytes.into()),
+            ..Self::new("")
+        }
+    }
+
+    pub fn guid(mut self, guid: u64) -> Self {
+        self.guid = guid;
+        self
+    }
+
+    /// Also answers Open Connection Request 1, like a real RakNet stack; pong
+    /// reflectors and honeypots usually don't.
+    pub fn raknet(mut self, raknet: bool) -> Self {
+        self.raknet = raknet;
+        self
+    }
+
+    /// Waits this long before every reply.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub async fn spawn(self) -> io::Result<Running> {
+        let socket = UdpSocket::bind("127.0.0.1:0").await?;
+        let addr = socket.local_addr()?;
+        let socket = Arc::new(socket);
+        let server = Arc::new(self);
+        let task = tokio::spawn(async move {
+            let mut buf = [0u8; 2048];
+            while let Ok((n, peer)) = socket.recv_from(&mut buf).await {
+                let Some(reply) = server.reply(&buf[..n]) else {
+                    continue;
+                };
+                let socket = socket.clone();
+                let delay = server.delay;
+                tokio::spawn(async move {
+                    sleep(delay).await;
+                    let _ = socket.send_to(&reply, peer).await;
+                });
+            }
+        });
+        Ok(Running { addr, task })
+    }
+
+    fn reply(&self, req: &[u8]) -> Option<Vec<u8>> {
+        match *req.first()? {
+            // Unconnected ping: id + time(8) + MAGIC + client guid(8)
+            0x01 | 0x02 => {
+                if let Some(raw) = &self.raw_pong {
+                    return Some(raw.clone());
+                }
+                let mut out = vec![0x1C];
+                out.extend_from_slice(req.get(1..9)?);
+                out.extend_from_slice(&self.guid.to_be_bytes());
+                out.extend_from_slice(&MAGIC);
+                out.extend_from_slice(&(self.motd.len() as u16).to_be_bytes());
+                out.extend_from_slice(self.motd.as_bytes());
+                Some(out)
+            }
+            // OCR1: id + MAGIC + protocol + zero padding up to the MTU
+            0x05 if self.raknet => {
+                let mut out = vec![0x06];
+                out.extend_from_slice(&MAGIC);
+                out.extend_from_slice(&self.guid.to_be_bytes());
+                out.push(0x00); // no security
+                out.extend_from_slice(&(req.len() as u16 + 28).to_be_bytes());
+                Some(out)
+            }
+            _ => None,
+        }
+    }
+}
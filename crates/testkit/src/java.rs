@@ -0,0 +1,269 @@
+use crate::{Running, frame, read_varint, write_string, write_varint};
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::{sleep, timeout};
+
+// Larger packets than any client sends before login; guards the test process.
+const MAX_PACKET: usize = 64 * 1024;
+
+/// What a login attempt is told after Login Start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Login {
+    /// Login Success, as an offline-mode server sends it.
+    Success,
+    /// Encryption Request, as an online-mode server sends it.
+    EncryptionRequest,
+    /// Set Compression with this threshold, as offline servers behind a proxy do.
+    Compression(i32),
+    /// Disconnect with this JSON text component.
+    Disconnect(String),
+}
+
+impl Login {
+    /// The vanilla whitelist kick.
+    pub fn not_whitelisted() -> Self {
+        Self::Disconnect(r#"{"translate":"multiplayer.disconnect.not_whitelisted"}"#.into())
+    }
+}
+
+/// Fields of the pre-1.7 server list ping reply (`0xFE 0x01`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Legacy {
+    pub protocol: i32,
+    pub version: String,
+    pub motd: String,
+    pub online: u32,
+    pub max: u32,
+}
+
+/// A Java Edition server: status, ping, legacy ping and the first login packet.
+#[derive(Debug, Clone)]
+pub struct JavaServer {
+    // Written verbatim after the status request
+    status: Vec<u8>,
+    legacy: Option<Legacy>,
+    login: Login,
+    delay: Duration,
+}
+
+impl JavaServer {
+    /// Answers status requests with `json`.
+    pub fn new(json: &str) -> Self {
+        let mut payload = vec![0x00];
+        write_string(&mut payload, json);
+        Self {
+            status: frame(payload),
+            legacy: None,
+            login: Login::Success,
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Answers status requests with `bytes` as they are, framing included.
+    pub fn raw_status(bytes: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status: bytes.into(),
+            ..Self::new("")
+        }
+    }
+
+    /// Answers the legacy ping; without it the connection is dropped.
+    pub fn legacy(mut self, legacy: Legacy) -> Self {
+        self.legacy = Some(legacy);
+        self
+    }
+
+    pub fn login(mut self, login: Login) -> Self {
+        self.login = login;
+        self
+    }
+
+    /// Waits this long before every reply.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub async fn spawn(self) -> io::Result<Running> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = Arc::new(self);
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let server = server.clone();
+                tokio::spawn(async move {
+                    let _ = server.serve(stream).await;
+                });
+            }
+        });
+        Ok(Running { addr, task })
+    }
+
+    async fn serve(&self, mut stream: TcpStream) -> io::Result<()> {
+        let first = stream.read_u8().await?;
+        if first == 0xFE {
+            let Some(legacy) = &self.legacy else {
+                return Ok(());
+            };
+            sleep(self.delay).await;
+            stream.write_all(&legacy_reply(legacy)).await?;
+            // 1.6 clients send more after 0xFE 0x01; closing with it unread
+            // would reset the connection before the client reads the reply.
+            stream.shutdown().await?;
+            let _ = timeout(Duration::from_secs(1), stream.read_to_end(&mut Vec::new())).await;
+            return Ok(());
+        }
+
+        let handshake = read_packet(&mut stream, Some(first)).await?;
+        let mut cur = &handshake[..];
+        let next_state = (|| {
+            read_varint(&mut cur)?; // packet id
+            read_varint(&mut cur)?; // protocol
+            let host_len = read_varint(&mut cur)? as usize;
+            cur = cur.get(host_len + 2..)?; // host + port
+            read_varint(&mut cur)
+        })()
+        .ok_or(io::ErrorKind::InvalidData)?;
+
+        // Status request or Login Start; neither body matters here.
+        read_packet(&mut stream, None).await?;
+        sleep(self.delay).await;
+        if next_state != 1 {
+            return stream.write_all(&login_reply(&self.login)).await;
+        }
+        stream.write_all(&self.status).await?;
+
+        // Ping: echoed back as the pong.
+        let ping = read_packet(&mut stream, None).await?;
+        sleep(self.delay).await;
+        stream.write_all(&frame(ping)).await
+    }
+}
+
+async fn read_packet(stream: &mut TcpStream, first: Option<u8>) -> io::Result<Vec<u8>> {
+    let mut header = Vec::with_capacity(5);
+    if let Some(b) = first {
+        header.push(b);
+    }
+    while header.last().is_none_or(|b| b & 0x80 != 0) {
+        if header.len() == 5 {
+            return Err(io::ErrorKind::InvalidData.into());
+        }
+        header.push(stream.read_u8().await?);
+    }
+    let len = read_varint(&mut &header[..]).ok_or(io::ErrorKind::InvalidData)?;
+    let len = usize::try_from(len).map_err(|_| io::ErrorKind::InvalidData)?;
+    if len > MAX_PACKET {
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+    let mut payload = vec![0u8; len];
+    stream.read_exact(&mut payload).await?;
+    Ok(payload)
+}
+
+// 0xFF kick with `§1\0protocol\0version\0motd\0online\0max` in UTF-16BE.
+fn legacy_reply(legacy: &Legacy) -> Vec<u8> {
+    let text = format!(
+        "§1\0{}\0{}\0{}\0{}\0{}",
+        legacy.protocol, legacy.version, legacy.motd, legacy.online, legacy.max
+    );
+    let units: Vec<u16> = text.encode_utf16().collect();
+    let mut out = vec![0xFF];
+    out.extend_from_slice(&(units.len() as u16).to_be_bytes());
+    for unit in units {
+        out.extend_from_slice(&unit.to_be_bytes());
+    }
+    out
+}
+
+// Bodies follow 1.21; the scanner only looks past the packet id for disconnects.
+fn login_reply(login: &Login) -> Vec<u8> {
+    let mut payload = Vec::new();
+    match login {
+        Login::Success => {
+            write_varint(&mut payload, 0x02);
+            payload.extend_from_slice(&[0u8; 16]); // uuid
+            write_string(&mut payload, "testkit");
+            write_varint(&mut payload, 0); // properties
+        }
+        Login::EncryptionRequest => {
+            write_varint(&mut payload, 0x01);
+            write_string(&mut payload, ""); // server id
+            write_varint(&mut payload, 162);
+            payload.extend_from_slice(&[0x30; 162]); // public key, DER-sized
+            write_varint(&mut payload, 4);
+            payload.extend_from_slice(&[1, 2, 3, 4]); // verify token
+            payload.push(0x01); // should authenticate
+        }
+        Login::Compression(threshold) => {
+            write_varint(&mut payload, 0x03);
+            write_varint(&mut payload, *threshold);
+        }
+        Login::Disconnect(reason) => {
+            write_varint(&mut payload, 0x00);
+            write_string(&mut payload, reason);
+        }
+    }
+    frame(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn answers_legacy_ping() {
+        let server = JavaServer::new("{}")
+            .legacy(Legacy {
+                protocol: 127,
+                version: "1.21".into(),
+                motd: "Old friend".into(),
+                online: 2,
+                max: 10,
+            })
+            .spawn()
+            .await
+            .unwrap();
+        let mut client = TcpStream::connect(server.addr()).await.unwrap();
+        client.write_all(&[0xFE, 0x01]).await.unwrap();
+        let mut reply = Vec::new();
+        client.read_to_end(&mut reply).await.unwrap();
+
+        assert_eq!(reply[0], 0xFF);
+        let units: Vec<u16> = reply[3..]
+            .chunks(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect();
+        assert_eq!(
+            u16::from_be_bytes([reply[1], reply[2]]) as usize,
+            units.len()
+        );
+        let text = String::from_utf16(&units).unwrap();
+        assert_eq!(
+            text.split('\0').collect::<Vec<_>>(),
+            ["§1", "127", "1.21", "Old friend", "2", "10"]
+        );
+    }
+
+    #[tokio::test]
+    async fn echoes_the_status_ping() {
+        let server = JavaServer::new("{}").spawn().await.unwrap();
+        let mut client = TcpStream::connect(server.addr()).await.unwrap();
+        let mut handshake = vec![0x00];
+        write_varint(&mut handshake, 767);
+        write_string(&mut handshake, "localhost");
+        handshake.extend_from_slice(&25565u16.to_be_bytes());
+        write_varint(&mut handshake, 1);
+        client.write_all(&frame(handshake)).await.unwrap();
+        client.write_all(&[0x01, 0x00]).await.unwrap();
+
+        let status = read_packet(&mut client, None).await.unwrap();
+        assert_eq!(status, [0x00, 0x02, b'{', b'}']);
+        let ping = [0x01, 0, 0, 0, 0, 0, 0, 0, 42];
+        client.write_all(&frame(ping.to_vec())).await.unwrap();
+        assert_eq!(read_packet(&mut client, None).await.unwrap(), ping);
+    }
+}
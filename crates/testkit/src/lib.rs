@@ -0,0 +1,76 @@
+//! Fake Minecraft servers for integration tests. Each one binds an ephemeral
+//! port on 127.0.0.1, answers probes the way the real software does and stops
+//! when its [`Running`] handle is dropped. Replies can be delayed or replaced
+//! with raw bytes to reproduce odd servers met in the wild.
+
+mod bedrock;
+mod java;
+mod query;
+
+pub use bedrock::BedrockServer;
+pub use java::{JavaServer, Legacy, Login};
+pub use query::QueryServer;
+
+use std::net::SocketAddr;
+use tokio::task::JoinHandle;
+
+/// A fake server accepting probes until dropped.
+#[derive(Debug)]
+pub struct Running {
+    addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl Running {
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+}
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn write_varint(buf: &mut Vec<u8>, value: i32) {
+    let mut v = value as u32;
+    loop {
+        if v & !0x7F == 0 {
+            buf.push(v as u8);
+            return;
+        }
+        buf.push((v & 0x7F | 0x80) as u8);
+        v >>= 7;
+    }
+}
+
+fn write_string(buf: &mut Vec<u8>, s: &str) {
+    write_varint(buf, s.len() as i32);
+    buf.extend_from_slice(s.as_bytes());
+}
+
+fn read_varint(buf: &mut &[u8]) -> Option<i32> {
+    let mut result = 0i32;
+    let mut shift = 0u32;
+    loop {
+        let (&b, rest) = buf.split_first()?;
+        *buf = rest;
+        result |= ((b & 0x7F) as i32) << shift;
+        if b & 0x80 == 0 {
+            return Some(result);
+        }
+        shift += 7;
+        if shift >= 35 {
+            return None;
+        }
+    }
+}
+
+// Length-prefixed packet: varint(len) + payload.
+fn frame(payload: Vec<u8>) -> Vec<u8> {
+    let mut packet = Vec::with_capacity(payload.len() + 3);
+    write_varint(&mut packet, payload.len() as i32);
+    packet.extend_from_slice(&payload);
+    packet
+}
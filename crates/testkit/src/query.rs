@@ -0,0 +1,187 @@
+use crate::Running;
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::UdpSocket;
+use tokio::time::sleep;
+
+/// A GameSpy4 query listener (`enable-query=true`): challenge, basic and full stat.
+#[derive(Debug, Clone)]
+pub struct QueryServer {
+    token: i32,
+    motd: String,
+    version: String,
+    plugins: String,
+    map: String,
+    max_players: u32,
+    host_port: u16,
+    players: Vec<String>,
+    delay: Duration,
+}
+
+impl Default for QueryServer {
+    fn default() -> Self {
+        Self {
+            token: 9_513_307,
+            motd: "A Minecraft Server".into(),
+            version: "1.21".into(),
+            plugins: String::new(),
+            map: "world".into(),
+            max_players: 20,
+            host_port: 25565,
+            players: Vec::new(),
+            delay: Duration::ZERO,
+        }
+    }
+}
+
+impl QueryServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The challenge token handed out; stat requests carrying another are ignored.
+    pub fn token(mut self, token: i32) -> Self {
+        self.token = token;
+        self
+    }
+
+    pub fn motd(mut self, motd: &str) -> Self {
+        self.motd = motd.into();
+        self
+    }
+
+    pub fn version(mut self, version: &str) -> Self {
+        self.version = version.into();
+        self
+    }
+
+    /// The raw `plugins` value, e.g. `Paper on 1.21: LuckPerms 5.4; Vault 1.7`.
+    pub fn plugins(mut self, plugins: &str) -> Self {
+        self.plugins = plugins.into();
+        self
+    }
+
+    pub fn map(mut self, map: &str) -> Self {
+        self.map = map.into();
+        self
+    }
+
+    pub fn max_players(mut self, max: u32) -> Self {
+        self.max_players = max;
+        self
+    }
+
+    pub fn players<S: Into<String>>(mut self, players: impl IntoIterator<Item = S>) -> Self {
+        self.players = players.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Waits this long before every reply.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub async fn spawn(self) -> io::Result<Running> {
+        self.spawn_on(0).await
+    }
+
+    /// Binds `port`, e.g. a [`crate::JavaServer`]'s, since query shares the
+    /// game port unless `query.port` says otherwise.
+    pub async fn spawn_on(self, port: u16) -> io::Result<Running> {
+        let socket = UdpSocket::bind(("127.0.0.1", port)).await?;
+        let addr = socket.local_addr()?;
+        let socket = Arc::new(socket);
+        let server = Arc::new(self);
+        let task = tokio::spawn(async move {
+            let mut buf = [0u8; 1500];
+            while let Ok((n, peer)) = socket.recv_from(&mut buf).await {
+                let Some(reply) = server.reply(&buf[..n]) else {
+                    continue;
+                };
+                let socket = socket.clone();
+                let delay = server.delay;
+                tokio::spawn(async move {
+                    sleep(delay).await;
+                    let _ = socket.send_to(&reply, peer).await;
+                });
+            }
+        });
+        Ok(Running { addr, task })
+    }
+
+    // magic(FE FD) + type + session(4) [+ token(4) [+ padding(4) for full stat]]
+    fn reply(&self, req: &[u8]) -> Option<Vec<u8>> {
+        let (&kind, rest) = req.strip_prefix(&[0xFE, 0xFD])?.split_first()?;
+        let session = rest.get(..4)?;
+        let mut out = vec![kind];
+        out.extend_from_slice(session);
+        match kind {
+            0x09 => {
+                out.extend_from_slice(self.token.to_string().as_bytes());
+                out.push(0);
+            }
+            0x00 => {
+                let token = i32::from_be_bytes(rest.get(4..8)?.try_into().ok()?);
+                if token != self.token {
+                    return None;
+                }
+                if rest.len() >= 12 {
+                    self.full_stat(&mut out);
+                } else {
+                    self.basic_stat(&mut out);
+                }
+            }
+            _ => return None,
+        }
+        Some(out)
+    }
+
+    fn basic_stat(&self, out: &mut Vec<u8>) {
+        for field in [
+            self.motd.as_str(),
+            "SMP",
+            &self.map,
+            &self.players.len().to_string(),
+            &self.max_players.to_string(),
+        ] {
+            cstr(out, field);
+        }
+        out.extend_from_slice(&self.host_port.to_le_bytes());
+        cstr(out, "127.0.0.1");
+    }
+
+    fn full_stat(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(b"splitnum\x00\x80\x00");
+        let numplayers = self.players.len().to_string();
+        let maxplayers = self.max_players.to_string();
+        let hostport = self.host_port.to_string();
+        for (key, value) in [
+            ("hostname", self.motd.as_str()),
+            ("gametype", "SMP"),
+            ("game_id", "MINECRAFT"),
+            ("version", &self.version),
+            ("plugins", &self.plugins),
+            ("map", &self.map),
+            ("numplayers", &numplayers),
+            ("maxplayers", &maxplayers),
+            ("hostport", &hostport),
+            ("hostip", "127.0.0.1"),
+        ] {
+            cstr(out, key);
+            cstr(out, value);
+        }
+        out.push(0);
+        out.extend_from_slice(b"\x01player_\x00\x00");
+        for name in &self.players {
+            cstr(out, name);
+        }
+        out.push(0);
+    }
+}
+
+fn cstr(out: &mut Vec<u8>, s: &str) {
+    out.extend_from_slice(s.as_bytes());
+    out.push(0);
+}